@@ -136,21 +136,30 @@
 //! [`Error`]: ../struct.Error.html
 
 use crate::codec::{Codec, SendError, UserError};
-use crate::ext::Protocol;
+use crate::ext::{ExtensiblePriority, HeaderOrder, Protocol};
 #[cfg(feature = "unstable")]
 use crate::frame::ExperimentalSettings;
 use crate::frame::{
     Headers, Priorities, Pseudo, PseudoOrder, Reason, Settings, SettingsOrder, StreamDependency,
-    StreamId,
+    StreamId, DEFAULT_INITIAL_WINDOW_SIZE,
 };
+use crate::preface::PREFACE;
 use crate::proto::{self, Error};
-use crate::{FlowControl, PingPong, RecvStream, SendStream};
+use crate::upgrade;
+use crate::{
+    AltSvc, ConnectionStats, EncodingPolicy, ExtensionFrame, Fingerprint, FlowControl,
+    FrameObserver, PingPong, RecvStream, SendStream, SettingsSnapshot, Timer, TokioTimer,
+};
+
+pub use crate::preface::{PrefaceSequence, PrefaceSequenceBuilder};
+pub use crate::profile::{Profile, ProfileBuilder};
 
-use bytes::{Buf, Bytes};
-use http::{uri, HeaderMap, Method, Request, Response, Version};
+use bytes::{Buf, Bytes, BytesMut};
+use http::{uri, HeaderMap, HeaderValue, Method, Request, Response, Version};
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
@@ -180,6 +189,7 @@ use tracing::Instrument;
 pub struct SendRequest<B: Buf> {
     inner: proto::Streams<B, Peer>,
     pending: Option<proto::OpaqueStreamRef>,
+    retain_requests: bool,
 }
 
 /// Returns a `SendRequest` instance once it is ready to send at least one
@@ -243,6 +253,8 @@ pub struct Connection<T, B: Buf = Bytes> {
 pub struct ResponseFuture {
     inner: proto::OpaqueStreamRef,
     push_promise_consumed: bool,
+    /// The request, handed back if the server refuses it.
+    request: Option<Box<Request<Option<Bytes>>>>,
 }
 
 /// A future of a pushed HTTP response.
@@ -274,6 +286,18 @@ pub struct PushPromises {
     inner: proto::OpaqueStreamRef,
 }
 
+/// A stream of the informational (1xx) responses received before the final
+/// response.
+///
+/// Returned by [`ResponseFuture::informational_responses`].
+///
+/// [`ResponseFuture::informational_responses`]: struct.ResponseFuture.html#method.informational_responses
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct InformationalResponses<'a> {
+    inner: &'a mut ResponseFuture,
+}
+
 /// Builds client connections with custom configuration values.
 ///
 /// Methods can be chained in order to set the configuration values.
@@ -315,6 +339,21 @@ pub struct Builder {
     /// Time to keep locally reset streams around before reaping.
     reset_stream_duration: Duration,
 
+    /// Time to wait for the remote to acknowledge our SETTINGS.
+    settings_ack_timeout: Option<Duration>,
+
+    /// Time without receiving a frame before a keep-alive PING is sent.
+    keep_alive_interval: Option<Duration>,
+
+    /// Time to wait for the acknowledgement of a keep-alive PING.
+    keep_alive_timeout: Duration,
+
+    /// Whether keep-alive PINGs are sent while there are no open streams.
+    keep_alive_while_idle: bool,
+
+    /// Timer used for timeouts and keep-alive intervals.
+    timer: Arc<dyn Timer>,
+
     /// Initial maximum number of locally initiated (send) streams.
     /// After receiving a SETTINGS frame from the remote peer,
     /// the connection will overwrite this value with the
@@ -326,6 +365,9 @@ pub struct Builder {
     /// Initial target window size for new connections.
     initial_target_connection_window_size: Option<u32>,
 
+    /// Maximum window size when the windows adapt to the connection.
+    adaptive_window: Option<u32>,
+
     /// Maximum amount of bytes to "buffer" for writing per stream.
     max_send_buffer_size: usize,
 
@@ -357,6 +399,18 @@ pub struct Builder {
 
     /// Priority stream list
     priorities: Option<Priorities>,
+
+    /// Policy deciding how header fields are HPACK encoded
+    encoding_policy: Option<Arc<dyn EncodingPolicy>>,
+
+    /// Frames sent right after the connection preface
+    preface_sequence: Option<PrefaceSequence>,
+
+    /// Extension frame types delivered to the application
+    extension_frame_types: Vec<u8>,
+
+    /// Called with each frame sent and received
+    frame_observer: Option<Arc<dyn FrameObserver>>,
 }
 
 #[derive(Debug)]
@@ -450,6 +504,23 @@ where
     /// The caller should always set the request's version field to 2.0 unless
     /// specifically transmitting an HTTP 1.1 request over 2.0.
     ///
+    /// # Per-request HEADERS encoding
+    ///
+    /// A `PseudoOrder` or `StreamDependency` found in the request extensions
+    /// is used for this request's HEADERS frame instead of the values set with
+    /// [`Builder::headers_pseudo_order`] and
+    /// [`Builder::headers_stream_dependency`].
+    ///
+    /// A [`HeaderOrder`] in the request extensions sets the order in which the
+    /// header fields are encoded.
+    ///
+    /// # Retrying refused requests
+    ///
+    /// If the server refuses the request, with a `GOAWAY` or a
+    /// `REFUSED_STREAM` reset, the returned error is [retryable]. When this
+    /// `SendRequest` [retains requests], the request is handed back by
+    /// [`Error::take_request`], so it can be sent again on a new connection.
+    ///
     /// # Examples
     ///
     /// Sending a request with no body
@@ -523,14 +594,26 @@ where
     /// [`SendStream`]: ../struct.SendStream.html
     /// [`SendStream::send_data`]: ../struct.SendStream.html#method.send_data
     /// [`SendStream::send_trailers`]: ../struct.SendStream.html#method.send_trailers
+    /// [`Builder::headers_pseudo_order`]: struct.Builder.html#method.headers_pseudo_order
+    /// [`Builder::headers_stream_dependency`]: struct.Builder.html#method.headers_stream_dependency
+    /// [`HeaderOrder`]: ../ext/struct.HeaderOrder.html
+    /// [retryable]: ../struct.Error.html#method.is_retryable
+    /// [retains requests]: #method.set_retain_requests
+    /// [`Error::take_request`]: ../struct.Error.html#method.take_request
     pub fn send_request(
         &mut self,
         request: Request<()>,
         end_of_stream: bool,
     ) -> Result<(ResponseFuture, SendStream<B>), crate::Error> {
+        let retained = if self.retain_requests {
+            Some(retain_request(&request))
+        } else {
+            None
+        };
+
         self.inner
             .send_request(request, end_of_stream, self.pending.as_ref())
-            .map_err(Into::into)
+            .map_err(|e| crate::Error::from(e).with_request(retained.clone()))
             .map(|(stream, is_full)| {
                 if stream.is_pending_open() && is_full {
                     // Only prevent sending another request when the request queue
@@ -541,6 +624,7 @@ where
                 let response = ResponseFuture {
                     inner: stream.clone_to_opaque(),
                     push_promise_consumed: false,
+                    request: retained,
                 };
 
                 let stream = SendStream::new(stream);
@@ -549,6 +633,49 @@ where
             })
     }
 
+    /// Sends a PRIORITY_UPDATE frame, changing the priority of a request.
+    ///
+    /// `stream_id` may refer to a request that has not been sent yet, as
+    /// allowed by [RFC 9218].
+    ///
+    /// See [`ExtensiblePriority`] for how the initial priority of a request is
+    /// signaled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `stream_id` is not a client initiated stream ID.
+    ///
+    /// [RFC 9218]: https://www.rfc-editor.org/rfc/rfc9218#section-7.1
+    /// [`ExtensiblePriority`]: ../ext/struct.ExtensiblePriority.html
+    pub fn send_priority_update(
+        &mut self,
+        stream_id: crate::StreamId,
+        priority: &ExtensiblePriority,
+    ) -> Result<(), crate::Error> {
+        self.inner
+            .send_priority_update(stream_id.as_u32().into(), priority)
+            .map_err(Into::into)
+    }
+
+    /// Sets whether requests sent with this handle are retained, to be handed
+    /// back if the server refuses them.
+    ///
+    /// The method, URI, version, headers and extensions of each request are
+    /// cloned when it is sent. If the request then fails with a [retryable]
+    /// error, the clone is returned by [`Error::take_request`]. A body to hand
+    /// back with the request can be set with
+    /// [`ResponseFuture::set_replay_body`].
+    ///
+    /// Clones of this handle retain requests if this handle does. This is
+    /// disabled by default.
+    ///
+    /// [retryable]: ../struct.Error.html#method.is_retryable
+    /// [`Error::take_request`]: ../struct.Error.html#method.take_request
+    /// [`ResponseFuture::set_replay_body`]: struct.ResponseFuture.html#method.set_replay_body
+    pub fn set_retain_requests(&mut self, enabled: bool) {
+        self.retain_requests = enabled;
+    }
+
     /// Returns whether the [extended CONNECT protocol][1] is enabled or not.
     ///
     /// This setting is configured by the server peer by sending the
@@ -562,6 +689,38 @@ where
         self.inner.is_extended_connect_protocol_enabled()
     }
 
+    /// Returns true if the server listed the `https` origin of `authority` in
+    /// an ORIGIN frame.
+    ///
+    /// The origin the connection was established for is not part of this set,
+    /// only the origins received in ORIGIN frames are. A request for an origin
+    /// of this set may be sent on this connection instead of a new one.
+    ///
+    /// See [RFC 8336] for details.
+    ///
+    /// [RFC 8336]: https://www.rfc-editor.org/rfc/rfc8336#section-2.3
+    pub fn is_authoritative_for(&self, authority: &uri::Authority) -> bool {
+        self.inner.is_authoritative_for(authority)
+    }
+
+    /// Returns the SETTINGS sent by the server.
+    ///
+    /// See [`Connection::remote_settings`] for details.
+    ///
+    /// [`Connection::remote_settings`]: struct.Connection.html#method.remote_settings
+    pub fn remote_settings(&self) -> SettingsSnapshot {
+        self.inner.remote_settings()
+    }
+
+    /// Returns the local SETTINGS acknowledged by the server.
+    ///
+    /// See [`Connection::local_settings`] for details.
+    ///
+    /// [`Connection::local_settings`]: struct.Connection.html#method.local_settings
+    pub fn local_settings(&self) -> SettingsSnapshot {
+        self.inner.local_settings()
+    }
+
     /// Returns the current max send streams
     pub fn current_max_send_streams(&self) -> usize {
         self.inner.current_max_send_streams()
@@ -590,6 +749,7 @@ where
         SendRequest {
             inner: self.inner.clone(),
             pending: None,
+            retain_requests: self.retain_requests,
         }
     }
 }
@@ -617,6 +777,17 @@ where
     }
 }
 
+/// Clones the parts of a request which are handed back if it is refused.
+fn retain_request(request: &Request<()>) -> Box<Request<Option<Bytes>>> {
+    let mut retained = Request::new(None);
+    *retained.method_mut() = request.method().clone();
+    *retained.uri_mut() = request.uri().clone();
+    *retained.version_mut() = request.version();
+    *retained.headers_mut() = request.headers().clone();
+    *retained.extensions_mut() = request.extensions().clone();
+    Box::new(retained)
+}
+
 // ===== impl ReadySendRequest =====
 
 impl<B> Future for ReadySendRequest<B>
@@ -670,9 +841,15 @@ impl Builder {
         Builder {
             max_send_buffer_size: proto::DEFAULT_MAX_SEND_BUFFER_SIZE,
             reset_stream_duration: Duration::from_secs(proto::DEFAULT_RESET_STREAM_SECS),
+            settings_ack_timeout: None,
+            keep_alive_interval: None,
+            keep_alive_timeout: Duration::from_secs(proto::DEFAULT_KEEP_ALIVE_TIMEOUT_SECS),
+            keep_alive_while_idle: false,
+            timer: Arc::new(TokioTimer),
             reset_stream_max: proto::DEFAULT_RESET_STREAM_MAX,
             pending_accept_reset_stream_max: proto::DEFAULT_REMOTE_RESET_STREAM_MAX,
             initial_target_connection_window_size: None,
+            adaptive_window: None,
             initial_max_send_streams: usize::MAX,
             settings: Default::default(),
             stream_id: 1.into(),
@@ -680,6 +857,10 @@ impl Builder {
             headers_pseudo_order: None,
             headers_stream_dependency: None,
             priorities: None,
+            encoding_policy: None,
+            preface_sequence: None,
+            extension_frame_types: Vec::new(),
+            frame_observer: None,
         }
     }
 
@@ -753,6 +934,51 @@ impl Builder {
         self
     }
 
+    /// Enables flow control windows which adapt to the connection.
+    ///
+    /// The bandwidth-delay product of the connection is estimated from the
+    /// round trip time of PING frames sent while DATA is received. When the
+    /// receive windows limit the throughput, the stream and connection
+    /// windows are increased together, by sending a SETTINGS frame with a new
+    /// initial window size and WINDOW_UPDATE frames, up to `max_window_size`.
+    ///
+    /// The estimate starts at the [`initial_window_size`], and each window is
+    /// only increased once the estimate exceeds it: a larger
+    /// [`initial_connection_window_size`] is kept until the estimate grows
+    /// past it, and windows are never decreased. While the new SETTINGS
+    /// frame is waiting for an acknowledgement, `set_initial_window_size` on
+    /// the connection returns an error.
+    ///
+    /// By default, the windows do not adapt.
+    ///
+    /// [`initial_window_size`]: #method.initial_window_size
+    /// [`initial_connection_window_size`]: #method.initial_connection_window_size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::client::*;
+    /// # use bytes::Bytes;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Result<((SendRequest<Bytes>, Connection<T, Bytes>)), h2::Error>
+    /// # {
+    /// // `client_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let client_fut = Builder::new()
+    ///     .adaptive_window(8 * 1024 * 1024)
+    ///     .handshake(my_io);
+    /// # client_fut.await
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn adaptive_window(&mut self, max_window_size: u32) -> &mut Self {
+        self.adaptive_window = Some(max_window_size.min(proto::MAX_WINDOW_SIZE));
+        self
+    }
+
     /// Indicates the size (in octets) of the largest HTTP/2 frame payload that the
     /// configured client is able to accept.
     ///
@@ -1013,6 +1239,134 @@ impl Builder {
         self
     }
 
+    /// Sets the maximum time to wait for the remote to acknowledge local
+    /// SETTINGS.
+    ///
+    /// Each SETTINGS frame sent by the connection, including the initial one
+    /// sent during the handshake, must be acknowledged by the remote. If the
+    /// acknowledgement does not arrive within `dur`, the connection is closed
+    /// with a GOAWAY frame carrying `SETTINGS_TIMEOUT`, and the connection
+    /// future returns an error for which [`Error::is_settings_timeout`]
+    /// returns `true`.
+    ///
+    /// The timeout is waited on with the [`timer`](#method.timer). With the
+    /// default [`TokioTimer`], the connection must then be polled within a
+    /// Tokio runtime with the time driver enabled, or it panics once the
+    /// timeout starts. By default, there is no timeout.
+    ///
+    /// [`Error::is_settings_timeout`]: ../struct.Error.html#method.is_settings_timeout
+    /// [`TokioTimer`]: ../struct.TokioTimer.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::client::*;
+    /// # use std::time::Duration;
+    /// # use bytes::Bytes;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Result<((SendRequest<Bytes>, Connection<T, Bytes>)), h2::Error>
+    /// # {
+    /// // `client_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let client_fut = Builder::new()
+    ///     .settings_ack_timeout(Duration::from_secs(10))
+    ///     .handshake(my_io);
+    /// # client_fut.await
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn settings_ack_timeout(&mut self, dur: Duration) -> &mut Self {
+        self.settings_ack_timeout = Some(dur);
+        self
+    }
+
+    /// Sets the interval at which keep-alive PINGs are sent.
+    ///
+    /// When no frame is received from the remote for `interval`, a PING is
+    /// sent. If the remote does not acknowledge it within the
+    /// [`keep_alive_timeout`], the connection is closed and fails with an
+    /// error for which [`Error::is_keep_alive_timeout`] returns `true`.
+    ///
+    /// The interval and the timeout are waited on with the
+    /// [`timer`](#method.timer). With the default [`TokioTimer`], the
+    /// connection must then be polled within a Tokio runtime with the time
+    /// driver enabled, or it panics. By default, keep-alive PINGs are not
+    /// sent.
+    ///
+    /// [`keep_alive_timeout`]: #method.keep_alive_timeout
+    /// [`Error::is_keep_alive_timeout`]: ../struct.Error.html#method.is_keep_alive_timeout
+    /// [`TokioTimer`]: ../struct.TokioTimer.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::client::*;
+    /// # use std::time::Duration;
+    /// # use bytes::Bytes;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Result<((SendRequest<Bytes>, Connection<T, Bytes>)), h2::Error>
+    /// # {
+    /// // `client_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let client_fut = Builder::new()
+    ///     .keep_alive_interval(Duration::from_secs(30))
+    ///     .keep_alive_timeout(Duration::from_secs(10))
+    ///     .handshake(my_io);
+    /// # client_fut.await
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn keep_alive_interval(&mut self, interval: Duration) -> &mut Self {
+        self.keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Sets the time to wait for the acknowledgement of a keep-alive PING.
+    ///
+    /// Only used when a [`keep_alive_interval`] is set.
+    ///
+    /// The default value is currently 20 seconds.
+    ///
+    /// [`keep_alive_interval`]: #method.keep_alive_interval
+    pub fn keep_alive_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.keep_alive_timeout = timeout;
+        self
+    }
+
+    /// Sets whether keep-alive PINGs are sent while there are no open streams.
+    ///
+    /// Only used when a [`keep_alive_interval`] is set.
+    ///
+    /// The default value is `false`.
+    ///
+    /// [`keep_alive_interval`]: #method.keep_alive_interval
+    pub fn keep_alive_while_idle(&mut self, enabled: bool) -> &mut Self {
+        self.keep_alive_while_idle = enabled;
+        self
+    }
+
+    /// Sets the [`Timer`] used to wait for timeouts and keep-alive intervals.
+    ///
+    /// The default is [`TokioTimer`], which requires the Tokio runtime's time
+    /// driver. The timer is only used when a [`settings_ack_timeout`] or a
+    /// [`keep_alive_interval`] is set, so connections without them do not
+    /// need the time driver.
+    ///
+    /// [`settings_ack_timeout`]: #method.settings_ack_timeout
+    /// [`keep_alive_interval`]: #method.keep_alive_interval
+    /// [`Timer`]: ../trait.Timer.html
+    /// [`TokioTimer`]: ../struct.TokioTimer.html
+    pub fn timer<T: Timer>(&mut self, timer: T) -> &mut Self {
+        self.timer = Arc::new(timer);
+        self
+    }
+
     /// Sets the maximum number of local resets due to protocol errors made by the remote end.
     ///
     /// Invalid frames and many other protocol errors will lead to resets being generated for those streams.
@@ -1219,7 +1573,7 @@ impl Builder {
     /// are encoded in the HEADERS frame. Customizing the order may be useful for interoperability
     /// or testing purposes.
     pub fn headers_pseudo_order(&mut self, order: PseudoOrder) -> &mut Self {
-        self.headers_pseudo_order = Some(order.into());
+        self.headers_pseudo_order = Some(order);
         self
     }
 
@@ -1247,6 +1601,248 @@ impl Builder {
         self
     }
 
+    /// Sets the policy deciding how header fields are HPACK encoded.
+    ///
+    /// The policy chooses, for every header field, whether it is inserted into
+    /// the dynamic table and whether its strings are Huffman encoded. See
+    /// [`EncodingPolicy`] for details, and [`ChromePolicy`] and
+    /// [`FirefoxPolicy`] for policies mimicking browsers.
+    ///
+    /// By default, h2 uses its own heuristics.
+    ///
+    /// [`EncodingPolicy`]: ../trait.EncodingPolicy.html
+    /// [`ChromePolicy`]: ../struct.ChromePolicy.html
+    /// [`FirefoxPolicy`]: ../struct.FirefoxPolicy.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::client::*;
+    /// # use bytes::Bytes;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Result<((SendRequest<Bytes>, Connection<T, Bytes>)), h2::Error>
+    /// # {
+    /// // `client_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let client_fut = Builder::new()
+    ///     .encoding_policy(h2::ChromePolicy)
+    ///     .handshake(my_io);
+    /// # client_fut.await
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn encoding_policy<P: EncodingPolicy>(&mut self, policy: P) -> &mut Self {
+        self.encoding_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Registers an extension frame type to receive.
+    ///
+    /// Received frames of this type are returned by
+    /// [`Connection::poll_extension_frame`]. Frames of unregistered unknown
+    /// types are ignored.
+    ///
+    /// [`Connection::poll_extension_frame`]: struct.Connection.html#method.poll_extension_frame
+    ///
+    /// # Panics
+    ///
+    /// Panics if `kind` is a frame type known to h2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::client::*;
+    /// # use bytes::Bytes;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Result<((SendRequest<Bytes>, Connection<T, Bytes>)), h2::Error>
+    /// # {
+    /// // `client_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let client_fut = Builder::new()
+    ///     .extension_frame_type(0xf0)
+    ///     .handshake(my_io);
+    /// # client_fut.await
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn extension_frame_type(&mut self, kind: u8) -> &mut Self {
+        assert!(
+            crate::frame::is_extension_type(kind),
+            "frame type {} is not an extension frame type",
+            kind
+        );
+
+        if !self.extension_frame_types.contains(&kind) {
+            self.extension_frame_types.push(kind);
+        }
+        self
+    }
+
+    /// Sets the [`FrameObserver`] called with each frame sent and received.
+    ///
+    /// The observer sees the frames as they are written to and read from the
+    /// I/O object, including the HPACK decoded header fields of header blocks.
+    ///
+    /// The frames of the [`preface_sequence`](#method.preface_sequence) are
+    /// observed too.
+    ///
+    /// By default, no observer is set and frames are not inspected.
+    ///
+    /// [`FrameObserver`]: ../trait.FrameObserver.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::client::*;
+    /// # use bytes::Bytes;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Result<((SendRequest<Bytes>, Connection<T, Bytes>)), h2::Error>
+    /// # {
+    /// #[derive(Debug)]
+    /// struct Log;
+    ///
+    /// impl h2::FrameObserver for Log {
+    ///     fn on_frame(&self, frame: &h2::ObservedFrame<'_>) {
+    ///         println!("{:?}", frame);
+    ///     }
+    /// }
+    ///
+    /// // `client_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let client_fut = Builder::new()
+    ///     .frame_observer(Log)
+    ///     .handshake(my_io);
+    /// # client_fut.await
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn frame_observer<O: FrameObserver>(&mut self, observer: O) -> &mut Self {
+        self.frame_observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Sets the exact sequence of frames sent after the connection preface.
+    ///
+    /// See [`PrefaceSequence`] for details.
+    ///
+    /// [`PrefaceSequence`]: struct.PrefaceSequence.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::client::*;
+    /// # use bytes::Bytes;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Result<((SendRequest<Bytes>, Connection<T, Bytes>)), h2::Error>
+    /// # {
+    /// // `client_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let client_fut = Builder::new()
+    ///     .initial_connection_window_size(15_728_640)
+    ///     .preface_sequence(
+    ///         PrefaceSequence::builder()
+    ///             .settings()
+    ///             .window_update()
+    ///             .priorities()
+    ///             .build(),
+    ///     )
+    ///     .handshake(my_io);
+    /// # client_fut.await
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn preface_sequence(&mut self, sequence: PrefaceSequence) -> &mut Self {
+        self.preface_sequence = Some(sequence);
+        self
+    }
+
+    /// Applies a connection [`Profile`].
+    ///
+    /// This replaces the initial SETTINGS frame (values and order), the
+    /// connection level WINDOW_UPDATE, the pseudo-header order and stream
+    /// dependency of HEADERS frames, and the PRIORITY frames with the ones of
+    /// `profile`. Options that are not part of a profile, such as
+    /// [`max_concurrent_reset_streams`], are left untouched.
+    ///
+    /// Configuration methods called after this one further adjust the
+    /// profile's values.
+    ///
+    /// [`Profile`]: struct.Profile.html
+    /// [`max_concurrent_reset_streams`]: #method.max_concurrent_reset_streams
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::client::*;
+    /// # use bytes::Bytes;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Result<((SendRequest<Bytes>, Connection<T, Bytes>)), h2::Error>
+    /// # {
+    /// // `client_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let client_fut = Builder::new()
+    ///     .profile(&Profile::firefox())
+    ///     .handshake(my_io);
+    /// # client_fut.await
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn profile(&mut self, profile: &Profile) -> &mut Self {
+        self.settings = profile.settings().clone();
+        self.initial_target_connection_window_size = profile
+            .connection_window_increment()
+            .map(|increment| DEFAULT_INITIAL_WINDOW_SIZE + increment);
+        self.headers_pseudo_order = Some(profile.pseudo_order().clone());
+        self.headers_stream_dependency = profile.stream_dependency();
+        self.priorities = profile.priorities().cloned();
+        self
+    }
+
+    /// Returns the [`Fingerprint`] of the connection preface this builder
+    /// produces.
+    ///
+    /// [`Fingerprint`]: ../struct.Fingerprint.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use h2::client::{Builder, Profile};
+    ///
+    /// let fingerprint = Builder::new().profile(&Profile::chrome()).fingerprint();
+    ///
+    /// assert_eq!(
+    ///     fingerprint.to_string(),
+    ///     "1:65536;2:0;4:6291456;6:262144|15663105|0|m,a,s,p"
+    /// );
+    /// ```
+    pub fn fingerprint(&self) -> Fingerprint {
+        let window_update = self
+            .initial_target_connection_window_size
+            .map(|size| size.saturating_sub(DEFAULT_INITIAL_WINDOW_SIZE));
+
+        Fingerprint::from_parts(
+            &self.settings,
+            window_update,
+            self.priorities.as_ref(),
+            self.headers_pseudo_order.as_ref(),
+        )
+    }
+
     /// Creates a new configured HTTP/2 client backed by `io`.
     ///
     /// It is expected that `io` already be in an appropriate state to commence
@@ -1318,6 +1914,107 @@ impl Builder {
     {
         Connection::handshake2(io, self.clone())
     }
+
+    /// Returns the value of the `HTTP2-Settings` header to send in an
+    /// HTTP/1.1 request upgrading to HTTP/2 over cleartext TCP.
+    ///
+    /// The value encodes the SETTINGS configured on this builder, which are
+    /// sent again in the connection preface once the upgrade succeeds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use h2::client::*;
+    /// let settings = Builder::new()
+    ///     .initial_window_size(1_000_000)
+    ///     .http2_settings_header();
+    ///
+    /// assert_eq!(settings, "AAQAD0JA");
+    /// ```
+    pub fn http2_settings_header(&self) -> HeaderValue {
+        upgrade::encode_settings(&self.settings)
+    }
+
+    /// Creates a new configured HTTP/2 client backed by `io`, upgraded from
+    /// an HTTP/1.1 request with `Upgrade: h2c`.
+    ///
+    /// The caller is responsible for writing the upgrade request to `io`,
+    /// with the [`http2_settings_header`] of this builder, and for reading
+    /// the `101 Switching Protocols` response before calling this function.
+    /// `method` is the method of the upgrade request.
+    ///
+    /// As described in [RFC 7540 §3.2], the request continues as stream 1,
+    /// which is half-closed by the client. The returned [`ResponseFuture`]
+    /// resolves to its response, sent over HTTP/2. The connection preface
+    /// is sent as for [`handshake`], and later requests use stream 3 on.
+    ///
+    /// [`http2_settings_header`]: Builder::http2_settings_header
+    /// [`handshake`]: Builder::handshake
+    /// [RFC 7540 §3.2]: https://datatracker.ietf.org/doc/html/rfc7540#section-3.2
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+    /// # use h2::client::*;
+    /// # use bytes::Bytes;
+    /// # use http::Method;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin + Send + 'static>(mut my_io: T)
+    /// #     -> Result<(), Box<dyn std::error::Error>>
+    /// # {
+    /// let builder = Builder::new();
+    /// let settings = builder.http2_settings_header();
+    ///
+    /// let request = format!(
+    ///     "GET / HTTP/1.1\r\n\
+    ///      Host: example.com\r\n\
+    ///      Connection: Upgrade, HTTP2-Settings\r\n\
+    ///      Upgrade: h2c\r\n\
+    ///      HTTP2-Settings: {}\r\n\r\n",
+    ///     settings.to_str().unwrap(),
+    /// );
+    /// my_io.write_all(request.as_bytes()).await?;
+    /// // ... read the `101 Switching Protocols` response ...
+    ///
+    /// let (send_request, connection, response) = builder
+    ///     .handshake_upgraded::<_, Bytes>(my_io, &Method::GET)
+    ///     .await?;
+    /// tokio::spawn(async move { connection.await });
+    ///
+    /// let response = response.await?;
+    /// # drop(send_request);
+    /// # Ok(())
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn handshake_upgraded<T, B>(
+        &self,
+        io: T,
+        method: &Method,
+    ) -> impl Future<Output = Result<(SendRequest<B>, Connection<T, B>, ResponseFuture), crate::Error>>
+    where
+        T: AsyncRead + AsyncWrite + Unpin,
+        B: Buf,
+    {
+        let mut builder = self.clone();
+        builder.stream_id = 1.into();
+        let is_head = method == Method::HEAD;
+
+        async move {
+            let (mut send_request, connection) = Connection::handshake2(io, builder).await?;
+            let stream = send_request.inner.open_upgraded(is_head)?;
+
+            let response = ResponseFuture {
+                inner: stream.clone_to_opaque(),
+                push_promise_consumed: false,
+                request: None,
+            };
+
+            Ok((send_request, connection, response))
+        }
+    }
 }
 
 impl Default for Builder {
@@ -1380,8 +2077,23 @@ where
 {
     tracing::debug!("binding client connection");
 
-    let msg: &'static [u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
-    io.write_all(msg).await.map_err(crate::Error::from_io)?;
+    io.write_all(PREFACE).await.map_err(crate::Error::from_io)?;
+
+    tracing::debug!("client connection bound");
+
+    Ok(())
+}
+
+async fn write_preface_sequence<T>(io: &mut T, writes: &[BytesMut]) -> Result<(), crate::Error>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    tracing::debug!("binding client connection; writes={}", writes.len());
+
+    for buf in writes {
+        io.write_all(buf).await.map_err(crate::Error::from_io)?;
+        io.flush().await.map_err(crate::Error::from_io)?;
+    }
 
     tracing::debug!("client connection bound");
 
@@ -1397,7 +2109,30 @@ where
         mut io: T,
         builder: Builder,
     ) -> Result<(SendRequest<B>, Connection<T, B>), crate::Error> {
-        bind_connection(&mut io).await?;
+        let window_update = builder
+            .initial_target_connection_window_size
+            .map(|size| size.saturating_sub(DEFAULT_INITIAL_WINDOW_SIZE))
+            .filter(|&increment| increment > 0);
+
+        let sequence = builder.preface_sequence.as_ref();
+        let sent_window_update = sequence.map_or(false, |sequence| sequence.has_window_update());
+        let sent_priorities = sequence.map_or(false, |sequence| sequence.has_priorities());
+
+        let writes = match sequence {
+            Some(sequence) => {
+                let writes = sequence.encode(
+                    &builder.settings,
+                    window_update,
+                    builder.priorities.as_ref(),
+                );
+                write_preface_sequence(&mut io, &writes).await?;
+                writes
+            }
+            None => {
+                bind_connection(&mut io).await?;
+                Vec::new()
+            }
+        };
 
         // Create the codec
         let mut codec = Codec::new(io);
@@ -1410,10 +2145,27 @@ where
             codec.set_max_recv_header_list_size(max as usize);
         }
 
-        // Send initial settings frame
-        codec
-            .buffer(builder.settings.clone().into())
-            .expect("invalid SETTINGS frame");
+        if let Some(policy) = builder.encoding_policy {
+            codec.set_encoding_policy(policy);
+        }
+
+        codec.set_extension_frame_types(builder.extension_frame_types);
+
+        if let Some(observer) = builder.frame_observer {
+            codec.set_frame_observer(observer);
+        }
+
+        // Count and observe the frames of the preface sequence
+        for buf in &writes {
+            codec.record_written(buf.strip_prefix(PREFACE).unwrap_or(buf));
+        }
+
+        // Send initial settings frame, unless the preface sequence already did
+        if sequence.is_none() {
+            codec
+                .buffer(builder.settings.clone().into())
+                .expect("invalid SETTINGS frame");
+        }
 
         let inner = proto::Connection::new(
             codec,
@@ -1422,18 +2174,34 @@ where
                 initial_max_send_streams: builder.initial_max_send_streams,
                 max_send_buffer_size: builder.max_send_buffer_size,
                 reset_stream_duration: builder.reset_stream_duration,
+                settings_ack_timeout: builder.settings_ack_timeout,
+                keep_alive: builder
+                    .keep_alive_interval
+                    .map(|interval| proto::KeepAliveConfig {
+                        interval,
+                        timeout: builder.keep_alive_timeout,
+                        while_idle: builder.keep_alive_while_idle,
+                    }),
+                adaptive_window: builder.adaptive_window,
+                timer: builder.timer.clone(),
                 reset_stream_max: builder.reset_stream_max,
                 remote_reset_stream_max: builder.pending_accept_reset_stream_max,
                 local_error_reset_streams_max: builder.local_max_error_reset_streams,
                 settings: builder.settings.clone(),
                 headers_pseudo_order: builder.headers_pseudo_order,
                 headers_stream_dependency: builder.headers_stream_dependency,
-                priorities: builder.priorities,
+                priorities: if sent_priorities {
+                    None
+                } else {
+                    builder.priorities
+                },
+                dependency_tree_scheduling: false,
             },
         );
         let send_request = SendRequest {
             inner: inner.streams().clone(),
             pending: None,
+            retain_requests: false,
         };
 
         let mut connection = Connection { inner };
@@ -1441,6 +2209,10 @@ where
             connection.set_target_window_size(sz);
         }
 
+        if let (true, Some(increment)) = (sent_window_update, window_update) {
+            connection.inner.sent_connection_window_update(increment);
+        }
+
         Ok((send_request, connection))
     }
 
@@ -1485,6 +2257,43 @@ where
         Ok(())
     }
 
+    /// Sets the connection to a GOAWAY state.
+    ///
+    /// Does not terminate the connection. Must continue being polled to close
+    /// connection.
+    ///
+    /// After flushing the GOAWAY frame, the connection is closed. Any
+    /// outstanding streams do not prevent the connection from closing, and
+    /// fail with an error.
+    ///
+    /// For graceful shutdowns, see [`graceful_shutdown`](Connection::graceful_shutdown).
+    pub fn abrupt_shutdown(&mut self, reason: Reason) {
+        self.inner.go_away_from_user(reason, Bytes::new());
+    }
+
+    /// Sets the connection to a GOAWAY state, with debug data in the GOAWAY
+    /// frame.
+    ///
+    /// See [`abrupt_shutdown`](Connection::abrupt_shutdown).
+    pub fn abrupt_shutdown_with_debug_data(&mut self, reason: Reason, debug_data: Bytes) {
+        self.inner.go_away_from_user(reason, debug_data);
+    }
+
+    /// Starts a [graceful shutdown][1] process.
+    ///
+    /// Must continue being polled to close connection.
+    ///
+    /// A GOAWAY frame is sent to the server, and [`SendRequest::send_request`]
+    /// returns an error from now on. Requests which were already sent are
+    /// still processed. Once all active streams have completed, the
+    /// connection is closed.
+    ///
+    /// [1]: http://httpwg.org/specs/rfc7540.html#GOAWAY
+    pub fn graceful_shutdown(&mut self) {
+        self.inner.refuse_new_streams();
+        self.inner.go_away_gracefully();
+    }
+
     /// Takes a `PingPong` instance from the connection.
     ///
     /// # Note
@@ -1494,6 +2303,55 @@ where
         self.inner.take_user_pings().map(PingPong::new)
     }
 
+    /// Polls for the next ALTSVC frame received on stream 0.
+    ///
+    /// These frames advertise alternative services for the origin they carry.
+    /// ALTSVC frames received on a request stream are added to the extensions
+    /// of the response instead. Frames are read while the `Connection` future
+    /// is polled, which this method does not do.
+    ///
+    /// Returns `None` once the connection is closed.
+    ///
+    /// See [RFC 7838] for details.
+    ///
+    /// [RFC 7838]: https://www.rfc-editor.org/rfc/rfc7838#section-4
+    pub fn poll_altsvc(&mut self, cx: &mut Context) -> Poll<Option<AltSvc>> {
+        self.inner.poll_altsvc(cx)
+    }
+
+    /// Polls for the next extension frame received from the server.
+    ///
+    /// Only frames of the types registered with
+    /// [`Builder::extension_frame_type`] are returned. Frames are read while
+    /// the `Connection` future is polled, which this method does not do.
+    ///
+    /// Returns `None` once the connection is closed.
+    ///
+    /// [`Builder::extension_frame_type`]: struct.Builder.html#method.extension_frame_type
+    pub fn poll_extension_frame(&mut self, cx: &mut Context) -> Poll<Option<ExtensionFrame>> {
+        self.inner.poll_extension_frame(cx)
+    }
+
+    /// Sends an extension frame to the server.
+    ///
+    /// The frame is written the next time the `Connection` future is polled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `kind` is a frame type known to h2, or if `payload`
+    /// is larger than the maximum frame size of the server.
+    pub fn send_extension_frame(
+        &mut self,
+        kind: u8,
+        flags: u8,
+        stream_id: u32,
+        payload: Bytes,
+    ) -> Result<(), crate::Error> {
+        self.inner
+            .send_extension_frame(kind, flags, stream_id, payload)?;
+        Ok(())
+    }
+
     /// Returns the maximum number of concurrent streams that may be initiated
     /// by this client.
     ///
@@ -1506,6 +2364,45 @@ where
     pub fn max_concurrent_send_streams(&self) -> usize {
         self.inner.max_send_streams()
     }
+
+    /// Returns a snapshot of the statistics of the connection.
+    ///
+    /// This includes the frames and bytes sent and received by frame type,
+    /// the RST_STREAM and GOAWAY frames by reason, the HPACK compression of
+    /// the header blocks, the connection-level flow control windows and the
+    /// round trip time of the last acknowledged PING.
+    pub fn stats(&self) -> ConnectionStats {
+        self.inner.stats()
+    }
+
+    /// Returns the SETTINGS sent by the server.
+    ///
+    /// Every SETTINGS frame received so far is merged into the returned
+    /// snapshot, including parameters with an id unknown to h2.
+    pub fn remote_settings(&self) -> SettingsSnapshot {
+        self.inner.remote_settings()
+    }
+
+    /// Returns the local SETTINGS acknowledged by the server.
+    ///
+    /// Local SETTINGS are only included once the server acknowledged them.
+    pub fn local_settings(&self) -> SettingsSnapshot {
+        self.inner.local_settings()
+    }
+
+    /// Polls until the server sends new SETTINGS.
+    ///
+    /// Returns the SETTINGS sent by the server, as [`remote_settings`] does,
+    /// once a SETTINGS frame was received since this method last returned
+    /// them. SETTINGS are received while the connection is polled, which this
+    /// method does not do.
+    ///
+    /// Returns `None` once the connection is closed.
+    ///
+    /// [`remote_settings`]: #method.remote_settings
+    pub fn poll_remote_settings(&mut self, cx: &mut Context) -> Poll<Option<SettingsSnapshot>> {
+        self.inner.poll_remote_settings(cx)
+    }
     /// Returns the maximum number of concurrent streams that may be initiated
     /// by the server on this connection.
     ///
@@ -1563,7 +2460,13 @@ impl Future for ResponseFuture {
     type Output = Result<Response<RecvStream>, crate::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let (parts, _) = ready!(self.inner.poll_response(cx))?.into_parts();
+        let (parts, _) = match ready!(self.inner.poll_response(cx)) {
+            Ok(response) => response.into_parts(),
+            Err(e) => {
+                let request = self.request.take();
+                return Poll::Ready(Err(crate::Error::from(e).with_request(request)));
+            }
+        };
         let body = RecvStream::new(FlowControl::new(self.inner.clone()));
 
         Poll::Ready(Ok(Response::from_parts(parts, body)))
@@ -1579,6 +2482,44 @@ impl ResponseFuture {
     pub fn stream_id(&self) -> crate::StreamId {
         crate::StreamId::from_internal(self.inner.stream_id())
     }
+
+    /// Sets the body handed back with the request if the server refuses it.
+    ///
+    /// This has no effect unless the request was sent with a `SendRequest`
+    /// that [retains requests]. The body is not sent by this call, it should
+    /// hold the data sent with the request's `SendStream`.
+    ///
+    /// [retains requests]: struct.SendRequest.html#method.set_retain_requests
+    pub fn set_replay_body(&mut self, body: Bytes) {
+        if let Some(ref mut request) = self.request {
+            *request.body_mut() = Some(body);
+        }
+    }
+
+    /// Polls for the next informational (1xx) response, such as
+    /// `100 Continue` or `103 Early Hints`.
+    ///
+    /// Informational responses are received before the final response.
+    /// Returns `None` once the final response was received, which can then be
+    /// obtained by polling the `ResponseFuture`. Informational responses which
+    /// were not polled when the final response is returned are dropped.
+    pub fn poll_informational(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Response<()>, crate::Error>>> {
+        self.inner.poll_informational(cx).map_err(Into::into)
+    }
+
+    /// Returns a stream of the informational (1xx) responses received before
+    /// the final response.
+    ///
+    /// See [`poll_informational`] for details.
+    ///
+    /// [`poll_informational`]: #method.poll_informational
+    pub fn informational_responses(&mut self) -> InformationalResponses<'_> {
+        InformationalResponses { inner: self }
+    }
+
     /// Returns a stream of PushPromises
     ///
     /// # Panics
@@ -1615,6 +2556,7 @@ impl PushPromises {
                     inner: ResponseFuture {
                         inner: response,
                         push_promise_consumed: false,
+                        request: None,
                     },
                 };
                 Poll::Ready(Some(Ok(PushPromise { request, response })))
@@ -1635,6 +2577,31 @@ impl futures_core::Stream for PushPromises {
     }
 }
 
+// ===== impl InformationalResponses =====
+
+impl InformationalResponses<'_> {
+    /// Polls for the next informational response.
+    ///
+    /// See [`ResponseFuture::poll_informational`] for details.
+    ///
+    /// [`ResponseFuture::poll_informational`]: struct.ResponseFuture.html#method.poll_informational
+    pub fn poll_informational(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Response<()>, crate::Error>>> {
+        self.inner.poll_informational(cx)
+    }
+}
+
+#[cfg(feature = "stream")]
+impl futures_core::Stream for InformationalResponses<'_> {
+    type Item = Result<Response<()>, crate::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_informational(cx)
+    }
+}
+
 // ===== impl PushPromise =====
 
 impl PushPromise {
@@ -1686,6 +2653,7 @@ impl Peer {
         end_of_stream: bool,
         pseudo_order: Option<PseudoOrder>,
         headers_stream_dependency: Option<StreamDependency>,
+        header_order: Option<HeaderOrder>,
     ) -> Result<Headers, SendError> {
         use http::request::Parts;
 
@@ -1746,6 +2714,10 @@ impl Peer {
             frame.set_stream_dependency(stream_dep);
         }
 
+        if let Some(order) = header_order {
+            frame.set_header_order(order);
+        }
+
         if end_of_stream {
             frame.set_end_stream()
         }
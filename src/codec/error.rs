@@ -49,6 +49,16 @@ pub enum UserError {
 
     /// Tries to send push promise to peer who has disabled server push
     PeerDisabledServerPush,
+
+    /// An ALTSVC or ORIGIN frame origin is not a serialized origin.
+    InvalidOrigin,
+
+    /// Tries to open a stream after starting a graceful shutdown.
+    ShuttingDown,
+
+    /// An informational response has a status other than 1xx, or is a
+    /// `101 Switching Protocols`.
+    InvalidInformationalStatus,
 }
 
 // ===== impl SendError =====
@@ -97,6 +107,9 @@ impl fmt::Display for UserError {
             SendPingWhilePending => "send_ping before received previous pong",
             SendSettingsWhilePending => "sending SETTINGS before received previous ACK",
             PeerDisabledServerPush => "sending PUSH_PROMISE to peer who disabled server push",
+            InvalidOrigin => "invalid origin",
+            ShuttingDown => "connection is shutting down",
+            InvalidInformationalStatus => "invalid informational response status",
         })
     }
 }
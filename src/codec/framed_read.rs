@@ -1,18 +1,22 @@
+use crate::fingerprint::{Fingerprint, Recorder};
 use crate::frame::{self, Frame, Kind, Reason};
 use crate::frame::{
     DEFAULT_MAX_FRAME_SIZE, DEFAULT_SETTINGS_HEADER_TABLE_SIZE, MAX_MAX_FRAME_SIZE,
 };
+use crate::observer::{self, FrameDirection, FrameObserver, ObservedFrame};
 use crate::proto::Error;
+use crate::stats::{FrameStats, HpackStats};
 
 use crate::hpack;
 
 use futures_core::Stream;
 
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, Bytes, BytesMut};
 
 use std::io;
 
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::io::AsyncRead;
 use tokio_util::codec::FramedRead as InnerFramedRead;
@@ -33,6 +37,18 @@ pub struct FramedRead<T> {
     max_continuation_frames: usize,
 
     partial: Option<Partial>,
+
+    // records the peer's fingerprint, if enabled
+    fingerprint: Option<Recorder>,
+
+    // extension frame types delivered instead of being ignored
+    extension_frame_types: Vec<u8>,
+
+    // counts of the frames received
+    stats: FrameStats,
+
+    // called with each frame received, if set
+    observer: Option<Arc<dyn FrameObserver>>,
 }
 
 /// Partially loaded headers frame
@@ -64,6 +80,10 @@ impl<T> FramedRead<T> {
             max_header_list_size,
             max_continuation_frames,
             partial: None,
+            fingerprint: None,
+            extension_frame_types: Vec::new(),
+            stats: FrameStats::default(),
+            observer: None,
         }
     }
 
@@ -105,6 +125,56 @@ impl<T> FramedRead<T> {
     pub fn set_header_table_size(&mut self, val: usize) {
         self.hpack.queue_size_update(val);
     }
+
+    /// Sets the extension frame types that are decoded instead of ignored.
+    pub fn set_extension_frame_types(&mut self, types: Vec<u8>) {
+        self.extension_frame_types = types;
+    }
+
+    /// Starts recording the fingerprint of the peer.
+    pub fn record_fingerprint(&mut self) {
+        self.fingerprint = Some(Recorder::default());
+    }
+
+    /// Sets the observer called with each frame received.
+    pub fn set_frame_observer(&mut self, observer: Arc<dyn FrameObserver>) {
+        self.hpack.keep_fields();
+        self.observer = Some(observer);
+    }
+
+    fn observe(&mut self, raw: &[u8]) {
+        let (head, payload) = raw.split_at(frame::HEADER_LEN);
+        let fields = if observer::ends_header_block(head) {
+            self.hpack.take_fields()
+        } else {
+            None
+        };
+
+        if let Some(ref observer) = self.observer {
+            observer.on_frame(&ObservedFrame::new(
+                FrameDirection::Inbound,
+                head,
+                Some(payload),
+                fields.as_deref(),
+            ));
+        }
+    }
+
+    /// Returns the counts of the frames received.
+    pub fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    /// Returns the counts of the header fields decoded.
+    pub fn hpack_stats(&self) -> &HpackStats {
+        self.hpack.stats()
+    }
+
+    /// Returns the fingerprint of the peer, once its first HEADERS frame has
+    /// been received.
+    pub fn fingerprint(&self) -> Option<&Fingerprint> {
+        self.fingerprint.as_ref().and_then(Recorder::fingerprint)
+    }
 }
 
 fn calc_max_continuation_frames(header_max: usize, frame_max: usize) -> usize {
@@ -124,6 +194,8 @@ fn decode_frame(
     max_header_list_size: usize,
     max_continuation_frames: usize,
     partial_inout: &mut Option<Partial>,
+    fingerprint: &mut Option<Recorder>,
+    extension_frame_types: &[u8],
     mut bytes: BytesMut,
 ) -> Result<Option<Frame>, Error> {
     let span = tracing::trace_span!("FramedRead::decode_frame", offset = bytes.len());
@@ -201,11 +273,18 @@ fn decode_frame(
         Kind::Settings => {
             let res = frame::Settings::load(head, &bytes[frame::HEADER_LEN..]);
 
-            res.map_err(|e| {
+            let settings = res.map_err(|e| {
                 proto_err!(conn: "failed to load SETTINGS frame; err={:?}", e);
                 Error::library_go_away(Reason::PROTOCOL_ERROR)
-            })?
-            .into()
+            })?;
+
+            if let Some(recorder) = fingerprint {
+                if !settings.is_ack() {
+                    recorder.recv_settings(&bytes[frame::HEADER_LEN..]);
+                }
+            }
+
+            settings.into()
         }
         Kind::Ping => {
             let res = frame::Ping::load(head, &bytes[frame::HEADER_LEN..]);
@@ -360,12 +439,58 @@ fn decode_frame(
                 return Ok(None);
             }
         }
+        Kind::AltSvc => {
+            // ALTSVC is a non-critical extension, malformed frames are ignored
+            // rather than treated as errors.
+            //
+            // See: https://www.rfc-editor.org/rfc/rfc7838#section-4
+            match frame::AltSvc::load(head, &bytes[frame::HEADER_LEN..]) {
+                Ok(frame) if frame.is_valid() => frame.into(),
+                res => {
+                    tracing::debug!(?res, "ignoring invalid ALTSVC frame");
+                    return Ok(None);
+                }
+            }
+        }
+        Kind::Origin => {
+            // ORIGIN is a non-critical extension, invalid frames are ignored
+            // rather than treated as errors.
+            //
+            // See: https://www.rfc-editor.org/rfc/rfc8336#section-2.1
+            match frame::Origin::load(head, &bytes[frame::HEADER_LEN..]) {
+                Ok(frame) => frame.into(),
+                Err(e) => {
+                    tracing::debug!(error = ?e, "ignoring invalid ORIGIN frame");
+                    return Ok(None);
+                }
+            }
+        }
+        Kind::PriorityUpdate => {
+            let res = frame::PriorityUpdate::load(head, &bytes[frame::HEADER_LEN..]);
+
+            res.map_err(|e| {
+                proto_err!(conn: "failed to load PRIORITY_UPDATE frame; err={:?}", e);
+                match e {
+                    frame::Error::BadFrameSize => Error::library_go_away(Reason::FRAME_SIZE_ERROR),
+                    _ => Error::library_go_away(Reason::PROTOCOL_ERROR),
+                }
+            })?
+            .into()
+        }
         Kind::Unknown => {
-            // Unknown frames are ignored
-            return Ok(None);
+            if !extension_frame_types.contains(&bytes[3]) {
+                // Unknown frames are ignored
+                return Ok(None);
+            }
+
+            frame::ExtensionFrame::load(&bytes).into()
         }
     };
 
+    if let Some(recorder) = fingerprint {
+        recorder.recv_frame(&frame);
+    }
+
     Ok(Some(frame))
 }
 
@@ -387,21 +512,39 @@ where
             };
 
             tracing::trace!(read.bytes = bytes.len());
+            self.stats.record_frame(bytes[3], bytes.len());
+            let raw = self
+                .observer
+                .as_ref()
+                .map(|_| Bytes::copy_from_slice(&bytes));
             let Self {
                 ref mut hpack,
                 max_header_list_size,
                 ref mut partial,
                 max_continuation_frames,
+                ref mut fingerprint,
+                ref extension_frame_types,
                 ..
             } = *self;
-            if let Some(frame) = decode_frame(
+            let res = decode_frame(
                 hpack,
                 max_header_list_size,
                 max_continuation_frames,
                 partial,
+                fingerprint,
+                extension_frame_types,
                 bytes,
-            )? {
+            );
+            if let Some(raw) = raw {
+                self.observe(&raw);
+            }
+            if let Some(frame) = res? {
                 tracing::debug!(?frame, "received");
+                match frame {
+                    Frame::Reset(ref v) => self.stats.record_reset(v.reason()),
+                    Frame::GoAway(ref v) => self.stats.record_go_away(v.reason()),
+                    _ => {}
+                }
                 return Poll::Ready(Some(Ok(frame)));
             }
         }
@@ -1,15 +1,18 @@
 use crate::codec::UserError;
 use crate::codec::UserError::*;
 use crate::frame::{self, Frame, FrameSize};
-use crate::hpack;
+use crate::hpack::{self, EncodingPolicy};
+use crate::observer::{self, FrameDirection, FrameObserver, ObservedFrame};
+use crate::stats::{FrameStats, HpackStats};
 
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio_util::io::poll_write_buf;
 
 use std::io::{self, Cursor};
+use std::sync::Arc;
 
 // A macro to get around a method needing to borrow &mut self
 macro_rules! limited_write_buf {
@@ -52,6 +55,15 @@ struct Encoder<B> {
 
     /// Min buffer required to attempt to write a frame
     min_buffer_capacity: usize,
+
+    /// Counts of the frames sent
+    stats: FrameStats,
+
+    /// Called with each frame sent, if set
+    observer: Option<Arc<dyn FrameObserver>>,
+
+    /// Header fields of the header block being sent, for the observer
+    fields: Option<Vec<(Bytes, Bytes)>>,
 }
 
 #[derive(Debug)]
@@ -99,6 +111,9 @@ where
                 max_frame_size: frame::DEFAULT_MAX_FRAME_SIZE,
                 chain_threshold,
                 min_buffer_capacity: chain_threshold + frame::HEADER_LEN,
+                stats: FrameStats::default(),
+                observer: None,
+                fields: None,
             },
         }
     }
@@ -210,6 +225,7 @@ where
                 if let Some(continuation) = frame.encode(&mut buf) {
                     self.next = Some(Next::Continuation(continuation));
                 }
+                self.record_frame(0);
                 ControlFlow::Continue
             }
             None => ControlFlow::Break,
@@ -224,6 +240,14 @@ where
 
         tracing::debug!(frame = ?item, "send");
 
+        match item {
+            Frame::Reset(ref v) => self.stats.record_reset(v.reason()),
+            Frame::GoAway(ref v) => self.stats.record_go_away(v.reason()),
+            _ => {}
+        }
+
+        let start = self.buf.get_ref().len();
+
         match item {
             Frame::Data(mut v) => {
                 // Ensure that the payload is not greater than the max frame.
@@ -294,11 +318,92 @@ where
                 v.encode(self.buf.get_mut());
                 tracing::trace!(rem = self.buf.remaining(), "encoded reset");
             }
+            Frame::Extension(v) => {
+                if v.payload().len() > self.max_frame_size() {
+                    return Err(PayloadTooBig);
+                }
+
+                v.encode(self.buf.get_mut());
+                tracing::trace!(rem = self.buf.remaining(), "encoded extension frame");
+            }
+            Frame::AltSvc(v) => {
+                if v.payload_len() > self.max_frame_size() {
+                    return Err(PayloadTooBig);
+                }
+
+                v.encode(self.buf.get_mut());
+                tracing::trace!(rem = self.buf.remaining(), "encoded altsvc");
+            }
+            Frame::Origin(v) => {
+                if v.payload_len() > self.max_frame_size() {
+                    return Err(PayloadTooBig);
+                }
+
+                v.encode(self.buf.get_mut());
+                tracing::trace!(rem = self.buf.remaining(), "encoded origin");
+            }
+            Frame::PriorityUpdate(v) => {
+                if v.payload_len() > self.max_frame_size() {
+                    return Err(PayloadTooBig);
+                }
+
+                v.encode(self.buf.get_mut());
+                tracing::trace!(rem = self.buf.remaining(), "encoded priority_update");
+            }
         }
 
+        self.record_frame(start);
+
         Ok(())
     }
 
+    /// Records the frame encoded at `start` in the buffer, including the
+    /// payload of a chained DATA frame.
+    fn record_frame(&mut self, start: usize) {
+        let buf = self.buf.get_ref();
+        let mut len = buf.len() - start;
+        if let Some(Next::Data(ref frame)) = self.next {
+            len += frame.payload().remaining();
+        }
+        self.stats.record_frame(buf[start + 3], len);
+
+        if self.observer.is_some() {
+            self.observe(start);
+        }
+    }
+
+    fn observe(&mut self, start: usize) {
+        let head = &self.buf.get_ref()[start..start + frame::HEADER_LEN];
+
+        if observer::starts_header_block(head) {
+            self.fields = self.hpack.take_fields();
+        }
+
+        let fields = if observer::ends_header_block(head) {
+            self.fields.take()
+        } else {
+            None
+        };
+
+        let buf = self.buf.get_ref();
+        let payload = match self.next {
+            Some(Next::Data(ref frame)) => {
+                let chunk = frame.payload().chunk();
+                Some(chunk).filter(|chunk| chunk.len() == frame.payload().remaining())
+            }
+            _ => Some(&buf[start + frame::HEADER_LEN..]),
+        };
+
+        if let Some(ref observer) = self.observer {
+            observer.on_frame(&ObservedFrame::new(
+                FrameDirection::Outbound,
+                &buf[start..start + frame::HEADER_LEN],
+                payload,
+                fields.as_deref(),
+            ));
+        }
+    }
+
     fn has_capacity(&self) -> bool {
         self.next.is_none()
             && (self.buf.get_ref().capacity() - self.buf.get_ref().len()
@@ -336,6 +441,46 @@ impl<T, B> FramedWrite<T, B> {
         self.encoder.hpack.update_max_size(val);
     }
 
+    /// Set the policy deciding how header fields are encoded.
+    pub fn set_encoding_policy(&mut self, policy: Arc<dyn EncodingPolicy>) {
+        self.encoder.hpack.set_policy(policy);
+    }
+
+    /// Sets the observer called with each frame sent.
+    pub fn set_frame_observer(&mut self, observer: Arc<dyn FrameObserver>) {
+        self.encoder.hpack.keep_fields();
+        self.encoder.observer = Some(observer);
+    }
+
+    /// Records the frames in `buf`, written to `T` before the codec was
+    /// created, as sent.
+    pub fn record_written(&mut self, buf: &[u8]) {
+        for (head, payload) in observer::split_frames(buf) {
+            self.encoder
+                .stats
+                .record_frame(head[3], head.len() + payload.len());
+
+            if let Some(ref observer) = self.encoder.observer {
+                observer.on_frame(&ObservedFrame::new(
+                    FrameDirection::Outbound,
+                    head,
+                    Some(payload),
+                    None,
+                ));
+            }
+        }
+    }
+
+    /// Returns the counts of the frames sent.
+    pub fn stats(&self) -> &FrameStats {
+        &self.encoder.stats
+    }
+
+    /// Returns the counts of the header fields encoded.
+    pub fn hpack_stats(&self) -> &HpackStats {
+        self.encoder.hpack.stats()
+    }
+
     /// Retrieve the last data frame that has been sent
     pub fn take_last_data_frame(&mut self) -> Option<frame::Data<B>> {
         self.encoder.last_data_frame.take()
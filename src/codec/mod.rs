@@ -7,13 +7,18 @@ pub use self::error::{SendError, UserError};
 use self::framed_read::FramedRead;
 use self::framed_write::FramedWrite;
 
+use crate::fingerprint::Fingerprint;
 use crate::frame::{self, Data, Frame};
+use crate::hpack::EncodingPolicy;
+use crate::observer::FrameObserver;
 use crate::proto::Error;
+use crate::stats::{FrameStats, HpackStats};
 
 use bytes::Buf;
 use futures_core::Stream;
 use futures_sink::Sink;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_util::codec::length_delimited;
@@ -85,6 +90,26 @@ impl<T, B> Codec<T, B> {
         self.inner.get_ref().max_frame_size()
     }
 
+    /// Returns the counts of the frames sent.
+    pub fn send_stats(&self) -> &FrameStats {
+        self.inner.get_ref().stats()
+    }
+
+    /// Returns the counts of the frames received.
+    pub fn recv_stats(&self) -> &FrameStats {
+        self.inner.stats()
+    }
+
+    /// Returns the counts of the header fields encoded in sent frames.
+    pub fn send_hpack_stats(&self) -> &HpackStats {
+        self.inner.get_ref().hpack_stats()
+    }
+
+    /// Returns the counts of the header fields decoded from received frames.
+    pub fn recv_hpack_stats(&self) -> &HpackStats {
+        self.inner.hpack_stats()
+    }
+
     /// Set the peer's max frame size.
     pub fn set_max_send_frame_size(&mut self, val: usize) {
         self.framed_write().set_max_frame_size(val)
@@ -95,6 +120,28 @@ impl<T, B> Codec<T, B> {
         self.framed_write().set_header_table_size(val)
     }
 
+    /// Set the policy deciding how header fields are encoded.
+    pub fn set_encoding_policy(&mut self, policy: Arc<dyn EncodingPolicy>) {
+        self.framed_write().set_encoding_policy(policy)
+    }
+
+    /// Set the observer called with each frame sent and received.
+    pub fn set_frame_observer(&mut self, observer: Arc<dyn FrameObserver>) {
+        self.framed_write().set_frame_observer(observer.clone());
+        self.inner.set_frame_observer(observer);
+    }
+
+    /// Records the frames in `buf`, written to the I/O before the codec was
+    /// created, in the send stats and the frame observer.
+    pub fn record_written(&mut self, buf: &[u8]) {
+        self.framed_write().record_written(buf)
+    }
+
+    /// Set the extension frame types that are received instead of ignored.
+    pub fn set_extension_frame_types(&mut self, types: Vec<u8>) {
+        self.inner.set_extension_frame_types(types)
+    }
+
     /// Set the decoder header table size size.
     pub fn set_recv_header_table_size(&mut self, val: usize) {
         self.inner.set_header_table_size(val)
@@ -105,6 +152,18 @@ impl<T, B> Codec<T, B> {
         self.inner.set_max_header_list_size(val);
     }
 
+    /// Starts recording the fingerprint of the peer from the frames it sends
+    /// before its first HEADERS frame.
+    pub fn record_peer_fingerprint(&mut self) {
+        self.inner.record_fingerprint();
+    }
+
+    /// Returns the fingerprint of the peer, once its first HEADERS frame has
+    /// been received.
+    pub fn peer_fingerprint(&self) -> Option<&Fingerprint> {
+        self.inner.fingerprint()
+    }
+
     /// Get a reference to the inner stream.
     #[cfg(feature = "unstable")]
     pub fn get_ref(&self) -> &T {
@@ -3,6 +3,7 @@ use crate::frame::StreamId;
 use crate::proto::{self, Initiator};
 
 use bytes::Bytes;
+use http::Request;
 use std::{error, fmt, io};
 
 pub use crate::frame::Reason;
@@ -20,6 +21,8 @@ pub use crate::frame::Reason;
 #[derive(Debug)]
 pub struct Error {
     kind: Kind,
+    /// The request which failed, retained to be sent again.
+    request: Option<Box<Request<Option<Bytes>>>>,
 }
 
 #[derive(Debug)]
@@ -29,7 +32,10 @@ enum Kind {
     Reset(StreamId, Reason, Initiator),
 
     /// A GO_AWAY frame was received or sent.
-    GoAway(Bytes, Reason, Initiator),
+    ///
+    /// The last stream ID is set if the stream was refused by a received
+    /// GO_AWAY.
+    GoAway(Bytes, Reason, Initiator, Option<StreamId>),
 
     /// The user created an error from a bare Reason.
     Reason(Reason),
@@ -40,6 +46,9 @@ enum Kind {
 
     /// An `io::Error` occurred while trying to read or write.
     Io(io::Error),
+
+    /// A keep-alive PING was not acknowledged in time.
+    KeepAliveTimeout,
 }
 
 // ===== impl Error =====
@@ -51,7 +60,7 @@ impl Error {
     /// action taken by the peer (i.e. a protocol error).
     pub fn reason(&self) -> Option<Reason> {
         match self.kind {
-            Kind::Reset(_, reason, _) | Kind::GoAway(_, reason, _) | Kind::Reason(reason) => {
+            Kind::Reset(_, reason, _) | Kind::GoAway(_, reason, _, _) | Kind::Reason(reason) => {
                 Some(reason)
             }
             _ => None,
@@ -80,8 +89,13 @@ impl Error {
     }
 
     pub(crate) fn from_io(err: io::Error) -> Self {
+        Error::new(Kind::Io(err))
+    }
+
+    fn new(kind: Kind) -> Self {
         Error {
-            kind: Kind::Io(err),
+            kind,
+            request: None,
         }
     }
 
@@ -101,17 +115,85 @@ impl Error {
     pub fn is_remote(&self) -> bool {
         matches!(
             self.kind,
-            Kind::GoAway(_, _, Initiator::Remote) | Kind::Reset(_, _, Initiator::Remote)
+            Kind::GoAway(_, _, Initiator::Remote, _) | Kind::Reset(_, _, Initiator::Remote)
         )
     }
 
+    /// Returns true if the request failed before the remote processed it, and
+    /// can safely be sent again, on a new connection.
+    ///
+    /// This is the case for requests refused with a `REFUSED_STREAM` reset,
+    /// and for requests on streams with an ID greater than the last stream ID
+    /// of a received `GOAWAY`.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind,
+            Kind::GoAway(_, _, Initiator::Remote, Some(_))
+                | Kind::Reset(_, Reason::REFUSED_STREAM, Initiator::Remote)
+        )
+    }
+
+    /// Returns the last stream ID of the received `GOAWAY` which refused the
+    /// request.
+    ///
+    /// Returns `None` if the request was not refused by a `GOAWAY`.
+    pub fn last_stream_id(&self) -> Option<crate::StreamId> {
+        match self.kind {
+            Kind::GoAway(_, _, _, Some(id)) => Some(crate::StreamId::from_internal(id)),
+            _ => None,
+        }
+    }
+
+    /// Takes the request which failed, so it can be sent again.
+    ///
+    /// A request is only handed back if it is [retryable], and if the
+    /// `SendRequest` it was sent with retains requests. See
+    /// [`SendRequest::set_retain_requests`].
+    ///
+    /// The body of the request is the one set with
+    /// [`ResponseFuture::set_replay_body`], if any.
+    ///
+    /// [retryable]: #method.is_retryable
+    /// [`SendRequest::set_retain_requests`]: client/struct.SendRequest.html#method.set_retain_requests
+    /// [`ResponseFuture::set_replay_body`]: client/struct.ResponseFuture.html#method.set_replay_body
+    pub fn take_request(&mut self) -> Option<Request<Option<Bytes>>> {
+        self.request.take().map(|request| *request)
+    }
+
+    /// Attaches the retained request, if the error is retryable.
+    pub(crate) fn with_request(mut self, request: Option<Box<Request<Option<Bytes>>>>) -> Self {
+        if self.is_retryable() {
+            self.request = request;
+        }
+        self
+    }
+
+    /// Returns true if the connection was closed because the remote did not
+    /// acknowledge our SETTINGS in time.
+    ///
+    /// See `settings_ack_timeout` on the client and server `Builder`.
+    pub fn is_settings_timeout(&self) -> bool {
+        matches!(
+            self.kind,
+            Kind::GoAway(_, Reason::SETTINGS_TIMEOUT, Initiator::Library, _)
+        )
+    }
+
+    /// Returns true if the connection was closed because the remote did not
+    /// acknowledge a keep-alive PING in time.
+    ///
+    /// See `keep_alive_interval` on the client and server `Builder`.
+    pub fn is_keep_alive_timeout(&self) -> bool {
+        matches!(self.kind, Kind::KeepAliveTimeout)
+    }
+
     /// Returns true if the error was created by `h2`.
     ///
     /// Such as noticing some protocol error and sending a GOAWAY or RST_STREAM.
     pub fn is_library(&self) -> bool {
         matches!(
             self.kind,
-            Kind::GoAway(_, _, Initiator::Library) | Kind::Reset(_, _, Initiator::Library)
+            Kind::GoAway(_, _, Initiator::Library, _) | Kind::Reset(_, _, Initiator::Library)
         )
     }
 }
@@ -120,25 +202,22 @@ impl From<proto::Error> for Error {
     fn from(src: proto::Error) -> Error {
         use crate::proto::Error::*;
 
-        Error {
-            kind: match src {
-                Reset(stream_id, reason, initiator) => Kind::Reset(stream_id, reason, initiator),
-                GoAway(debug_data, reason, initiator) => {
-                    Kind::GoAway(debug_data, reason, initiator)
-                }
-                Io(kind, inner) => {
-                    Kind::Io(inner.map_or_else(|| kind.into(), |inner| io::Error::new(kind, inner)))
-                }
-            },
-        }
+        Error::new(match src {
+            Reset(stream_id, reason, initiator) => Kind::Reset(stream_id, reason, initiator),
+            GoAway(debug_data, reason, initiator, last_stream_id) => {
+                Kind::GoAway(debug_data, reason, initiator, last_stream_id)
+            }
+            Io(kind, inner) => {
+                Kind::Io(inner.map_or_else(|| kind.into(), |inner| io::Error::new(kind, inner)))
+            }
+            KeepAliveTimeout => Kind::KeepAliveTimeout,
+        })
     }
 }
 
 impl From<Reason> for Error {
     fn from(src: Reason) -> Error {
-        Error {
-            kind: Kind::Reason(src),
-        }
+        Error::new(Kind::Reason(src))
     }
 }
 
@@ -153,9 +232,7 @@ impl From<SendError> for Error {
 
 impl From<UserError> for Error {
     fn from(src: UserError) -> Error {
-        Error {
-            kind: Kind::User(src),
-        }
+        Error::new(Kind::User(src))
     }
 }
 
@@ -171,21 +248,22 @@ impl fmt::Display for Error {
             Kind::Reset(_, reason, Initiator::Remote) => {
                 return write!(fmt, "stream error received: {}", reason)
             }
-            Kind::GoAway(ref debug_data, reason, Initiator::User) => {
+            Kind::GoAway(ref debug_data, reason, Initiator::User, _) => {
                 write!(fmt, "connection error sent by user: {}", reason)?;
                 debug_data
             }
-            Kind::GoAway(ref debug_data, reason, Initiator::Library) => {
+            Kind::GoAway(ref debug_data, reason, Initiator::Library, _) => {
                 write!(fmt, "connection error detected: {}", reason)?;
                 debug_data
             }
-            Kind::GoAway(ref debug_data, reason, Initiator::Remote) => {
+            Kind::GoAway(ref debug_data, reason, Initiator::Remote, _) => {
                 write!(fmt, "connection error received: {}", reason)?;
                 debug_data
             }
             Kind::Reason(reason) => return write!(fmt, "protocol error: {}", reason),
             Kind::User(ref e) => return write!(fmt, "user error: {}", e),
             Kind::Io(ref e) => return e.fmt(fmt),
+            Kind::KeepAliveTimeout => return fmt.write_str("keep-alive timed out"),
         };
 
         if !debug_data.is_empty() {
@@ -201,11 +279,49 @@ impl error::Error for Error {}
 #[cfg(test)]
 mod tests {
     use super::Error;
-    use crate::Reason;
+    use crate::{proto, Reason};
+    use bytes::Bytes;
 
     #[test]
     fn error_from_reason() {
         let err = Error::from(Reason::HTTP_1_1_REQUIRED);
         assert_eq!(err.reason(), Some(Reason::HTTP_1_1_REQUIRED));
     }
+
+    #[test]
+    fn error_is_retryable() {
+        let err = Error::from(proto::Error::remote_reset(1.into(), Reason::REFUSED_STREAM));
+        assert!(err.is_retryable());
+
+        let err = Error::from(proto::Error::library_reset(
+            1.into(),
+            Reason::REFUSED_STREAM,
+        ));
+        assert!(!err.is_retryable());
+
+        let err = Error::from(proto::Error::remote_go_away(Bytes::new(), Reason::NO_ERROR));
+        assert!(!err.is_retryable());
+        assert!(err.last_stream_id().is_none());
+
+        let err = Error::from(proto::Error::remote_go_away_refused(
+            Bytes::new(),
+            Reason::NO_ERROR,
+            5.into(),
+        ));
+        assert!(err.is_retryable());
+        assert_eq!(err.last_stream_id().map(|id| id.as_u32()), Some(5));
+    }
+
+    #[test]
+    fn error_is_keep_alive_timeout() {
+        let err = Error::from(proto::Error::KeepAliveTimeout);
+        assert!(err.is_keep_alive_timeout());
+        assert!(!err.is_io());
+
+        // An I/O error from the transport is never mistaken for it.
+        let io = std::io::Error::new(std::io::ErrorKind::TimedOut, "keep-alive timed out");
+        let err = Error::from(proto::Error::from(io));
+        assert!(!err.is_keep_alive_timeout());
+        assert!(err.is_io());
+    }
 }
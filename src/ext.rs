@@ -3,6 +3,7 @@
 use crate::hpack::BytesStr;
 
 use bytes::Bytes;
+use http::header::{HeaderName, HeaderValue};
 use std::fmt;
 
 /// Represents the `:protocol` pseudo-header used by
@@ -53,3 +54,260 @@ impl fmt::Debug for Protocol {
         self.value.fmt(f)
     }
 }
+
+/// The order in which the regular header fields of a request or response are
+/// encoded in its HEADERS frame.
+///
+/// By default, fields are encoded in `HeaderMap` iteration order, which
+/// groups all the values of a name together. When a `HeaderOrder` is placed in
+/// the extensions of a request given to [`SendRequest::send_request`], or of a
+/// response given to [`SendResponse::send_response`], fields are encoded in
+/// the listed order instead:
+///
+/// * Each occurrence of a name encodes the next value of that name, so a name
+///   may be listed several times to interleave its values with other fields.
+/// * The last occurrence of a name encodes all the remaining values of that
+///   name.
+/// * Names without a value in the `HeaderMap` are skipped.
+/// * Fields whose name is not listed are encoded last, in `HeaderMap`
+///   iteration order.
+///
+/// # Examples
+///
+/// ```
+/// use h2::ext::HeaderOrder;
+/// use http::header::{COOKIE, USER_AGENT};
+/// use http::Request;
+///
+/// let mut request = Request::get("https://example.com/")
+///     .header(COOKIE, "a=1")
+///     .header(COOKIE, "b=2")
+///     .header(USER_AGENT, "h2")
+///     .body(())
+///     .unwrap();
+///
+/// // Encodes `cookie: a=1`, `user-agent: h2` and then `cookie: b=2`.
+/// let order: HeaderOrder = [COOKIE, USER_AGENT, COOKIE].into_iter().collect();
+/// request.extensions_mut().insert(order);
+/// ```
+///
+/// [`SendRequest::send_request`]: ../client/struct.SendRequest.html#method.send_request
+/// [`SendResponse::send_response`]: ../server/struct.SendResponse.html#method.send_response
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HeaderOrder {
+    names: Vec<HeaderName>,
+}
+
+impl HeaderOrder {
+    /// Creates an empty `HeaderOrder`.
+    pub fn new() -> Self {
+        HeaderOrder::default()
+    }
+
+    /// Appends a header name to the order.
+    pub fn push(&mut self, name: HeaderName) {
+        self.names.push(name);
+    }
+
+    /// Returns an iterator over the header names, in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, HeaderName> {
+        self.names.iter()
+    }
+
+    /// Returns the number of header names in the order.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if the order contains no header name.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+impl FromIterator<HeaderName> for HeaderOrder {
+    fn from_iter<I: IntoIterator<Item = HeaderName>>(iter: I) -> Self {
+        HeaderOrder {
+            names: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a HeaderOrder {
+    type Item = &'a HeaderName;
+    type IntoIter = std::slice::Iter<'a, HeaderName>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.names.iter()
+    }
+}
+
+/// The priority of a request, as signaled by the `priority` header field and
+/// PRIORITY_UPDATE frames of [Extensible Priorities].
+///
+/// A server finds the priority of a request in its extensions, if the client
+/// signaled one. Streams with a lower urgency are sent first by the server.
+///
+/// A client places an `ExtensiblePriority` in the extensions of a request given
+/// to [`SendRequest::send_request`] to send it as the `priority` header field,
+/// unless the request already has one. Requests are reprioritized with
+/// [`SendRequest::send_priority_update`] and
+/// [`SendStream::send_priority_update`].
+///
+/// # Examples
+///
+/// ```
+/// use h2::ext::ExtensiblePriority;
+///
+/// let priority = ExtensiblePriority::parse(b"u=1, i");
+/// assert_eq!(priority.urgency(), 1);
+/// assert!(priority.is_incremental());
+/// assert_eq!(priority.to_string(), "u=1, i");
+/// ```
+///
+/// [Extensible Priorities]: https://www.rfc-editor.org/rfc/rfc9218
+/// [`SendRequest::send_request`]: ../client/struct.SendRequest.html#method.send_request
+/// [`SendRequest::send_priority_update`]: ../client/struct.SendRequest.html#method.send_priority_update
+/// [`SendStream::send_priority_update`]: ../struct.SendStream.html#method.send_priority_update
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ExtensiblePriority {
+    urgency: u8,
+    incremental: bool,
+}
+
+impl ExtensiblePriority {
+    /// The lowest urgency, which is the least important.
+    pub const MAX_URGENCY: u8 = 7;
+
+    /// Creates a priority from its urgency and incremental parameters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `urgency` is greater than [`MAX_URGENCY`](Self::MAX_URGENCY).
+    pub fn new(urgency: u8, incremental: bool) -> Self {
+        assert!(urgency <= Self::MAX_URGENCY, "invalid urgency {}", urgency);
+
+        ExtensiblePriority {
+            urgency,
+            incremental,
+        }
+    }
+
+    /// Parses the value of a `priority` header field, or the Priority Field
+    /// Value of a PRIORITY_UPDATE frame.
+    ///
+    /// As required by RFC 9218, invalid or unknown parameters are ignored and
+    /// missing parameters take their default value.
+    pub fn parse(src: &[u8]) -> Self {
+        let mut priority = ExtensiblePriority::default();
+
+        for member in src.split(|&b| b == b',') {
+            // Parameters of a member are ignored
+            let member = member.split(|&b| b == b';').next().unwrap_or_default();
+            let member = trim(member);
+
+            let (key, value) = match member.iter().position(|&b| b == b'=') {
+                Some(i) => (&member[..i], Some(&member[i + 1..])),
+                None => (member, None),
+            };
+
+            match (key, value) {
+                (b"u", Some(value)) => {
+                    if let Some(urgency) = parse_urgency(value) {
+                        priority.urgency = urgency;
+                    }
+                }
+                (b"i", None) | (b"i", Some(b"?1")) => priority.incremental = true,
+                (b"i", Some(b"?0")) => priority.incremental = false,
+                _ => {}
+            }
+        }
+
+        priority
+    }
+
+    /// Returns the urgency, from 0 (most urgent) to 7.
+    pub fn urgency(&self) -> u8 {
+        self.urgency
+    }
+
+    /// Returns whether the response can be processed incrementally.
+    pub fn is_incremental(&self) -> bool {
+        self.incremental
+    }
+
+    /// Returns the priority as a `priority` header field value.
+    pub fn to_header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.to_string()).expect("valid header value")
+    }
+}
+
+impl Default for ExtensiblePriority {
+    /// Returns the default priority, with an urgency of 3 and not incremental.
+    fn default() -> Self {
+        ExtensiblePriority {
+            urgency: 3,
+            incremental: false,
+        }
+    }
+}
+
+impl fmt::Display for ExtensiblePriority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "u={}", self.urgency)?;
+
+        if self.incremental {
+            f.write_str(", i")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn trim(mut src: &[u8]) -> &[u8] {
+    while let [b' ' | b'\t', rest @ ..] = src {
+        src = rest;
+    }
+
+    while let [rest @ .., b' ' | b'\t'] = src {
+        src = rest;
+    }
+
+    src
+}
+
+fn parse_urgency(src: &[u8]) -> Option<u8> {
+    match src {
+        [digit @ b'0'..=b'7'] => Some(digit - b'0'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_extensible_priority() {
+        let parse = |src: &str| {
+            let priority = ExtensiblePriority::parse(src.as_bytes());
+            (priority.urgency(), priority.is_incremental())
+        };
+
+        assert_eq!(parse(""), (3, false));
+        assert_eq!(parse("u=0"), (0, false));
+        assert_eq!(parse("i"), (3, true));
+        assert_eq!(parse(" u=5 ,\ti "), (5, true));
+        assert_eq!(parse("u=1;a=b, i=?0, i=?1"), (1, true));
+        assert_eq!(parse("u=2, i=?0"), (2, false));
+        assert_eq!(parse("u=8, i=1, x=y"), (3, false));
+        assert_eq!(parse("u=1, u=-1, u=a"), (1, false));
+    }
+
+    #[test]
+    fn test_encode_extensible_priority() {
+        assert_eq!(ExtensiblePriority::default().to_string(), "u=3");
+        assert_eq!(ExtensiblePriority::new(0, true).to_string(), "u=0, i");
+        assert_eq!(ExtensiblePriority::new(7, false).to_header_value(), "u=7");
+    }
+}
@@ -0,0 +1,654 @@
+#[cfg(feature = "unstable")]
+use crate::frame::{ExperimentalSettings, Setting};
+use crate::frame::{
+    Frame, Priorities, Priority, PseudoId, PseudoOrder, SettingId, Settings, SettingsOrder,
+    StreamDependency, StreamId, DEFAULT_INITIAL_WINDOW_SIZE, DEFAULT_MAX_FRAME_SIZE,
+    MAX_MAX_FRAME_SIZE,
+};
+use crate::profile::{Profile, ProfileBuilder};
+use crate::proto::MAX_WINDOW_SIZE;
+
+use smallvec::SmallVec;
+
+use std::str::FromStr;
+use std::{error, fmt};
+
+/// An HTTP/2 fingerprint in the format described by Akamai.
+///
+/// The fingerprint is made of four `|` separated parts:
+///
+/// * The SETTINGS parameters of the connection preface, in wire order, as
+///   `id:value` pairs separated by `;`.
+/// * The increment of the connection level WINDOW_UPDATE sent after the
+///   SETTINGS frame, or `00` if none was sent.
+/// * The PRIORITY frames sent before the first request, as
+///   `stream_id:exclusive:dependency_id:weight` separated by `,`, or `0` if
+///   none were sent. The weight is the actual weight (1-256), not the value
+///   encoded on the wire.
+/// * The order of the `:method`, `:authority`, `:scheme` and `:path`
+///   pseudo-header fields, using their first letter, separated by `,`.
+///
+/// For example, the fingerprint of Chrome is
+/// `1:65536;2:0;4:6291456;6:262144|15663105|0|m,a,s,p`.
+///
+/// A `Fingerprint` is obtained by parsing such a string, or by rendering the
+/// configuration of a [`client::Builder`]. It can be turned into a client
+/// [`Profile`] with [`Fingerprint::to_profile`].
+///
+/// [`client::Builder`]: client/struct.Builder.html
+/// [`Profile`]: client/struct.Profile.html
+///
+/// # Examples
+///
+/// ```
+/// use h2::Fingerprint;
+///
+/// let fingerprint =
+///     Fingerprint::from_akamai_str("1:65536;2:0;4:6291456;6:262144|15663105|0|m,a,s,p")
+///         .unwrap();
+///
+/// assert_eq!(fingerprint.window_update(), Some(15663105));
+/// assert_eq!(
+///     fingerprint.to_string(),
+///     "1:65536;2:0;4:6291456;6:262144|15663105|0|m,a,s,p"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Fingerprint {
+    settings: SmallVec<[(SettingId, u32); 8]>,
+    window_update: Option<u32>,
+    priorities: Vec<Priority>,
+    pseudo_order: SmallVec<[PseudoId; 4]>,
+}
+
+/// Maximum number of PRIORITY frames recorded before the first HEADERS frame.
+const MAX_RECORDED_PRIORITIES: usize = 64;
+
+/// Records the fingerprint of the remote peer from the frames it sends before
+/// its first HEADERS frame.
+///
+/// At most `MAX_RECORDED_PRIORITIES` PRIORITY frames are recorded, so that a
+/// peer delaying its first request cannot grow the recorder without bound.
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    fingerprint: Fingerprint,
+    has_settings: bool,
+    has_window_update: bool,
+    is_complete: bool,
+}
+
+/// An error returned when a [`Fingerprint`] is malformed, or cannot be turned
+/// into a [`Profile`].
+///
+/// [`Fingerprint`]: struct.Fingerprint.html
+/// [`Profile`]: client/struct.Profile.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FingerprintError {
+    kind: Kind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Kind {
+    /// The string does not have exactly four `|` separated parts.
+    InvalidFormat,
+
+    /// A SETTINGS parameter could not be parsed.
+    InvalidSetting,
+
+    /// A SETTINGS value is outside of its legal range.
+    InvalidSettingValue(SettingId),
+
+    /// An unknown SETTINGS parameter cannot be sent by this build.
+    UnsupportedSetting(SettingId),
+
+    /// The WINDOW_UPDATE increment could not be parsed or is too large.
+    InvalidWindowUpdate,
+
+    /// A PRIORITY frame could not be parsed or targets an invalid stream.
+    InvalidPriority,
+
+    /// A pseudo-header letter is unknown or repeated.
+    InvalidPseudoOrder,
+}
+
+// ===== impl Fingerprint =====
+
+impl Fingerprint {
+    /// Parses an Akamai HTTP/2 fingerprint string.
+    ///
+    /// SETTINGS parameters may be separated by `;` or `,`. A WINDOW_UPDATE
+    /// part of `0` or `00` means that no WINDOW_UPDATE was sent.
+    pub fn from_akamai_str(src: &str) -> Result<Fingerprint, FingerprintError> {
+        let mut parts = src.trim().split('|');
+        let (settings, window_update, priorities, pseudo_order) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(s), Some(w), Some(p), Some(o)) if parts.next().is_none() => (s, w, p, o),
+                _ => return Err(Kind::InvalidFormat.into()),
+            };
+
+        let mut fingerprint = Fingerprint::default();
+
+        for setting in settings.split([';', ',']).filter(|s| !s.is_empty()) {
+            let (id, value) = setting.split_once(':').ok_or(Kind::InvalidSetting)?;
+            let id = id.parse::<u16>().map_err(|_| Kind::InvalidSetting)?;
+            let value = value.parse::<u32>().map_err(|_| Kind::InvalidSetting)?;
+            fingerprint.settings.push((SettingId::from(id), value));
+        }
+
+        let increment = window_update
+            .parse::<u32>()
+            .map_err(|_| Kind::InvalidWindowUpdate)?;
+        if increment != 0 {
+            fingerprint.window_update = Some(increment);
+        }
+
+        if priorities != "0" {
+            for priority in priorities.split(',') {
+                fingerprint.priorities.push(parse_priority(priority)?);
+            }
+        }
+
+        for letter in pseudo_order.split(',').filter(|s| !s.is_empty()) {
+            let id = match letter {
+                "m" => PseudoId::Method,
+                "a" => PseudoId::Authority,
+                "s" => PseudoId::Scheme,
+                "p" => PseudoId::Path,
+                _ => return Err(Kind::InvalidPseudoOrder.into()),
+            };
+            if fingerprint.pseudo_order.contains(&id) {
+                return Err(Kind::InvalidPseudoOrder.into());
+            }
+            fingerprint.pseudo_order.push(id);
+        }
+
+        Ok(fingerprint)
+    }
+
+    /// Renders the fingerprint as an Akamai HTTP/2 fingerprint string.
+    pub fn to_akamai_string(&self) -> String {
+        self.to_string()
+    }
+
+    pub(crate) fn from_parts(
+        settings: &Settings,
+        window_update: Option<u32>,
+        priorities: Option<&Priorities>,
+        pseudo_order: Option<&PseudoOrder>,
+    ) -> Fingerprint {
+        let mut fingerprint = Fingerprint {
+            window_update: window_update.filter(|increment| *increment != 0),
+            priorities: priorities.into_iter().flatten().cloned().collect(),
+            ..Fingerprint::default()
+        };
+
+        settings.for_each(|setting| fingerprint.settings.push((setting.id(), setting.value())));
+
+        let default_order = PseudoOrder::default();
+        let pseudo_order = pseudo_order.unwrap_or(&default_order);
+        fingerprint.pseudo_order = pseudo_order
+            .into_iter()
+            .filter(|id| {
+                matches!(
+                    id,
+                    PseudoId::Method | PseudoId::Authority | PseudoId::Scheme | PseudoId::Path
+                )
+            })
+            .copied()
+            .collect();
+
+        fingerprint
+    }
+
+    /// Returns the SETTINGS parameters, in wire order.
+    pub fn settings(&self) -> &[(SettingId, u32)] {
+        &self.settings
+    }
+
+    /// Returns the increment of the connection level WINDOW_UPDATE, if any.
+    pub fn window_update(&self) -> Option<u32> {
+        self.window_update
+    }
+
+    /// Returns the PRIORITY frames sent before the first request.
+    pub fn priorities(&self) -> &[Priority] {
+        &self.priorities
+    }
+
+    /// Returns the pseudo-header order.
+    pub fn pseudo_order(&self) -> &[PseudoId] {
+        &self.pseudo_order
+    }
+
+    /// Builds a client [`Profile`] that produces this fingerprint.
+    ///
+    /// A SETTINGS parameter listed more than once keeps its first position and
+    /// its last value. Unknown SETTINGS parameters can only be sent when the
+    /// `unstable` feature is enabled.
+    ///
+    /// The Akamai format does not describe the stream dependency of HEADERS
+    /// frames, so the returned profile does not set one.
+    ///
+    /// [`Profile`]: client/struct.Profile.html
+    pub fn to_profile(&self) -> Result<Profile, FingerprintError> {
+        let mut builder = Profile::builder();
+        let mut order = SettingsOrder::builder();
+        let mut unknown: SmallVec<[(SettingId, u32); 4]> = SmallVec::new();
+
+        for &(id, value) in &self.settings {
+            let invalid = || FingerprintError::from(Kind::InvalidSettingValue(id));
+
+            builder = match id {
+                SettingId::HeaderTableSize => builder.header_table_size(value),
+                SettingId::EnablePush => {
+                    builder.enable_push(parse_bool(value).ok_or_else(invalid)?)
+                }
+                SettingId::MaxConcurrentStreams => builder.max_concurrent_streams(value),
+                SettingId::InitialWindowSize => {
+                    if value > MAX_WINDOW_SIZE {
+                        return Err(invalid());
+                    }
+                    builder.initial_window_size(value)
+                }
+                SettingId::MaxFrameSize => {
+                    if value < DEFAULT_MAX_FRAME_SIZE || value > MAX_MAX_FRAME_SIZE {
+                        return Err(invalid());
+                    }
+                    builder.max_frame_size(value)
+                }
+                SettingId::MaxHeaderListSize => builder.max_header_list_size(value),
+                SettingId::EnableConnectProtocol => {
+                    builder.enable_connect_protocol(parse_bool(value).ok_or_else(invalid)?)
+                }
+                SettingId::NoRfc7540Priorities => {
+                    builder.no_rfc7540_priorities(parse_bool(value).ok_or_else(invalid)?)
+                }
+                SettingId::Unknown(_) => {
+                    match unknown.iter_mut().find(|(unknown_id, _)| *unknown_id == id) {
+                        Some(setting) => setting.1 = value,
+                        None => unknown.push((id, value)),
+                    }
+                    builder
+                }
+            };
+            order = order.push(id);
+        }
+
+        builder = builder.settings_order(order.build());
+
+        if !unknown.is_empty() {
+            builder = unknown_settings(builder, &unknown)?;
+        }
+
+        if let Some(increment) = self.window_update {
+            if increment > MAX_WINDOW_SIZE - DEFAULT_INITIAL_WINDOW_SIZE {
+                return Err(Kind::InvalidWindowUpdate.into());
+            }
+            builder = builder.connection_window_increment(increment);
+        }
+
+        if !self.priorities.is_empty() {
+            for priority in &self.priorities {
+                if priority.stream_id().is_zero()
+                    || priority.stream_id() == priority.dependency().dependency_id()
+                {
+                    return Err(Kind::InvalidPriority.into());
+                }
+            }
+            builder = builder.priorities(
+                Priorities::builder()
+                    .extend(self.priorities.iter().cloned())
+                    .build(),
+            );
+        }
+
+        builder = builder.pseudo_order(
+            PseudoOrder::builder()
+                .extend(self.pseudo_order.iter().copied())
+                .build(),
+        );
+
+        Ok(builder.build())
+    }
+}
+
+fn parse_priority(src: &str) -> Result<Priority, FingerprintError> {
+    let mut fields = src.split(':');
+    let mut next = || fields.next().ok_or(Kind::InvalidPriority);
+
+    let stream_id = next()?.parse::<u32>().map_err(|_| Kind::InvalidPriority)?;
+    let is_exclusive = match next()? {
+        "0" => false,
+        "1" => true,
+        _ => return Err(Kind::InvalidPriority.into()),
+    };
+    let dependency_id = next()?.parse::<u32>().map_err(|_| Kind::InvalidPriority)?;
+    let weight = next()?.parse::<u16>().map_err(|_| Kind::InvalidPriority)?;
+
+    if fields.next().is_some()
+        || stream_id > StreamId::MAX.into()
+        || dependency_id > StreamId::MAX.into()
+        || !(1..=256).contains(&weight)
+    {
+        return Err(Kind::InvalidPriority.into());
+    }
+
+    Ok(Priority::new(
+        stream_id.into(),
+        StreamDependency::new(dependency_id.into(), (weight - 1) as u8, is_exclusive),
+    ))
+}
+
+#[cfg(feature = "unstable")]
+fn unknown_settings(
+    builder: ProfileBuilder,
+    unknown: &[(SettingId, u32)],
+) -> Result<ProfileBuilder, FingerprintError> {
+    let mut experimental = ExperimentalSettings::builder();
+    for &(id, value) in unknown {
+        let setting = Setting::from_id(id, value).ok_or(Kind::UnsupportedSetting(id))?;
+        experimental = experimental.push(setting);
+    }
+    Ok(builder.experimental_settings(experimental.build()))
+}
+
+#[cfg(not(feature = "unstable"))]
+fn unknown_settings(
+    _builder: ProfileBuilder,
+    unknown: &[(SettingId, u32)],
+) -> Result<ProfileBuilder, FingerprintError> {
+    Err(Kind::UnsupportedSetting(unknown[0].0).into())
+}
+
+fn parse_bool(value: u32) -> Option<bool> {
+    match value {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    }
+}
+
+// ===== impl Recorder =====
+
+impl Recorder {
+    /// Returns the recorded fingerprint, once the first HEADERS frame has been
+    /// received.
+    pub(crate) fn fingerprint(&self) -> Option<&Fingerprint> {
+        if self.is_complete {
+            Some(&self.fingerprint)
+        } else {
+            None
+        }
+    }
+
+    /// Records the parameters of the first SETTINGS frame, in wire order.
+    pub(crate) fn recv_settings(&mut self, payload: &[u8]) {
+        if self.is_complete || self.has_settings {
+            return;
+        }
+
+        self.has_settings = true;
+        self.fingerprint
+            .settings
+            .extend(Settings::load_parameters(payload));
+    }
+
+    pub(crate) fn recv_frame(&mut self, frame: &Frame) {
+        if self.is_complete {
+            return;
+        }
+
+        match frame {
+            Frame::WindowUpdate(frame)
+                if frame.stream_id().is_zero() && !self.has_window_update =>
+            {
+                self.has_window_update = true;
+                self.fingerprint.window_update =
+                    Some(frame.size_increment()).filter(|increment| *increment != 0);
+            }
+            Frame::Priority(frame)
+                if self.fingerprint.priorities.len() < MAX_RECORDED_PRIORITIES =>
+            {
+                self.fingerprint.priorities.push(frame.clone());
+            }
+            Frame::Headers(frame) => {
+                let pseudo = frame.pseudo();
+                self.fingerprint.pseudo_order = pseudo
+                    .order
+                    .into_iter()
+                    .filter(|id| match id {
+                        PseudoId::Method => pseudo.method.is_some(),
+                        PseudoId::Authority => pseudo.authority.is_some(),
+                        PseudoId::Scheme => pseudo.scheme.is_some(),
+                        PseudoId::Path => pseudo.path.is_some(),
+                        PseudoId::Protocol | PseudoId::Status => false,
+                    })
+                    .copied()
+                    .collect();
+                self.is_complete = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = FingerprintError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        Fingerprint::from_akamai_str(src)
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (id, value)) in self.settings.iter().enumerate() {
+            if i > 0 {
+                fmt.write_str(";")?;
+            }
+            write!(fmt, "{}:{}", u16::from(*id), value)?;
+        }
+
+        match self.window_update {
+            Some(increment) => write!(fmt, "|{}|", increment)?,
+            None => fmt.write_str("|00|")?,
+        }
+
+        if self.priorities.is_empty() {
+            fmt.write_str("0")?;
+        }
+        for (i, priority) in self.priorities.iter().enumerate() {
+            if i > 0 {
+                fmt.write_str(",")?;
+            }
+            let dependency = priority.dependency();
+            write!(
+                fmt,
+                "{}:{}:{}:{}",
+                u32::from(priority.stream_id()),
+                dependency.is_exclusive() as u8,
+                u32::from(dependency.dependency_id()),
+                u16::from(dependency.weight()) + 1
+            )?;
+        }
+
+        fmt.write_str("|")?;
+        for (i, id) in self.pseudo_order.iter().enumerate() {
+            if i > 0 {
+                fmt.write_str(",")?;
+            }
+            fmt.write_str(match id {
+                PseudoId::Method => "m",
+                PseudoId::Authority => "a",
+                PseudoId::Scheme => "s",
+                PseudoId::Path => "p",
+                PseudoId::Protocol | PseudoId::Status => unreachable!(),
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+// ===== impl FingerprintError =====
+
+impl From<Kind> for FingerprintError {
+    fn from(kind: Kind) -> Self {
+        FingerprintError { kind }
+    }
+}
+
+impl error::Error for FingerprintError {}
+
+impl fmt::Display for FingerprintError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            Kind::InvalidFormat => fmt.write_str("fingerprint must have four `|` separated parts"),
+            Kind::InvalidSetting => fmt.write_str("invalid SETTINGS parameter"),
+            Kind::InvalidSettingValue(id) => {
+                write!(
+                    fmt,
+                    "invalid value for SETTINGS parameter {}",
+                    u16::from(id)
+                )
+            }
+            Kind::UnsupportedSetting(id) => {
+                write!(fmt, "unsupported SETTINGS parameter {}", u16::from(id))
+            }
+            Kind::InvalidWindowUpdate => fmt.write_str("invalid WINDOW_UPDATE increment"),
+            Kind::InvalidPriority => fmt.write_str("invalid PRIORITY frame"),
+            Kind::InvalidPseudoOrder => fmt.write_str("invalid pseudo-header order"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client;
+
+    const CHROME: &str = "1:65536;2:0;4:6291456;6:262144|15663105|0|m,a,s,p";
+    const FIREFOX_OLD: &str = "1:65536;4:131072;5:16384|12517377|3:0:0:201,5:0:0:101,7:0:0:1,9:0:7:1,11:0:3:1,13:0:0:241|m,p,a,s";
+
+    #[test]
+    fn test_parse_and_render() {
+        let fingerprint = Fingerprint::from_akamai_str(CHROME).unwrap();
+        assert_eq!(
+            fingerprint.settings(),
+            &[
+                (SettingId::HeaderTableSize, 65536),
+                (SettingId::EnablePush, 0),
+                (SettingId::InitialWindowSize, 6291456),
+                (SettingId::MaxHeaderListSize, 262144),
+            ]
+        );
+        assert_eq!(fingerprint.window_update(), Some(15663105));
+        assert!(fingerprint.priorities().is_empty());
+        assert_eq!(
+            fingerprint.pseudo_order(),
+            &[
+                PseudoId::Method,
+                PseudoId::Authority,
+                PseudoId::Scheme,
+                PseudoId::Path
+            ]
+        );
+        assert_eq!(fingerprint.to_string(), CHROME);
+
+        let fingerprint: Fingerprint = FIREFOX_OLD.parse().unwrap();
+        assert_eq!(fingerprint.priorities().len(), 6);
+        let priority = &fingerprint.priorities()[3];
+        assert_eq!(priority.stream_id(), StreamId::from(9));
+        assert_eq!(priority.dependency().dependency_id(), StreamId::from(7));
+        assert_eq!(priority.dependency().weight(), 0);
+        assert_eq!(fingerprint.to_string(), FIREFOX_OLD);
+    }
+
+    #[test]
+    fn test_parse_without_window_update() {
+        let fingerprint = Fingerprint::from_akamai_str("2:0,3:100|00|0|m,s,p,a").unwrap();
+        assert_eq!(fingerprint.window_update(), None);
+        assert_eq!(fingerprint.to_string(), "2:0;3:100|00|0|m,s,p,a");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for src in [
+            "1:65536|0|m,a,s,p",
+            "1:65536;2|0|0|m,a,s,p",
+            "1:65536|x|0|m,a,s,p",
+            "1:65536|0|3:2:0:201|m,a,s,p",
+            "1:65536|0|3:0:0:257|m,a,s,p",
+            "1:65536|0|0|m,a,x,p",
+            "1:65536|0|0|m,m,s,p",
+        ] {
+            assert!(Fingerprint::from_akamai_str(src).is_err(), "{}", src);
+        }
+    }
+
+    #[test]
+    fn test_builder_roundtrip() {
+        let profile = Fingerprint::from_akamai_str(FIREFOX_OLD)
+            .unwrap()
+            .to_profile()
+            .unwrap();
+        let fingerprint = client::Builder::new().profile(&profile).fingerprint();
+        assert_eq!(fingerprint.to_string(), FIREFOX_OLD);
+
+        let fingerprint = client::Builder::new()
+            .profile(&Profile::chrome())
+            .fingerprint();
+        assert_eq!(fingerprint.to_string(), CHROME);
+    }
+
+    #[test]
+    fn test_recorder_caps_priorities() {
+        let mut recorder = Recorder::default();
+        for id in 0..MAX_RECORDED_PRIORITIES as u32 + 10 {
+            let dependency = StreamDependency::new(StreamId::zero(), 0, false);
+            let frame = Priority::new(StreamId::from(2 * id + 3), dependency);
+            recorder.recv_frame(&frame.into());
+        }
+
+        let headers = crate::frame::Headers::new(
+            StreamId::from(1),
+            crate::frame::Pseudo::request(http::Method::GET, http::Uri::from_static("/"), None),
+            http::HeaderMap::new(),
+        );
+        recorder.recv_frame(&headers.into());
+
+        let fingerprint = recorder.fingerprint().unwrap();
+        assert_eq!(fingerprint.priorities().len(), MAX_RECORDED_PRIORITIES);
+        assert_eq!(
+            fingerprint.priorities().last().unwrap().stream_id(),
+            StreamId::from(2 * MAX_RECORDED_PRIORITIES as u32 + 1)
+        );
+    }
+
+    #[test]
+    fn test_to_profile_rejects_invalid_values() {
+        for src in [
+            "2:2|0|0|m,a,s,p",
+            "5:100|0|0|m,a,s,p",
+            "1:1|2147483647|0|m,a,s,p",
+        ] {
+            let fingerprint = Fingerprint::from_akamai_str(src).unwrap();
+            assert!(fingerprint.to_profile().is_err(), "{}", src);
+        }
+    }
+
+    #[test]
+    fn test_to_profile_unknown_setting() {
+        let fingerprint = Fingerprint::from_akamai_str("1:65536;15:7|00|0|m,a,s,p").unwrap();
+
+        #[cfg(feature = "unstable")]
+        {
+            let profile = fingerprint.to_profile().unwrap();
+            let rendered = client::Builder::new().profile(&profile).fingerprint();
+            assert_eq!(rendered, fingerprint);
+        }
+
+        #[cfg(not(feature = "unstable"))]
+        assert!(fingerprint.to_profile().is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::frame::{self, Error, Head, Kind, StreamId};
+
+use bytes::{BufMut, Bytes};
+
+/// The ALTSVC frame (type=0xa) advertises alternative services, as defined by
+/// [RFC 7838].
+///
+/// An ALTSVC frame sent on stream 0 carries the origin it applies to. An
+/// ALTSVC frame sent on any other stream applies to the origin of that
+/// stream, and carries no origin.
+///
+/// Clients receive the frames sent on stream 0 with
+/// `client::Connection::poll_altsvc`, and the frames sent on a request stream
+/// in the extensions of the response. Servers send them with
+/// `server::Connection::send_altsvc` and `server::SendResponse::send_altsvc`.
+///
+/// [RFC 7838]: https://www.rfc-editor.org/rfc/rfc7838#section-4
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AltSvc {
+    stream_id: StreamId,
+    origin: Bytes,
+    field_value: Bytes,
+}
+
+impl AltSvc {
+    /// Creates a new ALTSVC frame.
+    ///
+    /// The reserved bit of `stream_id` is ignored.
+    pub fn new(stream_id: u32, origin: Bytes, field_value: Bytes) -> AltSvc {
+        AltSvc {
+            stream_id: StreamId::parse(&stream_id.to_be_bytes()).0,
+            origin,
+            field_value,
+        }
+    }
+
+    /// Returns the stream the frame is sent on, `0` for the connection.
+    pub fn stream_id(&self) -> crate::StreamId {
+        crate::StreamId::from_internal(self.stream_id)
+    }
+
+    /// Returns the origin the alternative services apply to.
+    ///
+    /// The origin is empty if the frame is sent on a request stream.
+    pub fn origin(&self) -> &Bytes {
+        &self.origin
+    }
+
+    /// Returns the alternative services, in the format of the `Alt-Svc` header
+    /// field.
+    pub fn field_value(&self) -> &Bytes {
+        &self.field_value
+    }
+
+    /// Builds an `AltSvc` frame from a raw frame.
+    pub(crate) fn load(head: Head, payload: &[u8]) -> Result<AltSvc, Error> {
+        debug_assert_eq!(head.kind(), Kind::AltSvc);
+
+        if payload.len() < 2 {
+            return Err(Error::BadFrameSize);
+        }
+
+        let origin_len = u16::from_be_bytes([payload[0], payload[1]]) as usize;
+
+        if payload.len() < 2 + origin_len {
+            return Err(Error::BadFrameSize);
+        }
+
+        Ok(AltSvc {
+            stream_id: head.stream_id(),
+            origin: Bytes::copy_from_slice(&payload[2..2 + origin_len]),
+            field_value: Bytes::copy_from_slice(&payload[2 + origin_len..]),
+        })
+    }
+
+    /// Returns true if the frame carries an origin if, and only if, it is sent
+    /// on stream 0. Other frames must be ignored.
+    pub(crate) fn is_valid(&self) -> bool {
+        self.stream_id.is_zero() != self.origin.is_empty()
+    }
+
+    pub(crate) fn payload_len(&self) -> usize {
+        2 + self.origin.len() + self.field_value.len()
+    }
+
+    pub(crate) fn encode<B: BufMut>(&self, dst: &mut B) {
+        tracing::trace!("encoding ALTSVC; id={:?}", self.stream_id);
+        let head = Head::new(Kind::AltSvc, 0, self.stream_id);
+        head.encode(self.payload_len(), dst);
+        dst.put_u16(self.origin.len() as u16);
+        dst.put_slice(&self.origin);
+        dst.put_slice(&self.field_value);
+    }
+}
+
+impl<B> From<AltSvc> for frame::Frame<B> {
+    fn from(src: AltSvc) -> Self {
+        frame::Frame::AltSvc(src)
+    }
+}
+
+/// Returns true if `origin` is a serialized origin, as defined by
+/// [RFC 6454], such as `https://example.com:8443`.
+///
+/// [RFC 6454]: https://www.rfc-editor.org/rfc/rfc6454#section-6.2
+pub(crate) fn is_valid_origin(origin: &str) -> bool {
+    let uri = match origin.parse::<http::Uri>() {
+        Ok(uri) => uri,
+        Err(_) => return false,
+    };
+
+    let (scheme, authority) = match (uri.scheme_str(), uri.authority()) {
+        (Some(scheme), Some(authority)) => (scheme, authority.as_str()),
+        _ => return false,
+    };
+
+    // The serialization has no userinfo, path or query, and is lowercase.
+    !authority.contains('@')
+        && origin.len() == scheme.len() + 3 + authority.len()
+        && !origin.bytes().any(|b| b.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_altsvc_round_trip() {
+        let frame = AltSvc::new(
+            0,
+            Bytes::from_static(b"https://a.com"),
+            Bytes::from_static(b"h3=\":443\""),
+        );
+
+        let mut buf = Vec::new();
+        frame.encode(&mut buf);
+        assert_eq!(&buf[..11], &[0, 0, 24, 0xa, 0, 0, 0, 0, 0, 0, 13]);
+
+        let head = Head::parse(&buf);
+        let loaded = AltSvc::load(head, &buf[frame::HEADER_LEN..]).unwrap();
+        assert_eq!(loaded, frame);
+        assert!(loaded.is_valid());
+    }
+
+    #[test]
+    fn test_altsvc_invalid() {
+        let head = Head::new(Kind::AltSvc, 0, StreamId::zero());
+        assert_eq!(AltSvc::load(head, &[0]), Err(Error::BadFrameSize));
+        assert_eq!(AltSvc::load(head, &[0, 2, b'a']), Err(Error::BadFrameSize));
+
+        // No origin on stream 0
+        assert!(!AltSvc::load(head, &[0, 0, b'a']).unwrap().is_valid());
+
+        // An origin on a request stream
+        let head = Head::new(Kind::AltSvc, 0, StreamId::from(1));
+        assert!(!AltSvc::load(head, &[0, 1, b'a']).unwrap().is_valid());
+        assert!(AltSvc::load(head, &[0, 0, b'a']).unwrap().is_valid());
+    }
+
+    #[test]
+    fn test_is_valid_origin() {
+        assert!(is_valid_origin("https://example.com"));
+        assert!(is_valid_origin("http://example.com:8080"));
+        assert!(is_valid_origin("https://[::1]:443"));
+
+        assert!(!is_valid_origin(""));
+        assert!(!is_valid_origin("example.com"));
+        assert!(!is_valid_origin("https://example.com/"));
+        assert!(!is_valid_origin("https://example.com/path"));
+        assert!(!is_valid_origin("https://example.com?query"));
+        assert!(!is_valid_origin("https://user@example.com"));
+        assert!(!is_valid_origin("https://Example.com"));
+    }
+}
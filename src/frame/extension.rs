@@ -0,0 +1,121 @@
+use crate::frame::{self, Kind, StreamId};
+
+use bytes::{BufMut, Bytes};
+
+/// A frame of a type not defined by RFC 9113.
+///
+/// Extension frames are sent with `send_extension_frame`, and frames of the
+/// types registered with a `Builder`'s `extension_frame_type` are received
+/// with `poll_extension_frame`. Frames of other unknown types are ignored, as
+/// required by [RFC 9113].
+///
+/// [RFC 9113]: https://www.rfc-editor.org/rfc/rfc9113#section-5.5
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExtensionFrame {
+    kind: u8,
+    flags: u8,
+    stream_id: StreamId,
+    payload: Bytes,
+}
+
+impl ExtensionFrame {
+    /// Creates a new extension frame.
+    ///
+    /// The reserved bit of `stream_id` is ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `kind` is a frame type known to h2.
+    pub fn new(kind: u8, flags: u8, stream_id: u32, payload: Bytes) -> ExtensionFrame {
+        assert!(
+            is_extension_type(kind),
+            "frame type {} is not an extension frame type",
+            kind
+        );
+
+        ExtensionFrame {
+            kind,
+            flags,
+            stream_id: StreamId::parse(&stream_id.to_be_bytes()).0,
+            payload,
+        }
+    }
+
+    /// Returns the frame type.
+    pub fn kind(&self) -> u8 {
+        self.kind
+    }
+
+    /// Returns the frame flags.
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Returns the stream the frame is sent on, `0` for the connection.
+    pub fn stream_id(&self) -> crate::StreamId {
+        crate::StreamId::from_internal(self.stream_id)
+    }
+
+    /// Returns the frame payload.
+    pub fn payload(&self) -> &Bytes {
+        &self.payload
+    }
+
+    /// Consumes the frame, returning its payload.
+    pub fn into_payload(self) -> Bytes {
+        self.payload
+    }
+
+    /// Builds an `ExtensionFrame` from a raw frame, including its header.
+    pub(crate) fn load(frame: &[u8]) -> ExtensionFrame {
+        let head = frame::Head::parse(frame);
+
+        ExtensionFrame {
+            kind: frame[3],
+            flags: head.flag(),
+            stream_id: head.stream_id(),
+            payload: Bytes::copy_from_slice(&frame[frame::HEADER_LEN..]),
+        }
+    }
+
+    pub(crate) fn encode<B: BufMut>(&self, dst: &mut B) {
+        tracing::trace!(kind = self.kind, "encoding extension frame");
+        dst.put_uint(self.payload.len() as u64, 3);
+        dst.put_u8(self.kind);
+        dst.put_u8(self.flags);
+        dst.put_u32(self.stream_id.into());
+        dst.put_slice(&self.payload);
+    }
+}
+
+impl<B> From<ExtensionFrame> for frame::Frame<B> {
+    fn from(src: ExtensionFrame) -> Self {
+        frame::Frame::Extension(src)
+    }
+}
+
+/// Returns true if frames of this type are not handled by h2 itself.
+pub(crate) fn is_extension_type(kind: u8) -> bool {
+    Kind::new(kind) == Kind::Unknown
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extension_frame_round_trip() {
+        let frame = ExtensionFrame::new(0x2a, 1, 3, Bytes::from_static(b"ab"));
+
+        let mut buf = Vec::new();
+        frame.encode(&mut buf);
+        assert_eq!(buf, [0, 0, 2, 0x2a, 1, 0, 0, 0, 3, b'a', b'b']);
+        assert_eq!(ExtensionFrame::load(&buf), frame);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_extension_frame_known_type() {
+        ExtensionFrame::new(0x10, 0, 0, Bytes::new());
+    }
+}
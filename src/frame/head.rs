@@ -22,6 +22,9 @@ pub enum Kind {
     GoAway = 7,
     WindowUpdate = 8,
     Continuation = 9,
+    AltSvc = 10,
+    Origin = 12,
+    PriorityUpdate = 16,
     Unknown,
 }
 
@@ -88,6 +91,9 @@ impl Kind {
             7 => Kind::GoAway,
             8 => Kind::WindowUpdate,
             9 => Kind::Continuation,
+            10 => Kind::AltSvc,
+            12 => Kind::Origin,
+            16 => Kind::PriorityUpdate,
             _ => Kind::Unknown,
         }
     }
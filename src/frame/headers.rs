@@ -1,5 +1,5 @@
 use super::{util, StreamDependency, StreamId};
-use crate::ext::Protocol;
+use crate::ext::{HeaderOrder, Protocol};
 use crate::frame::{Error, Frame, Head, Kind};
 use crate::hpack::{self, BytesStr};
 
@@ -62,7 +62,7 @@ pub struct Continuation {
 }
 
 // TODO: These fields shouldn't be `pub`
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Debug, Default, Eq)]
 pub struct Pseudo {
     // Request
     pub method: Option<Method>,
@@ -78,6 +78,18 @@ pub struct Pseudo {
     pub order: PseudoOrder,
 }
 
+// The order only affects how the fields are encoded, so it is not compared.
+impl PartialEq for Pseudo {
+    fn eq(&self, other: &Pseudo) -> bool {
+        self.method == other.method
+            && self.scheme == other.scheme
+            && self.authority == other.authority
+            && self.path == other.path
+            && self.protocol == other.protocol
+            && self.status == other.status
+    }
+}
+
 define_enum_with_values! {
     /// Represents the order of HTTP/2 pseudo-header fields in the header block.
     ///
@@ -130,6 +142,15 @@ impl PseudoOrder {
             mask: 0,
         }
     }
+
+    /// Moves `id` to `index`, keeping the relative order of the other ids.
+    fn move_to(&mut self, id: PseudoId, index: usize) {
+        if let Some(pos) = self.ids.iter().position(|i| *i == id) {
+            let id = self.ids.remove(pos);
+            let index = index.min(self.ids.len());
+            self.ids.insert(index, id);
+        }
+    }
 }
 
 impl Default for PseudoOrder {
@@ -186,7 +207,16 @@ pub struct Iter {
     pseudo: Option<Pseudo>,
 
     /// Header fields
-    fields: header::IntoIter<HeaderValue>,
+    fields: Fields,
+}
+
+#[derive(Debug)]
+enum Fields {
+    /// Fields in `HeaderMap` iteration order
+    Map(header::IntoIter<HeaderValue>),
+
+    /// Fields in the order given by a `HeaderOrder`
+    Ordered(std::vec::IntoIter<(HeaderName, HeaderValue)>),
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -203,6 +233,9 @@ struct HeaderBlock {
     /// Pseudo headers, these are broken out as they must be sent as part of the
     /// headers frame.
     pseudo: Pseudo,
+
+    /// The order in which to encode the fields, if any.
+    field_order: Option<HeaderOrder>,
 }
 
 #[derive(Debug)]
@@ -229,6 +262,7 @@ impl Headers {
                 fields,
                 is_over_size: false,
                 pseudo,
+                field_order: None,
             },
             flags: HeadersFlag::default(),
         }
@@ -246,6 +280,7 @@ impl Headers {
                 fields,
                 is_over_size: false,
                 pseudo: Pseudo::default(),
+                field_order: None,
             },
             flags,
         }
@@ -311,6 +346,7 @@ impl Headers {
                 field_size: 0,
                 is_over_size: false,
                 pseudo: Pseudo::default(),
+                field_order: None,
             },
             flags,
         };
@@ -364,6 +400,15 @@ impl Headers {
         &self.header_block.pseudo
     }
 
+    /// Sets the order in which the regular fields are encoded.
+    pub fn set_header_order(&mut self, order: HeaderOrder) {
+        self.header_block.field_order = Some(order);
+    }
+
+    pub fn stream_dependency(&self) -> Option<&StreamDependency> {
+        self.stream_dep.as_ref()
+    }
+
     // If the stream dependency is set, the PRIORITY flag must be set
     pub fn set_stream_dependency(&mut self, stream_dep: StreamDependency) {
         self.flags = HeadersFlag(END_HEADERS | PRIORITY);
@@ -482,6 +527,7 @@ impl PushPromise {
                 fields,
                 is_over_size: false,
                 pseudo,
+                field_order: None,
             },
             promised_id,
             stream_id,
@@ -573,6 +619,7 @@ impl PushPromise {
                 field_size: 0,
                 is_over_size: false,
                 pseudo: Pseudo::default(),
+                field_order: None,
             },
             promised_id,
             stream_id: head.stream_id(),
@@ -757,6 +804,21 @@ impl Pseudo {
         self.order = order;
     }
 
+    /// Moves `id` after the pseudo-header fields decoded before it, so that
+    /// `order` reflects the order in which the peer sent them.
+    fn record_order(&mut self, id: PseudoId) {
+        let decoded = [
+            self.method.is_some(),
+            self.scheme.is_some(),
+            self.authority.is_some(),
+            self.path.is_some(),
+            self.protocol.is_some(),
+            self.status.is_some(),
+        ];
+        let index = decoded.iter().filter(|is_some| **is_some).count() - 1;
+        self.order.move_to(id, index);
+    }
+
     /// Whether it has status 1xx
     pub(crate) fn is_informational(&self) -> bool {
         self.status
@@ -863,10 +925,61 @@ impl Iterator for Iter {
 
         self.pseudo = None;
 
-        self.fields
-            .next()
-            .map(|(name, value)| Field { name, value })
+        match self.fields {
+            Fields::Map(ref mut fields) => fields.next().map(|(name, value)| Field { name, value }),
+            Fields::Ordered(ref mut fields) => fields.next().map(|(name, value)| Field {
+                name: Some(name),
+                value,
+            }),
+        }
+    }
+}
+
+/// Lists the fields of `map` in the order described by `order`.
+///
+/// See `ext::HeaderOrder` for how names that are repeated, missing from the
+/// map or missing from the order are handled.
+fn ordered_fields(map: HeaderMap, order: &HeaderOrder) -> Vec<(HeaderName, HeaderValue)> {
+    let mut fields = Vec::with_capacity(map.len());
+
+    // Group the values by name, in map iteration order. A `None` name means
+    // the same name as the previous field.
+    let mut groups: Vec<(HeaderName, Vec<HeaderValue>)> = Vec::new();
+    for (name, value) in map {
+        match name {
+            Some(name) => groups.push((name, vec![value])),
+            None => {
+                if let Some((_, values)) = groups.last_mut() {
+                    values.push(value);
+                }
+            }
+        }
+    }
+    let mut groups: Vec<_> = groups
+        .into_iter()
+        .map(|(name, values)| (name, values.into_iter()))
+        .collect();
+
+    for (i, name) in order.iter().enumerate() {
+        let values = match groups.iter_mut().find(|(n, _)| n == name) {
+            Some((_, values)) => values,
+            None => continue,
+        };
+
+        if order.iter().skip(i + 1).any(|n| n == name) {
+            if let Some(value) = values.next() {
+                fields.push((name.clone(), value));
+            }
+        } else {
+            fields.extend(values.map(|value| (name.clone(), value)));
+        }
+    }
+
+    for (name, values) in groups {
+        fields.extend(values.map(|value| (name.clone(), value)));
     }
+
+    fields
 }
 
 // ===== impl HeadersFlag =====
@@ -989,7 +1102,7 @@ impl HeaderBlock {
         let mut headers_size = self.calculate_header_list_size();
 
         macro_rules! set_pseudo {
-            ($field:ident, $val:expr) => {{
+            ($field:ident, $id:ident, $val:expr) => {{
                 if reg {
                     tracing::trace!("load_hpack; header malformed -- pseudo not at head of block");
                     malformed = true;
@@ -1002,6 +1115,7 @@ impl HeaderBlock {
                         decoded_header_size(stringify!($field).len() + 1, __val.as_str().len());
                     if headers_size < max_header_list_size {
                         self.pseudo.$field = Some(__val);
+                        self.pseudo.record_order(PseudoId::$id);
                     } else if !self.is_over_size {
                         tracing::trace!("load_hpack; header list size over max");
                         self.is_over_size = true;
@@ -1052,12 +1166,12 @@ impl HeaderBlock {
                         }
                     }
                 }
-                Authority(v) => set_pseudo!(authority, v),
-                Method(v) => set_pseudo!(method, v),
-                Scheme(v) => set_pseudo!(scheme, v),
-                Path(v) => set_pseudo!(path, v),
-                Protocol(v) => set_pseudo!(protocol, v),
-                Status(v) => set_pseudo!(status, v),
+                Authority(v) => set_pseudo!(authority, Authority, v),
+                Method(v) => set_pseudo!(method, Method, v),
+                Scheme(v) => set_pseudo!(scheme, Scheme, v),
+                Path(v) => set_pseudo!(path, Path, v),
+                Protocol(v) => set_pseudo!(protocol, Protocol, v),
+                Status(v) => set_pseudo!(status, Status, v),
             }
         });
 
@@ -1076,9 +1190,13 @@ impl HeaderBlock {
 
     fn into_encoding(self, encoder: &mut hpack::Encoder) -> EncodingHeaderBlock {
         let mut hpack = BytesMut::new();
+        let fields = match self.field_order {
+            Some(ref order) => Fields::Ordered(ordered_fields(self.fields, order).into_iter()),
+            None => Fields::Map(self.fields.into_iter()),
+        };
         let headers = Iter {
             pseudo: Some(self.pseudo),
-            fields: self.fields.into_iter(),
+            fields,
         };
 
         encoder.encode(headers, &mut hpack);
@@ -1368,4 +1486,94 @@ mod test {
         assert_eq!(order.ids[0], PseudoId::Scheme);
         assert_ne!(order.ids[1], PseudoId::Scheme);
     }
+
+    #[test]
+    fn test_encode_with_header_order() {
+        let mut encoder = Encoder::default();
+        let mut dst = BytesMut::new();
+
+        let mut fields = HeaderMap::new();
+        fields.append("cookie", HeaderValue::from_static("a=1"));
+        fields.append("cookie", HeaderValue::from_static("b=2"));
+        fields.append("cookie", HeaderValue::from_static("c=3"));
+        fields.append("accept", HeaderValue::from_static("*/*"));
+        fields.append("user-agent", HeaderValue::from_static("h2"));
+        fields.append("x-unlisted", HeaderValue::from_static("1"));
+
+        let order: HeaderOrder = ["user-agent", "cookie", "accept", "x-missing", "cookie"]
+            .iter()
+            .map(|name| HeaderName::from_static(name))
+            .collect();
+
+        let mut headers = Headers::new(StreamId::from(1), Pseudo::default(), fields);
+        headers.set_header_order(order);
+        assert!(headers
+            .encode(&mut encoder, &mut (&mut dst).limit(1024))
+            .is_none());
+
+        let mut decoded = Vec::new();
+        let mut payload = dst.split_off(frame::HEADER_LEN);
+        hpack::Decoder::new(4096)
+            .decode(&mut Cursor::new(&mut payload), |header| {
+                if let hpack::Header::Field { name, value } = header {
+                    decoded.push(format!("{}: {}", name, value.to_str().unwrap()));
+                }
+            })
+            .unwrap();
+
+        assert_eq!(
+            decoded,
+            [
+                "user-agent: h2",
+                "cookie: a=1",
+                "accept: */*",
+                "cookie: b=2",
+                "cookie: c=3",
+                "x-unlisted: 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_records_pseudo_order() {
+        let mut encoder = Encoder::default();
+        let mut dst = BytesMut::new();
+
+        let mut pseudo =
+            Pseudo::request(Method::GET, Uri::from_static("https://example.com/"), None);
+        pseudo.set_pseudo_order(
+            PseudoOrder::builder()
+                .extend([
+                    PseudoId::Method,
+                    PseudoId::Authority,
+                    PseudoId::Scheme,
+                    PseudoId::Path,
+                ])
+                .build(),
+        );
+        let headers = Headers::new(StreamId::from(1), pseudo, HeaderMap::new());
+        assert!(headers
+            .encode(&mut encoder, &mut (&mut dst).limit(1024))
+            .is_none());
+
+        let head = Head::parse(&dst);
+        let (mut headers, mut payload) =
+            Headers::load(head, dst.split_off(frame::HEADER_LEN)).unwrap();
+        let mut decoder = hpack::Decoder::new(4096);
+        headers
+            .load_hpack(&mut payload, 16 << 20, &mut decoder)
+            .unwrap();
+
+        assert_eq!(
+            headers.pseudo().order.ids.as_slice(),
+            [
+                PseudoId::Method,
+                PseudoId::Authority,
+                PseudoId::Scheme,
+                PseudoId::Path,
+                PseudoId::Protocol,
+                PseudoId::Status,
+            ]
+        );
+    }
 }
@@ -37,12 +37,16 @@ mod tests {
 
 #[macro_use]
 mod macros;
+mod altsvc;
 mod data;
+mod extension;
 mod go_away;
 mod head;
 mod headers;
+mod origin;
 mod ping;
 mod priority;
+mod priority_update;
 mod reason;
 mod reset;
 mod settings;
@@ -50,7 +54,11 @@ mod stream_id;
 mod util;
 mod window_update;
 
+pub(crate) use self::altsvc::is_valid_origin;
+pub use self::altsvc::AltSvc;
 pub use self::data::Data;
+pub(crate) use self::extension::is_extension_type;
+pub use self::extension::ExtensionFrame;
 pub use self::go_away::GoAway;
 pub use self::head::{Head, Kind};
 #[allow(unused_imports)]
@@ -58,9 +66,11 @@ pub use self::headers::{
     parse_u64, Continuation, Headers, Pseudo, PseudoId, PseudoOrder, PseudoOrderBuilder,
     PushPromise, PushPromiseHeaderError,
 };
+pub use self::origin::Origin;
 pub use self::ping::Ping;
 #[allow(unused_imports)]
 pub use self::priority::{Priorities, PrioritiesBuilder, Priority, StreamDependency};
+pub use self::priority_update::PriorityUpdate;
 pub use self::reason::Reason;
 pub use self::reset::Reset;
 #[cfg(feature = "unstable")]
@@ -95,6 +105,10 @@ pub enum Frame<T = Bytes> {
     GoAway(GoAway),
     WindowUpdate(WindowUpdate),
     Reset(Reset),
+    PriorityUpdate(PriorityUpdate),
+    AltSvc(AltSvc),
+    Origin(Origin),
+    Extension(ExtensionFrame),
 }
 
 impl<T> Frame<T> {
@@ -114,6 +128,10 @@ impl<T> Frame<T> {
             GoAway(frame) => frame.into(),
             WindowUpdate(frame) => frame.into(),
             Reset(frame) => frame.into(),
+            PriorityUpdate(frame) => frame.into(),
+            AltSvc(frame) => frame.into(),
+            Origin(frame) => frame.into(),
+            Extension(frame) => frame.into(),
         }
     }
 }
@@ -132,6 +150,10 @@ impl<T> fmt::Debug for Frame<T> {
             GoAway(ref frame) => fmt::Debug::fmt(frame, fmt),
             WindowUpdate(ref frame) => fmt::Debug::fmt(frame, fmt),
             Reset(ref frame) => fmt::Debug::fmt(frame, fmt),
+            PriorityUpdate(ref frame) => fmt::Debug::fmt(frame, fmt),
+            AltSvc(ref frame) => fmt::Debug::fmt(frame, fmt),
+            Origin(ref frame) => fmt::Debug::fmt(frame, fmt),
+            Extension(ref frame) => fmt::Debug::fmt(frame, fmt),
         }
     }
 }
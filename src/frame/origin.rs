@@ -0,0 +1,110 @@
+use crate::frame::{self, Error, Head, Kind, StreamId};
+
+use bytes::{BufMut, Bytes};
+
+/// The ORIGIN frame (type=0xc) lists the origins a server is authoritative
+/// for on the connection, as defined by [RFC 8336].
+///
+/// The frame is always sent on stream 0.
+///
+/// [RFC 8336]: https://www.rfc-editor.org/rfc/rfc8336#section-2
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Origin {
+    origins: Vec<Bytes>,
+}
+
+impl Origin {
+    pub fn new(origins: Vec<Bytes>) -> Origin {
+        Origin { origins }
+    }
+
+    /// The ASCII serialized origins carried by the frame.
+    pub fn origins(&self) -> &[Bytes] {
+        &self.origins
+    }
+
+    /// Builds an `Origin` frame from a raw frame.
+    pub fn load(head: Head, payload: &[u8]) -> Result<Origin, Error> {
+        debug_assert_eq!(head.kind(), Kind::Origin);
+
+        if !head.stream_id().is_zero() {
+            return Err(Error::InvalidStreamId);
+        }
+
+        let mut origins = Vec::new();
+        let mut rem = payload;
+
+        while !rem.is_empty() {
+            if rem.len() < 2 {
+                return Err(Error::BadFrameSize);
+            }
+
+            let len = u16::from_be_bytes([rem[0], rem[1]]) as usize;
+
+            if rem.len() < 2 + len {
+                return Err(Error::BadFrameSize);
+            }
+
+            origins.push(Bytes::copy_from_slice(&rem[2..2 + len]));
+            rem = &rem[2 + len..];
+        }
+
+        Ok(Origin { origins })
+    }
+
+    pub fn payload_len(&self) -> usize {
+        self.origins.iter().map(|origin| 2 + origin.len()).sum()
+    }
+
+    pub fn encode<B: BufMut>(&self, dst: &mut B) {
+        tracing::trace!("encoding ORIGIN; origins={:?}", self.origins);
+        let head = Head::new(Kind::Origin, 0, StreamId::zero());
+        head.encode(self.payload_len(), dst);
+
+        for origin in &self.origins {
+            dst.put_u16(origin.len() as u16);
+            dst.put_slice(origin);
+        }
+    }
+}
+
+impl<B> From<Origin> for frame::Frame<B> {
+    fn from(src: Origin) -> Self {
+        frame::Frame::Origin(src)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_origin_round_trip() {
+        let frame = Origin::new(vec![
+            Bytes::from_static(b"https://a.com"),
+            Bytes::from_static(b"https://b.com:8443"),
+        ]);
+
+        let mut buf = Vec::new();
+        frame.encode(&mut buf);
+        assert_eq!(&buf[..11], &[0, 0, 35, 0xc, 0, 0, 0, 0, 0, 0, 13]);
+
+        let head = Head::parse(&buf);
+        let loaded = Origin::load(head, &buf[frame::HEADER_LEN..]).unwrap();
+        assert_eq!(loaded, frame);
+    }
+
+    #[test]
+    fn test_origin_invalid() {
+        let head = Head::new(Kind::Origin, 0, StreamId::from(1));
+        assert_eq!(Origin::load(head, &[]), Err(Error::InvalidStreamId));
+
+        let head = Head::new(Kind::Origin, 0, StreamId::zero());
+        assert_eq!(Origin::load(head, &[0]), Err(Error::BadFrameSize));
+        assert_eq!(
+            Origin::load(head, &[0, 1, b'a', 0, 2, b'b']),
+            Err(Error::BadFrameSize)
+        );
+        assert_eq!(Origin::load(head, &[]), Ok(Origin::new(vec![])));
+    }
+}
@@ -84,6 +84,10 @@ impl Priority {
         self.stream_id
     }
 
+    pub fn dependency(&self) -> StreamDependency {
+        self.dependency
+    }
+
     pub fn encode<B: BufMut>(&self, dst: &mut B) {
         let head = self.head();
         head.encode(5, dst);
@@ -201,6 +205,15 @@ impl Priorities {
     }
 }
 
+impl<'a> IntoIterator for &'a Priorities {
+    type Item = &'a Priority;
+    type IntoIter = std::slice::Iter<'a, Priority>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.priorities.iter()
+    }
+}
+
 impl IntoIterator for Priorities {
     type Item = Priority;
     type IntoIter = std::vec::IntoIter<Priority>;
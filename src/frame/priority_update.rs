@@ -0,0 +1,116 @@
+use crate::frame::{self, Error, Head, Kind, StreamId};
+
+use bytes::{BufMut, Bytes};
+
+/// The PRIORITY_UPDATE frame (type=0x10) carries the priority of a stream, as
+/// defined by [RFC 9218].
+///
+/// The frame is always sent on stream 0, the prioritized stream is identified
+/// in its payload.
+///
+/// [RFC 9218]: https://www.rfc-editor.org/rfc/rfc9218#section-7.1
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PriorityUpdate {
+    prioritized_stream_id: StreamId,
+    field_value: Bytes,
+}
+
+impl PriorityUpdate {
+    pub fn new(prioritized_stream_id: StreamId, field_value: Bytes) -> PriorityUpdate {
+        PriorityUpdate {
+            prioritized_stream_id,
+            field_value,
+        }
+    }
+
+    pub fn prioritized_stream_id(&self) -> StreamId {
+        self.prioritized_stream_id
+    }
+
+    /// The Priority Field Value, in the format of the `priority` header field.
+    pub fn field_value(&self) -> &Bytes {
+        &self.field_value
+    }
+
+    /// Builds a `PriorityUpdate` frame from a raw frame.
+    pub fn load(head: Head, payload: &[u8]) -> Result<PriorityUpdate, Error> {
+        debug_assert_eq!(head.kind(), Kind::PriorityUpdate);
+
+        if !head.stream_id().is_zero() {
+            return Err(Error::InvalidStreamId);
+        }
+
+        if payload.len() < 4 {
+            return Err(Error::BadFrameSize);
+        }
+
+        let (prioritized_stream_id, _) = StreamId::parse(&payload[..4]);
+
+        if prioritized_stream_id.is_zero() {
+            return Err(Error::InvalidStreamId);
+        }
+
+        Ok(PriorityUpdate {
+            prioritized_stream_id,
+            field_value: Bytes::copy_from_slice(&payload[4..]),
+        })
+    }
+
+    pub fn payload_len(&self) -> usize {
+        4 + self.field_value.len()
+    }
+
+    pub fn encode<B: BufMut>(&self, dst: &mut B) {
+        tracing::trace!(
+            "encoding PRIORITY_UPDATE; id={:?}",
+            self.prioritized_stream_id
+        );
+        let head = Head::new(Kind::PriorityUpdate, 0, StreamId::zero());
+        head.encode(self.payload_len(), dst);
+        dst.put_u32(self.prioritized_stream_id.into());
+        dst.put_slice(&self.field_value);
+    }
+}
+
+impl<B> From<PriorityUpdate> for frame::Frame<B> {
+    fn from(src: PriorityUpdate) -> Self {
+        frame::Frame::PriorityUpdate(src)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_priority_update_round_trip() {
+        let frame = PriorityUpdate::new(StreamId::from(5), Bytes::from_static(b"u=1, i"));
+
+        let mut buf = Vec::new();
+        frame.encode(&mut buf);
+        assert_eq!(&buf[..9], &[0, 0, 10, 0x10, 0, 0, 0, 0, 0]);
+
+        let head = Head::parse(&buf);
+        let loaded = PriorityUpdate::load(head, &buf[frame::HEADER_LEN..]).unwrap();
+        assert_eq!(loaded, frame);
+    }
+
+    #[test]
+    fn test_priority_update_invalid() {
+        let head = Head::new(Kind::PriorityUpdate, 0, StreamId::from(1));
+        assert_eq!(
+            PriorityUpdate::load(head, &[0, 0, 0, 1]),
+            Err(Error::InvalidStreamId)
+        );
+
+        let head = Head::new(Kind::PriorityUpdate, 0, StreamId::zero());
+        assert_eq!(
+            PriorityUpdate::load(head, &[0, 0, 0, 0]),
+            Err(Error::InvalidStreamId)
+        );
+        assert_eq!(
+            PriorityUpdate::load(head, &[0, 0, 1]),
+            Err(Error::BadFrameSize)
+        );
+    }
+}
@@ -236,10 +236,20 @@ pub struct Settings {
     no_rfc7540_priorities: Option<u32>,
     #[cfg(feature = "unstable")]
     experimental_settings: Option<ExperimentalSettings>,
+    // Received settings with an id unknown to h2
+    unknown: Vec<(u16, u32)>,
     // Settings order
     settings_order: SettingsOrder,
+    // Ids of the received settings, in the order they were first sent in
+    received_order: ReceivedOrder,
 }
 
+/// The ids of the parameters of a received SETTINGS frame, in wire order.
+///
+/// It is ignored when comparing frames, as it does not change the settings.
+#[derive(Clone, Debug, Default)]
+struct ReceivedOrder(Vec<u16>);
+
 /// An enum that lists all valid settings that can be sent in a SETTINGS
 /// frame.
 ///
@@ -271,6 +281,9 @@ pub const MAX_INITIAL_WINDOW_SIZE: usize = (1 << 31) - 1;
 /// MAX_FRAME_SIZE upper bound
 pub const MAX_MAX_FRAME_SIZE: FrameSize = (1 << 24) - 1;
 
+/// The maximum number of unknown settings kept from a received SETTINGS frame
+const MAX_UNKNOWN_SETTINGS: usize = 32;
+
 // ===== impl Settings =====
 
 impl Settings {
@@ -344,6 +357,10 @@ impl Settings {
         self.header_table_size = size;
     }
 
+    pub fn is_no_rfc7540_priorities(&self) -> Option<bool> {
+        self.no_rfc7540_priorities.map(|val| val != 0)
+    }
+
     pub fn set_no_rfc7540_priorities(&mut self, enable: bool) {
         self.no_rfc7540_priorities = Some(enable as u32);
     }
@@ -353,6 +370,10 @@ impl Settings {
         self.experimental_settings = Some(experimental_settings)
     }
 
+    pub fn settings_order(&self) -> &SettingsOrder {
+        &self.settings_order
+    }
+
     pub fn set_settings_order(&mut self, settings_order: SettingsOrder) {
         self.settings_order = settings_order;
     }
@@ -387,6 +408,16 @@ impl Settings {
         debug_assert!(!settings.flags.is_ack());
 
         for raw in payload.chunks(6) {
+            let id: u16 = (u16::from(raw[0]) << 8) | u16::from(raw[1]);
+
+            if let SettingId::Unknown(_) = SettingId::from(id) {
+                let val: u32 = unpack_octets_4!(raw, 2, u32);
+                if settings.recv_unknown(id, val) {
+                    settings.received_order.push(id);
+                }
+                continue;
+            }
+
             if let Some(setting) = Setting::load(raw) {
                 match setting.id {
                     SettingId::HeaderTableSize => {
@@ -438,16 +469,81 @@ impl Settings {
                             return Err(Error::InvalidSettingValue);
                         }
                     },
-                    SettingId::Unknown(_) => {
-                        // ignore unknown settings
-                    }
+                    SettingId::Unknown(_) => unreachable!(),
                 }
+
+                settings.received_order.push(id);
             }
         }
 
         Ok(settings)
     }
 
+    /// Stores an unknown setting, returning whether it was kept.
+    fn recv_unknown(&mut self, id: u16, val: u32) -> bool {
+        if let Some(entry) = self.unknown.iter_mut().find(|(i, _)| *i == id) {
+            entry.1 = val;
+        } else if self.unknown.len() < MAX_UNKNOWN_SETTINGS {
+            self.unknown.push((id, val));
+        } else {
+            tracing::debug!("ignoring unknown setting; id={}", id);
+            return false;
+        }
+        true
+    }
+
+    /// Calls `f` with the id and value of every setting, including the
+    /// received settings with an id unknown to h2.
+    ///
+    /// Received settings are passed in the order they were first sent in,
+    /// other settings in the order they are encoded in.
+    pub(crate) fn for_each_parameter<F: FnMut(u16, u32)>(&self, mut f: F) {
+        if !self.received_order.0.is_empty() {
+            for &id in &self.received_order.0 {
+                if let Some(val) = self.value(id) {
+                    f(id, val);
+                }
+            }
+            return;
+        }
+
+        self.for_each(|setting| f(setting.id.into(), setting.value));
+
+        for &(id, val) in &self.unknown {
+            f(id, val);
+        }
+    }
+
+    /// Returns the value of the setting `id`, if it is set.
+    fn value(&self, id: u16) -> Option<u32> {
+        match SettingId::from(id) {
+            SettingId::HeaderTableSize => self.header_table_size,
+            SettingId::EnablePush => self.enable_push,
+            SettingId::MaxConcurrentStreams => self.max_concurrent_streams,
+            SettingId::InitialWindowSize => self.initial_window_size,
+            SettingId::MaxFrameSize => self.max_frame_size,
+            SettingId::MaxHeaderListSize => self.max_header_list_size,
+            SettingId::EnableConnectProtocol => self.enable_connect_protocol,
+            SettingId::NoRfc7540Priorities => self.no_rfc7540_priorities,
+            SettingId::Unknown(_) => self
+                .unknown
+                .iter()
+                .find(|&&(i, _)| i == id)
+                .map(|&(_, val)| val),
+        }
+    }
+
+    /// Returns the parameters of a SETTINGS frame payload in wire order,
+    /// including the unknown ones that `load` ignores.
+    pub(crate) fn load_parameters(payload: &[u8]) -> impl Iterator<Item = (SettingId, u32)> + '_ {
+        payload.chunks_exact(6).map(|raw| {
+            let id: u16 = (u16::from(raw[0]) << 8) | u16::from(raw[1]);
+            let val: u32 = unpack_octets_4!(raw, 2, u32);
+
+            (SettingId::from(id), val)
+        })
+    }
+
     fn payload_len(&self) -> usize {
         let mut len = 0;
         self.for_each(|_| len += 6);
@@ -470,7 +566,7 @@ impl Settings {
         });
     }
 
-    fn for_each<F: FnMut(Setting)>(&self, mut f: F) {
+    pub(crate) fn for_each<F: FnMut(Setting)>(&self, mut f: F) {
         for id in &self.settings_order {
             match id {
                 SettingId::HeaderTableSize => {
@@ -586,10 +682,32 @@ impl fmt::Debug for Settings {
             }
         });
 
+        for (id, val) in &self.unknown {
+            builder.field("unknown", &format!("id={id}, val={val}"));
+        }
+
         builder.finish()
     }
 }
 
+// ===== impl ReceivedOrder =====
+
+impl ReceivedOrder {
+    fn push(&mut self, id: u16) {
+        if !self.0.contains(&id) {
+            self.0.push(id);
+        }
+    }
+}
+
+impl PartialEq for ReceivedOrder {
+    fn eq(&self, _: &ReceivedOrder) -> bool {
+        true
+    }
+}
+
+impl Eq for ReceivedOrder {}
+
 // ===== impl Setting =====
 
 impl Setting {
@@ -608,6 +726,14 @@ impl Setting {
         Some(Setting { id, value })
     }
 
+    pub fn id(&self) -> SettingId {
+        self.id
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
     /// Creates a new `Setting` by parsing the given buffer of 6 bytes, which
     /// contains the raw byte representation of the setting, according to the
     /// "SETTINGS format" defined in section 6.5.1.
@@ -707,11 +833,29 @@ mod test {
         assert_ne!(order.ids[1], SettingId::HeaderTableSize);
     }
 
+    #[test]
+    fn test_load_unknown_settings() {
+        let head = Head::new(Kind::Settings, 0, StreamId::zero());
+        let payload = [
+            0, 4, 0, 0, 0xff, 0xff, // INITIAL_WINDOW_SIZE
+            0x0a, 0x0a, 0, 0, 0, 1, // unknown
+            0x0a, 0x0a, 0, 0, 0, 2, // unknown, again
+            0, 0x10, 0, 0, 0, 3, // unknown
+        ];
+
+        let settings = Settings::load(head, &payload).unwrap();
+        assert_eq!(settings.initial_window_size(), Some(0xffff));
+
+        let mut parameters = Vec::new();
+        settings.for_each_parameter(|id, val| parameters.push((id, val)));
+        assert_eq!(parameters, [(4, 0xffff), (0x0a0a, 2), (0x10, 3)]);
+    }
+
     #[cfg(feature = "unstable")]
     #[test]
     fn test_experimental_settings_builder() {
         // ignore id > SettingId::MAX_ID
-        assert!(SettingId::MAX_ID < 16);
+        const _: () = assert!(SettingId::MAX_ID < 16);
 
         let unknown = ExperimentalSettings::builder()
             .extend(vec![
@@ -1,5 +1,7 @@
+use super::field_log::FieldLog;
 use super::{header::BytesStr, huffman, Header};
 use crate::frame;
+use crate::stats::HpackStats;
 
 use bytes::{Buf, Bytes, BytesMut};
 use http::header;
@@ -19,6 +21,7 @@ pub struct Decoder {
     last_max_update: usize,
     table: Table,
     buffer: BytesMut,
+    log: FieldLog,
 }
 
 /// Represents all errors that can be encountered while performing the decoding
@@ -158,9 +161,25 @@ impl Decoder {
             last_max_update: size,
             table: Table::new(size),
             buffer: BytesMut::with_capacity(4096),
+            log: FieldLog::default(),
         }
     }
 
+    /// Returns the counts of the header fields decoded so far.
+    pub fn stats(&self) -> &HpackStats {
+        self.log.stats()
+    }
+
+    /// Starts keeping the header fields decoded, for `take_fields`.
+    pub fn keep_fields(&mut self) {
+        self.log.keep_fields();
+    }
+
+    /// Takes the header fields decoded since the last call, if they are kept.
+    pub fn take_fields(&mut self) -> Option<Vec<(Bytes, Bytes)>> {
+        self.log.take_fields()
+    }
+
     /// Queues a potential size update
     #[allow(dead_code)]
     pub fn queue_size_update(&mut self, size: usize) {
@@ -194,6 +213,12 @@ impl Decoder {
 
         tracing::trace!("decode");
 
+        let start = src.remaining();
+        let mut emit = |entry: Header, log: &mut FieldLog, is_hit: bool| {
+            log.record_field(entry.name().as_slice(), entry.value_slice(), is_hit);
+            f(entry);
+        };
+
         while let Some(ty) = peek_u8(src) {
             // At this point we are always at the beginning of the next block
             // within the HPACK data. The type of the block can always be
@@ -202,9 +227,9 @@ impl Decoder {
                 Indexed => {
                     tracing::trace!(rem = src.remaining(), kind = %"Indexed");
                     can_resize = false;
-                    let entry = self.decode_indexed(src)?;
+                    let (entry, is_hit) = self.decode_indexed(src)?;
                     consume(src);
-                    f(entry);
+                    emit(entry, &mut self.log, is_hit);
                 }
                 LiteralWithIndexing => {
                     tracing::trace!(rem = src.remaining(), kind = %"LiteralWithIndexing");
@@ -215,14 +240,14 @@ impl Decoder {
                     self.table.insert(entry.clone());
                     consume(src);
 
-                    f(entry);
+                    emit(entry, &mut self.log, false);
                 }
                 LiteralWithoutIndexing => {
                     tracing::trace!(rem = src.remaining(), kind = %"LiteralWithoutIndexing");
                     can_resize = false;
                     let entry = self.decode_literal(src, false)?;
                     consume(src);
-                    f(entry);
+                    emit(entry, &mut self.log, false);
                 }
                 LiteralNeverIndexed => {
                     tracing::trace!(rem = src.remaining(), kind = %"LiteralNeverIndexed");
@@ -232,7 +257,7 @@ impl Decoder {
 
                     // TODO: Track that this should never be indexed
 
-                    f(entry);
+                    emit(entry, &mut self.log, false);
                 }
                 SizeUpdate => {
                     tracing::trace!(rem = src.remaining(), kind = %"SizeUpdate");
@@ -247,6 +272,8 @@ impl Decoder {
             }
         }
 
+        self.log.record_block(start - src.remaining());
+
         Ok(())
     }
 
@@ -268,9 +295,14 @@ impl Decoder {
         Ok(())
     }
 
-    fn decode_indexed(&self, buf: &mut Cursor<&mut BytesMut>) -> Result<Header, DecoderError> {
+    /// Decodes an indexed header field, returning whether the index refers to
+    /// the dynamic table.
+    fn decode_indexed(
+        &self,
+        buf: &mut Cursor<&mut BytesMut>,
+    ) -> Result<(Header, bool), DecoderError> {
         let index = decode_int(buf, 7)?;
-        self.table.get(index)
+        Ok((self.table.get(index)?, index > 61))
     }
 
     fn decode_literal(
@@ -1,13 +1,21 @@
+use super::field_log::FieldLog;
 use super::table::{Index, Table};
-use super::{huffman, Header};
+use super::{huffman, EncodingPolicy, Header, Indexing};
+use crate::stats::HpackStats;
 
-use bytes::{BufMut, BytesMut};
+use bytes::{BufMut, Bytes, BytesMut};
 use http::header::{HeaderName, HeaderValue};
 
+use std::mem;
+use std::sync::Arc;
+
 #[derive(Debug)]
 pub struct Encoder {
     table: Table,
     size_update: Option<SizeUpdate>,
+    policy: Option<Arc<dyn EncodingPolicy>>,
+    is_first_block: bool,
+    log: FieldLog,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -21,9 +29,32 @@ impl Encoder {
         Encoder {
             table: Table::new(max_size, capacity),
             size_update: None,
+            policy: None,
+            is_first_block: true,
+            log: FieldLog::default(),
         }
     }
 
+    /// Returns the counts of the header fields encoded so far.
+    pub fn stats(&self) -> &HpackStats {
+        self.log.stats()
+    }
+
+    /// Starts keeping the header fields encoded, for `take_fields`.
+    pub fn keep_fields(&mut self) {
+        self.log.keep_fields();
+    }
+
+    /// Takes the header fields encoded since the last call, if they are kept.
+    pub fn take_fields(&mut self) -> Option<Vec<(Bytes, Bytes)>> {
+        self.log.take_fields()
+    }
+
+    /// Sets the policy deciding how each header field is encoded.
+    pub fn set_policy(&mut self, policy: Arc<dyn EncodingPolicy>) {
+        self.policy = Some(policy);
+    }
+
     /// Queues a max size update.
     ///
     /// The next call to `encode` will include a dynamic size update frame.
@@ -65,8 +96,32 @@ impl Encoder {
         let span = tracing::trace_span!("hpack::encode");
         let _e = span.enter();
 
+        let start = dst.len();
+        self.encode_block(headers, dst);
+        self.log.record_block(dst.len() - start);
+    }
+
+    fn encode_block<I>(&mut self, headers: I, dst: &mut BytesMut)
+    where
+        I: IntoIterator<Item = Header<Option<HeaderName>>>,
+    {
+        if mem::take(&mut self.is_first_block) {
+            let size_update = self
+                .policy
+                .as_ref()
+                .map_or(false, |policy| policy.size_update_in_first_block());
+
+            if size_update && self.size_update.is_none() {
+                self.size_update = Some(SizeUpdate::One(self.table.max_size()));
+            }
+        }
+
         self.encode_size_updates(dst);
 
+        if let Some(policy) = self.policy.clone() {
+            return self.encode_with_policy(&*policy, headers, dst);
+        }
+
         let mut last_index = None;
 
         for header in headers {
@@ -75,7 +130,8 @@ impl Encoder {
                 // index it in the table.
                 Ok(header) => {
                     let index = self.table.index(header);
-                    self.encode_header(&index, dst);
+                    self.record_field(&index, None);
+                    self.encode_header(&index, None, false, dst);
 
                     last_index = Some(index);
                 }
@@ -84,18 +140,77 @@ impl Encoder {
                 // which case, we skip table lookup and just use the same index
                 // as the previous entry.
                 Err(value) => {
-                    self.encode_header_without_name(
-                        last_index.as_ref().unwrap_or_else(|| {
-                            panic!("encoding header without name, but no previous index to use for name");
-                        }),
-                        &value,
-                        dst,
-                    );
+                    let last_index = last_index.as_ref().unwrap_or_else(|| {
+                        panic!(
+                            "encoding header without name, but no previous index to use for name"
+                        );
+                    });
+                    self.record_field(last_index, Some(&value));
+                    self.encode_header_without_name(last_index, &value, dst);
                 }
             }
         }
     }
 
+    fn encode_with_policy<I>(&mut self, policy: &dyn EncodingPolicy, headers: I, dst: &mut BytesMut)
+    where
+        I: IntoIterator<Item = Header<Option<HeaderName>>>,
+    {
+        let mut last_name = None;
+
+        for header in headers {
+            // Every field goes through the policy, so fields without a name
+            // are looked up again with the name of the previous field.
+            let header = match header.reify() {
+                Ok(header) => {
+                    if let Header::Field { ref name, .. } = header {
+                        last_name = Some(name.clone());
+                    }
+                    header
+                }
+                Err(value) => Header::Field {
+                    name: last_name.clone().unwrap_or_else(|| {
+                        panic!("encoding header without name, but no previous name to use");
+                    }),
+                    value,
+                },
+            };
+
+            let indexing = if header.is_sensitive() {
+                Indexing::NeverIndexed
+            } else {
+                policy.indexing(header.name().as_slice(), header.value_slice())
+            };
+
+            let index = match indexing {
+                Indexing::Incremental => self.table.index_incremental(header),
+                Indexing::WithoutIndexing => self.table.find(header, true),
+                Indexing::NeverIndexed => self.table.find(header, false),
+            };
+
+            self.record_field(&index, None);
+            self.encode_header(
+                &index,
+                Some(policy),
+                indexing == Indexing::NeverIndexed,
+                dst,
+            );
+        }
+    }
+
+    /// Records an encoded header field. `value` is set if the field reuses
+    /// the name of the field of `index`.
+    fn record_field(&mut self, index: &Index, value: Option<&HeaderValue>) {
+        let header = self.table.resolve(index);
+        let is_hit = value.is_none() && index.is_dynamic_table_hit();
+        let value = match value {
+            Some(value) => value.as_bytes(),
+            None => header.value_slice(),
+        };
+        self.log
+            .record_field(header.name().as_slice(), value, is_hit);
+    }
+
     fn encode_size_updates(&mut self, dst: &mut BytesMut) {
         match self.size_update.take() {
             Some(SizeUpdate::One(val)) => {
@@ -112,41 +227,62 @@ impl Encoder {
         }
     }
 
-    fn encode_header(&mut self, index: &Index, dst: &mut BytesMut) {
+    fn encode_header(
+        &mut self,
+        index: &Index,
+        policy: Option<&dyn EncodingPolicy>,
+        never_index: bool,
+        dst: &mut BytesMut,
+    ) {
+        let huffman = |string: &[u8]| policy.map_or(true, |policy| policy.huffman(string));
+
         match *index {
             Index::Indexed(idx, _) => {
                 encode_int(idx, 7, 0x80, dst);
             }
             Index::Name(idx, _) => {
                 let header = self.table.resolve(index);
+                let value = header.value_slice();
 
-                encode_not_indexed(idx, header.value_slice(), header.is_sensitive(), dst);
+                encode_not_indexed(
+                    idx,
+                    value,
+                    header.is_sensitive() || never_index,
+                    huffman(value),
+                    dst,
+                );
             }
             Index::Inserted(_) => {
                 let header = self.table.resolve(index);
+                let name = header.name();
+                let value = header.value_slice();
 
                 assert!(!header.is_sensitive());
 
                 dst.put_u8(0b0100_0000);
 
-                encode_str(header.name().as_slice(), dst);
-                encode_str(header.value_slice(), dst);
+                encode_str(name.as_slice(), huffman(name.as_slice()), dst);
+                encode_str(value, huffman(value), dst);
             }
             Index::InsertedValue(idx, _) => {
                 let header = self.table.resolve(index);
+                let value = header.value_slice();
 
                 assert!(!header.is_sensitive());
 
                 encode_int(idx, 6, 0b0100_0000, dst);
-                encode_str(header.value_slice(), dst);
+                encode_str(value, huffman(value), dst);
             }
             Index::NotIndexed(_) => {
                 let header = self.table.resolve(index);
+                let name = header.name();
+                let value = header.value_slice();
 
                 encode_not_indexed2(
-                    header.name().as_slice(),
-                    header.value_slice(),
-                    header.is_sensitive(),
+                    name.as_slice(),
+                    value,
+                    header.is_sensitive() || never_index,
+                    (huffman(name.as_slice()), huffman(value)),
                     dst,
                 );
             }
@@ -166,7 +302,7 @@ impl Encoder {
             | Index::InsertedValue(..) => {
                 let idx = self.table.resolve_idx(last);
 
-                encode_not_indexed(idx, value.as_ref(), value.is_sensitive(), dst);
+                encode_not_indexed(idx, value.as_ref(), value.is_sensitive(), true, dst);
             }
             Index::NotIndexed(_) => {
                 let last = self.table.resolve(last);
@@ -175,6 +311,7 @@ impl Encoder {
                     last.name().as_slice(),
                     value.as_ref(),
                     value.is_sensitive(),
+                    (true, true),
                     dst,
                 );
             }
@@ -192,29 +329,45 @@ fn encode_size_update(val: usize, dst: &mut BytesMut) {
     encode_int(val, 5, 0b0010_0000, dst)
 }
 
-fn encode_not_indexed(name: usize, value: &[u8], sensitive: bool, dst: &mut BytesMut) {
+fn encode_not_indexed(
+    name: usize,
+    value: &[u8],
+    sensitive: bool,
+    huffman: bool,
+    dst: &mut BytesMut,
+) {
     if sensitive {
         encode_int(name, 4, 0b10000, dst);
     } else {
         encode_int(name, 4, 0, dst);
     }
 
-    encode_str(value, dst);
+    encode_str(value, huffman, dst);
 }
 
-fn encode_not_indexed2(name: &[u8], value: &[u8], sensitive: bool, dst: &mut BytesMut) {
+fn encode_not_indexed2(
+    name: &[u8],
+    value: &[u8],
+    sensitive: bool,
+    huffman: (bool, bool),
+    dst: &mut BytesMut,
+) {
     if sensitive {
         dst.put_u8(0b10000);
     } else {
         dst.put_u8(0);
     }
 
-    encode_str(name, dst);
-    encode_str(value, dst);
+    encode_str(name, huffman.0, dst);
+    encode_str(value, huffman.1, dst);
 }
 
-fn encode_str(val: &[u8], dst: &mut BytesMut) {
-    if !val.is_empty() {
+fn encode_str(val: &[u8], huffman: bool, dst: &mut BytesMut) {
+    if !huffman {
+        // Write the string as is
+        encode_int(val.len(), 7, 0, dst);
+        dst.put_slice(val);
+    } else if !val.is_empty() {
         let idx = position(dst);
 
         // Push a placeholder byte for the length header
@@ -299,6 +452,7 @@ fn position(buf: &BytesMut) -> usize {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::hpack::{BytesStr, ChromePolicy, FirefoxPolicy};
     use http::*;
 
     #[test]
@@ -687,6 +841,104 @@ mod test {
         assert_eq!([63, 225, 129, 148, 144, 7], &dst[..]);
     }
 
+    #[test]
+    fn test_policy_without_indexing() {
+        let mut encoder = Encoder::default();
+        encoder.set_policy(Arc::new(ChromePolicy));
+
+        let path = Header::Path(BytesStr::from_static("/about"));
+        let len = huffman::encoded_len(b"/about");
+        let res = encode(&mut encoder, vec![path.clone(), header("foo", "bar")]);
+
+        // Literal without indexing, name pulled from the static table
+        assert_eq!(res[0], 4);
+        assert_eq!(res[1] as usize, 0x80 | len);
+        assert_eq!("/about", huff_decode(&res[2..2 + len]));
+        assert_eq!(res[2 + len], 0b0100_0000);
+        assert_eq!(encoder.table.len(), 1);
+
+        // The path is still not indexed, but `foo` now is
+        let res = encode(&mut encoder, vec![path, header("foo", "bar")]);
+        assert_eq!(res[0], 4);
+        assert_eq!(&res[2 + len..], &[0x80 | 62]);
+        assert_eq!(encoder.table.len(), 1);
+    }
+
+    #[test]
+    fn test_policy_never_indexed() {
+        let mut encoder = Encoder::default();
+        encoder.set_policy(Arc::new(FirefoxPolicy));
+
+        for _ in 0..2 {
+            let res = encode(&mut encoder, vec![header("cookie", "a=b")]);
+
+            // Never indexed, name pulled from the static table
+            assert_eq!(&[0b0001_1111, 32 - 15], &res[0..2]);
+            assert_eq!(encoder.table.len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_policy_raw_strings() {
+        #[derive(Debug)]
+        struct Raw;
+
+        impl EncodingPolicy for Raw {
+            fn indexing(&self, _: &[u8], _: &[u8]) -> Indexing {
+                Indexing::Incremental
+            }
+
+            fn huffman(&self, _: &[u8]) -> bool {
+                false
+            }
+        }
+
+        let mut encoder = Encoder::default();
+        encoder.set_policy(Arc::new(Raw));
+
+        let res = encode(&mut encoder, vec![header("foo", "bar")]);
+        assert_eq!(&res[..], b"\x40\x03foo\x03bar");
+
+        let res = encode(&mut encoder, vec![header("foo", "bar")]);
+        assert_eq!(&res[..], &[0x80 | 62]);
+    }
+
+    #[test]
+    fn test_policy_size_update_in_first_block() {
+        #[derive(Debug)]
+        struct SizeUpdateFirst;
+
+        impl EncodingPolicy for SizeUpdateFirst {
+            fn indexing(&self, _: &[u8], _: &[u8]) -> Indexing {
+                Indexing::Incremental
+            }
+
+            fn size_update_in_first_block(&self) -> bool {
+                true
+            }
+        }
+
+        let mut encoder = Encoder::default();
+        encoder.set_policy(Arc::new(SizeUpdateFirst));
+
+        let res = encode(&mut encoder, vec![method("GET")]);
+        assert_eq!(&res[..], &[0b0011_1111, 0xe1, 0x1f, 0x80 | 2]);
+
+        let res = encode(&mut encoder, vec![method("GET")]);
+        assert_eq!(&res[..], &[0x80 | 2]);
+    }
+
+    #[test]
+    fn test_huffman_encoded_len() {
+        let mut dst = BytesMut::new();
+
+        for src in [&b""[..], b"hello", b"/index.html", b"\x00\xff"] {
+            dst.clear();
+            huffman::encode(src, &mut dst);
+            assert_eq!(huffman::encoded_len(src), dst.len());
+        }
+    }
+
     #[test]
     #[ignore]
     fn test_evicted_overflow() {
@@ -0,0 +1,42 @@
+use crate::stats::HpackStats;
+
+use bytes::Bytes;
+
+use std::mem;
+
+/// Records the header fields going through an `Encoder` or a `Decoder`.
+#[derive(Debug, Default)]
+pub(crate) struct FieldLog {
+    stats: HpackStats,
+    /// The fields since the last call to `take_fields`, if enabled.
+    fields: Option<Vec<(Bytes, Bytes)>>,
+}
+
+impl FieldLog {
+    pub fn stats(&self) -> &HpackStats {
+        &self.stats
+    }
+
+    /// Starts keeping the names and values of the header fields.
+    pub fn keep_fields(&mut self) {
+        self.fields.get_or_insert_with(Vec::new);
+    }
+
+    /// Takes the header fields kept since the last call, if enabled.
+    pub fn take_fields(&mut self) -> Option<Vec<(Bytes, Bytes)>> {
+        self.fields.as_mut().map(mem::take)
+    }
+
+    pub fn record_field(&mut self, name: &[u8], value: &[u8], is_dynamic_table_hit: bool) {
+        self.stats
+            .record_field(name.len() + value.len(), is_dynamic_table_hit);
+
+        if let Some(ref mut fields) = self.fields {
+            fields.push((Bytes::copy_from_slice(name), Bytes::copy_from_slice(value)));
+        }
+    }
+
+    pub fn record_block(&mut self, len: usize) {
+        self.stats.record_block(len);
+    }
+}
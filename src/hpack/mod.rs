@@ -1,7 +1,9 @@
 mod decoder;
 mod encoder;
+mod field_log;
 pub(crate) mod header;
 pub(crate) mod huffman;
+mod policy;
 mod table;
 
 #[cfg(test)]
@@ -10,3 +12,4 @@ mod test;
 pub use self::decoder::{Decoder, DecoderError, NeedMore};
 pub use self::encoder::Encoder;
 pub use self::header::{BytesStr, Header};
+pub use self::policy::{ChromePolicy, EncodingPolicy, FirefoxPolicy, Indexing};
@@ -0,0 +1,100 @@
+use super::huffman;
+
+use std::fmt;
+
+/// Decides how the HPACK encoder represents each header field.
+///
+/// By default, the encoder indexes every field it considers worth indexing,
+/// and Huffman encodes every string literal. Browsers make these choices
+/// differently, and the choices are visible on the wire. An `EncodingPolicy`
+/// set with [`client::Builder::encoding_policy`] or
+/// [`server::Builder::encoding_policy`] is consulted for every field instead.
+///
+/// Pseudo-header fields are passed with their `:` prefixed name.
+///
+/// [`client::Builder::encoding_policy`]: crate::client::Builder::encoding_policy
+/// [`server::Builder::encoding_policy`]: crate::server::Builder::encoding_policy
+pub trait EncodingPolicy: fmt::Debug + Send + Sync + 'static {
+    /// Returns how the field is indexed.
+    ///
+    /// Fields with a sensitive `HeaderValue` are always sent as
+    /// [`Indexing::NeverIndexed`].
+    fn indexing(&self, name: &[u8], value: &[u8]) -> Indexing;
+
+    /// Returns whether a string literal (a name or a value) is Huffman encoded.
+    fn huffman(&self, string: &[u8]) -> bool {
+        let _ = string;
+        true
+    }
+
+    /// Returns whether the first header block starts with a dynamic table size
+    /// update, even if the table size never changed.
+    fn size_update_in_first_block(&self) -> bool {
+        false
+    }
+}
+
+/// How a header field is indexed by the HPACK encoder.
+///
+/// See <https://www.rfc-editor.org/rfc/rfc7541#section-6>.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Indexing {
+    /// Use a matching table entry, or insert the field into the dynamic table
+    /// if it fits.
+    Incremental,
+
+    /// Use a matching table entry, or send a literal without inserting it.
+    WithoutIndexing,
+
+    /// Send a literal that intermediaries must never index.
+    NeverIndexed,
+}
+
+/// An [`EncodingPolicy`] that mimics the HPACK encoder of Chrome.
+///
+/// Pseudo-header fields other than `:authority` are not inserted into the
+/// dynamic table, every other field is. Strings are Huffman encoded when it
+/// makes them shorter.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChromePolicy;
+
+/// An [`EncodingPolicy`] that mimics the HPACK encoder of Firefox.
+///
+/// `:path` is not inserted into the dynamic table. `authorization`,
+/// `proxy-authorization` and `cookie` values shorter than 20 bytes are never
+/// indexed. Strings are Huffman encoded when it makes them shorter.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FirefoxPolicy;
+
+// ===== impl ChromePolicy =====
+
+impl EncodingPolicy for ChromePolicy {
+    fn indexing(&self, name: &[u8], _value: &[u8]) -> Indexing {
+        if name.starts_with(b":") && name != b":authority" {
+            Indexing::WithoutIndexing
+        } else {
+            Indexing::Incremental
+        }
+    }
+
+    fn huffman(&self, string: &[u8]) -> bool {
+        huffman::encoded_len(string) < string.len()
+    }
+}
+
+// ===== impl FirefoxPolicy =====
+
+impl EncodingPolicy for FirefoxPolicy {
+    fn indexing(&self, name: &[u8], value: &[u8]) -> Indexing {
+        match name {
+            b":path" => Indexing::WithoutIndexing,
+            b"authorization" | b"proxy-authorization" => Indexing::NeverIndexed,
+            b"cookie" if value.len() < 20 => Indexing::NeverIndexed,
+            _ => Indexing::Incremental,
+        }
+    }
+
+    fn huffman(&self, string: &[u8]) -> bool {
+        huffman::encoded_len(string) < string.len()
+    }
+}
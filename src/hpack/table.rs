@@ -38,6 +38,13 @@ pub enum Index {
     NotIndexed(Header),
 }
 
+impl Index {
+    /// Returns true if the whole header field is indexed in the dynamic table.
+    pub fn is_dynamic_table_hit(&self) -> bool {
+        matches!(*self, Index::Indexed(idx, _) if idx >= DYN_OFFSET)
+    }
+}
+
 #[derive(Debug)]
 struct Slot {
     hash: HashValue,
@@ -157,6 +164,59 @@ impl Table {
         self.index_dynamic(header, statik)
     }
 
+    /// Index the header in the HPACK table, inserting it into the dynamic
+    /// table whenever it fits.
+    ///
+    /// Unlike `index`, this does not apply any heuristic about which headers
+    /// are worth indexing.
+    pub fn index_incremental(&mut self, header: Header) -> Index {
+        let statik = index_static(&header);
+
+        if let Some((n, true)) = statik {
+            return Index::Indexed(n, header);
+        }
+
+        if header.len() > self.max_size {
+            return Index::new(statik, header);
+        }
+
+        self.index_dynamic(header, statik)
+    }
+
+    /// Find the header in the HPACK table without inserting it.
+    ///
+    /// If `exact` is false, only the name is looked up.
+    pub fn find(&self, header: Header, exact: bool) -> Index {
+        let statik = index_static(&header);
+
+        if let Some((n, true)) = statik {
+            if exact {
+                return Index::Indexed(n, header);
+            }
+        }
+
+        let mut name = statik.map(|(n, _)| n);
+
+        for (i, slot) in self.slots.iter().enumerate() {
+            if slot.header.name() != header.name() {
+                continue;
+            }
+
+            if exact && slot.header.value_eq(&header) {
+                return Index::Indexed(i + DYN_OFFSET, header);
+            }
+
+            if name.is_none() {
+                name = Some(i + DYN_OFFSET);
+            }
+        }
+
+        match name {
+            Some(n) => Index::Name(n, header),
+            None => Index::NotIndexed(header),
+        }
+    }
+
     fn index_dynamic(&mut self, header: Header, statik: Option<(usize, bool)>) -> Index {
         debug_assert!(self.assert_valid_state("one"));
 
@@ -306,13 +366,10 @@ impl Table {
 
         let pos_idx = 0usize.wrapping_sub(self.inserted);
 
-        let prev = mem::replace(
-            &mut self.indices[probe],
-            Some(Pos {
-                index: pos_idx,
-                hash,
-            }),
-        );
+        let prev = self.indices[probe].replace(Pos {
+            index: pos_idx,
+            hash,
+        });
 
         if let Some(mut prev) = prev {
             // Shift forward
@@ -321,7 +378,7 @@ impl Table {
             probe_loop!(probe < self.indices.len(), {
                 let pos = &mut self.indices[probe];
 
-                prev = match mem::replace(pos, Some(prev)) {
+                prev = match pos.replace(prev) {
                     Some(p) => p,
                     None => break,
                 };
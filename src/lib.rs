@@ -108,7 +108,9 @@ macro_rules! ready {
 #[cfg_attr(feature = "unstable", allow(missing_docs))]
 mod codec;
 mod error;
+mod fingerprint;
 mod hpack;
+mod preface;
 
 #[cfg(not(feature = "unstable"))]
 mod proto;
@@ -126,15 +128,29 @@ pub mod frame;
 
 pub mod client;
 pub mod ext;
+mod observer;
+mod profile;
 pub mod server;
+mod settings;
 mod share;
+mod stats;
+mod timer;
+pub mod transcript;
+mod upgrade;
 
 #[cfg(fuzzing)]
 #[cfg_attr(feature = "unstable", allow(missing_docs))]
 pub mod fuzz_bridge;
 
 pub use crate::error::{Error, Reason};
+pub use crate::fingerprint::{Fingerprint, FingerprintError};
+pub use crate::frame::{AltSvc, ExtensionFrame};
+pub use crate::hpack::{ChromePolicy, EncodingPolicy, FirefoxPolicy, Indexing};
+pub use crate::observer::{FrameDirection, FrameObserver, ObservedFrame};
+pub use crate::settings::SettingsSnapshot;
 pub use crate::share::{FlowControl, Ping, PingPong, Pong, RecvStream, SendStream, StreamId};
+pub use crate::stats::{ConnectionStats, FrameStats, FrameTypeStats, HpackStats, StreamStats};
+pub use crate::timer::{Sleep, Timer, TokioTimer};
 
 #[cfg(feature = "unstable")]
 pub use codec::{Codec, SendError, UserError};
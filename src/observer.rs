@@ -0,0 +1,200 @@
+use crate::frame::{self, Head};
+use crate::StreamId;
+
+use bytes::Bytes;
+
+use std::fmt;
+
+const DATA: u8 = 0x0;
+const HEADERS: u8 = 0x1;
+const PUSH_PROMISE: u8 = 0x5;
+const CONTINUATION: u8 = 0x9;
+
+const END_HEADERS: u8 = 0x4;
+const PADDED: u8 = 0x8;
+
+/// Observes the frames written to and read from a connection.
+///
+/// An observer is registered with [`client::Builder::frame_observer`] or
+/// [`server::Builder::frame_observer`]. It is called synchronously, from the
+/// task polling the connection, for each frame as it is encoded for writing
+/// and as it is decoded after reading. Frames are observed in wire order in
+/// each direction.
+///
+/// When no observer is registered, frames are not inspected at all.
+///
+/// [`client::Builder::frame_observer`]: crate::client::Builder::frame_observer
+/// [`server::Builder::frame_observer`]: crate::server::Builder::frame_observer
+///
+/// # Examples
+///
+/// ```
+/// use h2::{FrameObserver, ObservedFrame};
+///
+/// #[derive(Debug)]
+/// struct Log;
+///
+/// impl FrameObserver for Log {
+///     fn on_frame(&self, frame: &ObservedFrame<'_>) {
+///         println!(
+///             "{:?} type={} flags={:#x} stream={}",
+///             frame.direction(),
+///             frame.frame_type(),
+///             frame.flags(),
+///             frame.stream_id().as_u32(),
+///         );
+///
+///         for (name, value) in frame.fields().into_iter().flatten() {
+///             println!("  {:?}: {:?}", name, value);
+///         }
+///     }
+/// }
+///
+/// let mut builder = h2::client::Builder::new();
+/// builder.frame_observer(Log);
+/// ```
+pub trait FrameObserver: fmt::Debug + Send + Sync + 'static {
+    /// Called with each frame written to or read from the connection.
+    fn on_frame(&self, frame: &ObservedFrame<'_>);
+}
+
+/// The direction of an [`ObservedFrame`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameDirection {
+    /// The frame was read from the connection.
+    Inbound,
+    /// The frame was written to the connection.
+    Outbound,
+}
+
+/// A frame passed to a [`FrameObserver`].
+#[derive(Debug)]
+pub struct ObservedFrame<'a> {
+    direction: FrameDirection,
+    frame_type: u8,
+    flags: u8,
+    stream_id: StreamId,
+    payload_len: usize,
+    pad_len: Option<u8>,
+    payload: Option<&'a [u8]>,
+    fields: Option<&'a [(Bytes, Bytes)]>,
+}
+
+// ===== impl ObservedFrame =====
+
+impl<'a> ObservedFrame<'a> {
+    /// Creates the view of the frame with the frame header `head`.
+    ///
+    /// `payload` is the payload, if it is available in one slice, and
+    /// `fields` the header fields of the block the frame ends.
+    pub(crate) fn new(
+        direction: FrameDirection,
+        head: &[u8],
+        payload: Option<&'a [u8]>,
+        fields: Option<&'a [(Bytes, Bytes)]>,
+    ) -> ObservedFrame<'a> {
+        let payload_len = payload_len(head);
+        let parsed = Head::parse(head);
+        let frame_type = head[3];
+        let flags = parsed.flag();
+
+        let pad_len = match frame_type {
+            DATA | HEADERS | PUSH_PROMISE if flags & PADDED == PADDED => {
+                payload.and_then(|payload| payload.first().copied())
+            }
+            _ => None,
+        };
+
+        ObservedFrame {
+            direction,
+            frame_type,
+            flags,
+            stream_id: StreamId::from_internal(parsed.stream_id()),
+            payload_len,
+            pad_len,
+            payload,
+            fields,
+        }
+    }
+
+    /// Returns whether the frame was read or written.
+    pub fn direction(&self) -> FrameDirection {
+        self.direction
+    }
+
+    /// Returns the frame type, such as `0x1` for HEADERS frames.
+    pub fn frame_type(&self) -> u8 {
+        self.frame_type
+    }
+
+    /// Returns the flags of the frame header.
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Returns the stream identifier of the frame header.
+    pub fn stream_id(&self) -> StreamId {
+        self.stream_id
+    }
+
+    /// Returns the length of the frame payload, excluding the frame header.
+    pub fn payload_len(&self) -> usize {
+        self.payload_len
+    }
+
+    /// Returns the length of the padding, if the frame is padded.
+    pub fn pad_len(&self) -> Option<u8> {
+        self.pad_len
+    }
+
+    /// Returns the payload of the frame, including any padding.
+    ///
+    /// This returns `None` for a DATA frame being written if its payload is
+    /// not contiguous in memory.
+    pub fn payload(&self) -> Option<&'a [u8]> {
+        self.payload
+    }
+
+    /// Returns the header fields of the header block, in the order they were
+    /// HPACK encoded, including pseudo-header fields.
+    ///
+    /// A header block may span a HEADERS or PUSH_PROMISE frame and
+    /// CONTINUATION frames. The fields are returned for the frame with the
+    /// END_HEADERS flag, which ends the block, and `None` is returned for
+    /// other frames.
+    pub fn fields(&self) -> Option<&'a [(Bytes, Bytes)]> {
+        self.fields
+    }
+}
+
+fn payload_len(head: &[u8]) -> usize {
+    (head[0] as usize) << 16 | (head[1] as usize) << 8 | head[2] as usize
+}
+
+/// Returns whether the frame with the frame header `head` starts a header
+/// block.
+pub(crate) fn starts_header_block(head: &[u8]) -> bool {
+    matches!(head[3], HEADERS | PUSH_PROMISE)
+}
+
+/// Returns whether the frame with the frame header `head` ends a header block.
+pub(crate) fn ends_header_block(head: &[u8]) -> bool {
+    matches!(head[3], HEADERS | PUSH_PROMISE | CONTINUATION) && head[4] & END_HEADERS == END_HEADERS
+}
+
+/// Splits `buf`, which is made of complete frames written outside of the
+/// codec, into the frame header and the payload of each frame.
+pub(crate) fn split_frames(mut buf: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    std::iter::from_fn(move || {
+        if buf.len() < frame::HEADER_LEN {
+            return None;
+        }
+
+        let (head, rest) = buf.split_at(frame::HEADER_LEN);
+        let len = payload_len(head).min(rest.len());
+        let (payload, rest) = rest.split_at(len);
+        buf = rest;
+
+        Some((head, payload))
+    })
+}
@@ -0,0 +1,294 @@
+use crate::frame::{self, Priorities, Settings, StreamId, WindowUpdate};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+/// The connection preface magic sent by clients before any frame.
+pub(crate) const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// The exact sequence of frames a client sends after the connection preface
+/// magic.
+///
+/// By default, a client writes the preface magic on its own, then sends the
+/// SETTINGS frame and the connection level WINDOW_UPDATE frame together, and
+/// sends the [`Priorities`] frames right before the HEADERS frame of the first
+/// request. A `PrefaceSequence` replaces this with an explicit list of frames
+/// and flush points, written during the handshake.
+///
+/// Every frame written before a flush point, starting with the preface magic,
+/// is coalesced into a single write. The sequence must start with the SETTINGS
+/// frame, as required by [RFC 9113].
+///
+/// A `PrefaceSequence` is applied to a client with
+/// [`Builder::preface_sequence`].
+///
+/// [`Priorities`]: ../frame/struct.Priorities.html
+/// [RFC 9113]: https://www.rfc-editor.org/rfc/rfc9113#section-3.4
+/// [`Builder::preface_sequence`]: struct.Builder.html#method.preface_sequence
+///
+/// # Examples
+///
+/// ```
+/// use h2::client::PrefaceSequence;
+///
+/// // Write the magic on its own, then SETTINGS, a GREASE frame and
+/// // WINDOW_UPDATE in a second write.
+/// let sequence = PrefaceSequence::builder()
+///     .flush()
+///     .settings()
+///     .unknown(0xb + 0x1f, 0, 0, "grease")
+///     .window_update()
+///     .build();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrefaceSequence {
+    steps: Vec<Step>,
+}
+
+/// A builder for constructing a [`PrefaceSequence`].
+///
+/// [`PrefaceSequence`]: struct.PrefaceSequence.html
+#[derive(Debug)]
+pub struct PrefaceSequenceBuilder {
+    steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Step {
+    Settings,
+    WindowUpdate,
+    Priorities,
+    Unknown {
+        kind: u8,
+        flags: u8,
+        stream_id: StreamId,
+        payload: Bytes,
+    },
+    Flush,
+}
+
+// ===== impl PrefaceSequence =====
+
+impl PrefaceSequence {
+    /// Returns a new, empty `PrefaceSequenceBuilder`.
+    pub fn builder() -> PrefaceSequenceBuilder {
+        PrefaceSequenceBuilder { steps: Vec::new() }
+    }
+
+    /// Returns true if the connection level WINDOW_UPDATE frame is part of the
+    /// sequence.
+    pub(crate) fn has_window_update(&self) -> bool {
+        self.steps.contains(&Step::WindowUpdate)
+    }
+
+    /// Returns true if the PRIORITY frames are part of the sequence.
+    pub(crate) fn has_priorities(&self) -> bool {
+        self.steps.contains(&Step::Priorities)
+    }
+
+    /// Encodes the preface magic and the frames of the sequence, returning one
+    /// buffer per write.
+    pub(crate) fn encode(
+        &self,
+        settings: &Settings,
+        window_update: Option<u32>,
+        priorities: Option<&Priorities>,
+    ) -> Vec<BytesMut> {
+        let mut writes = Vec::new();
+        let mut dst = BytesMut::new();
+
+        dst.put_slice(PREFACE);
+
+        for step in &self.steps {
+            match *step {
+                Step::Settings => settings.encode(&mut dst),
+                Step::WindowUpdate => {
+                    if let Some(increment) = window_update {
+                        WindowUpdate::new(StreamId::zero(), increment).encode(&mut dst);
+                    }
+                }
+                Step::Priorities => {
+                    for priority in priorities.into_iter().flatten() {
+                        priority.encode(&mut dst);
+                    }
+                }
+                Step::Unknown {
+                    kind,
+                    flags,
+                    stream_id,
+                    ref payload,
+                } => {
+                    dst.put_uint(payload.len() as u64, 3);
+                    dst.put_u8(kind);
+                    dst.put_u8(flags);
+                    dst.put_u32(stream_id.into());
+                    dst.put_slice(payload);
+                }
+                Step::Flush => {
+                    if !dst.is_empty() {
+                        writes.push(dst.split());
+                    }
+                }
+            }
+        }
+
+        if !dst.is_empty() {
+            writes.push(dst);
+        }
+
+        writes
+    }
+}
+
+// ===== impl PrefaceSequenceBuilder =====
+
+impl PrefaceSequenceBuilder {
+    /// Sends the SETTINGS frame configured on the client `Builder`.
+    pub fn settings(mut self) -> Self {
+        self.steps.push(Step::Settings);
+        self
+    }
+
+    /// Sends the connection level WINDOW_UPDATE frame.
+    ///
+    /// The increment is derived from the client `Builder`'s
+    /// `initial_connection_window_size`. Nothing is sent if the connection
+    /// window is not larger than the default.
+    ///
+    /// If the sequence contains no WINDOW_UPDATE, the client sends it after
+    /// the handshake instead.
+    pub fn window_update(mut self) -> Self {
+        self.steps.push(Step::WindowUpdate);
+        self
+    }
+
+    /// Sends the PRIORITY frames configured with the client `Builder`'s
+    /// `priorities`.
+    ///
+    /// If the sequence contains no PRIORITY frames, the client sends them
+    /// right before the HEADERS frame of a request instead.
+    pub fn priorities(mut self) -> Self {
+        self.steps.push(Step::Priorities);
+        self
+    }
+
+    /// Sends a frame of an unknown type, such as a GREASE frame.
+    ///
+    /// Receivers ignore frames of unknown types, see [RFC 9113].
+    ///
+    /// [RFC 9113]: https://www.rfc-editor.org/rfc/rfc9113#section-5.5
+    ///
+    /// # Panics
+    ///
+    /// Panics if `kind` is a frame type defined by RFC 9113, or if `payload`
+    /// is larger than the default maximum frame size.
+    pub fn unknown(
+        mut self,
+        kind: u8,
+        flags: u8,
+        stream_id: u32,
+        payload: impl Into<Bytes>,
+    ) -> Self {
+        let payload = payload.into();
+
+        assert!(
+            frame::Kind::new(kind) == frame::Kind::Unknown,
+            "frame type {} is not an unknown frame type",
+            kind
+        );
+        assert!(
+            payload.len() <= frame::DEFAULT_MAX_FRAME_SIZE as usize,
+            "payload is larger than the default max frame size"
+        );
+
+        self.steps.push(Step::Unknown {
+            kind,
+            flags,
+            stream_id: stream_id.into(),
+            payload,
+        });
+        self
+    }
+
+    /// Ends the current write.
+    ///
+    /// Frames added after a flush point are written separately from the ones
+    /// added before it.
+    pub fn flush(mut self) -> Self {
+        self.steps.push(Step::Flush);
+        self
+    }
+
+    /// Builds the `PrefaceSequence`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the first frame of the sequence is not the SETTINGS frame.
+    pub fn build(self) -> PrefaceSequence {
+        let first = self.steps.iter().find(|step| **step != Step::Flush);
+        assert!(
+            first == Some(&Step::Settings),
+            "the preface sequence must start with the SETTINGS frame"
+        );
+
+        PrefaceSequence { steps: self.steps }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::{Priority, StreamDependency};
+
+    #[test]
+    fn test_encode_sequence() {
+        let mut settings = Settings::default();
+        settings.set_initial_window_size(Some(131_072));
+
+        let priorities = Priorities::builder()
+            .push(Priority::new(
+                StreamId::from(3),
+                StreamDependency::new(StreamId::zero(), 200, false),
+            ))
+            .build();
+
+        let sequence = PrefaceSequence::builder()
+            .flush()
+            .settings()
+            .priorities()
+            .flush()
+            .unknown(0x2a, 1, 0, &b"ab"[..])
+            .window_update()
+            .build();
+
+        let writes = sequence.encode(&settings, Some(12_517_377), Some(&priorities));
+        assert_eq!(writes.len(), 3);
+        assert_eq!(&writes[0][..], PREFACE);
+        assert_eq!(
+            &writes[1][..],
+            &[
+                // SETTINGS
+                0, 0, 6, 4, 0, 0, 0, 0, 0, 0, 4, 0, 2, 0, 0, // PRIORITY
+                0, 0, 5, 2, 0, 0, 0, 0, 3, 0, 0, 0, 0, 200,
+            ][..]
+        );
+        assert_eq!(
+            &writes[2][..],
+            &[
+                // Unknown frame
+                0, 0, 2, 0x2a, 1, 0, 0, 0, 0, b'a', b'b', // WINDOW_UPDATE
+                0, 0, 4, 8, 0, 0, 0, 0, 0, 0, 0xbf, 0, 1,
+            ][..]
+        );
+        assert!(sequence.has_window_update());
+        assert!(sequence.has_priorities());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sequence_must_start_with_settings() {
+        PrefaceSequence::builder()
+            .window_update()
+            .settings()
+            .build();
+    }
+}
@@ -0,0 +1,454 @@
+#[cfg(feature = "unstable")]
+use crate::frame::ExperimentalSettings;
+use crate::frame::{
+    Priorities, PseudoId, PseudoOrder, SettingId, Settings, SettingsOrder, StreamDependency,
+    StreamId, DEFAULT_INITIAL_WINDOW_SIZE,
+};
+use crate::proto::MAX_WINDOW_SIZE;
+
+use std::borrow::Cow;
+
+/// A client connection profile.
+///
+/// A `Profile` bundles every connection level knob that shapes how a client
+/// looks on the wire: the initial SETTINGS values and their order, the
+/// connection level WINDOW_UPDATE increment sent right after SETTINGS, the
+/// pseudo-header order and stream dependency of HEADERS frames, and the
+/// PRIORITY frames sent before the first request.
+///
+/// Built-in presets are available for common browsers ([`Profile::chrome`],
+/// [`Profile::firefox`], [`Profile::safari`] and [`Profile::edge`]). Custom
+/// profiles are constructed with [`Profile::builder`], or derived from an
+/// existing profile with [`Profile::into_builder`].
+///
+/// A profile is applied to a client with [`Builder::profile`].
+///
+/// [`Builder::profile`]: struct.Builder.html#method.profile
+///
+/// # Examples
+///
+/// ```
+/// # use tokio::io::{AsyncRead, AsyncWrite};
+/// # use h2::client::*;
+/// # use bytes::Bytes;
+/// #
+/// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+/// # -> Result<((SendRequest<Bytes>, Connection<T, Bytes>)), h2::Error>
+/// # {
+/// let profile = Profile::chrome();
+/// assert_eq!(profile.name(), "chrome");
+///
+/// let client_fut = Builder::new()
+///     .profile(&profile)
+///     .handshake(my_io);
+/// # client_fut.await
+/// # }
+/// #
+/// # pub fn main() {}
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Profile {
+    name: Cow<'static, str>,
+    version: Cow<'static, str>,
+    settings: Settings,
+    connection_window_increment: Option<u32>,
+    pseudo_order: PseudoOrder,
+    stream_dependency: Option<StreamDependency>,
+    priorities: Option<Priorities>,
+}
+
+/// A builder for constructing a [`Profile`].
+///
+/// Values that are never set are left out of the resulting profile: the
+/// matching SETTINGS parameter is not sent, no connection level WINDOW_UPDATE
+/// is sent, HEADERS frames carry no stream dependency and no PRIORITY frames
+/// are sent.
+///
+/// [`Profile`]: struct.Profile.html
+#[derive(Debug)]
+pub struct ProfileBuilder {
+    profile: Profile,
+}
+
+// ===== impl Profile =====
+
+impl Profile {
+    /// Returns a new, empty `ProfileBuilder`.
+    pub fn builder() -> ProfileBuilder {
+        ProfileBuilder {
+            profile: Profile {
+                name: Cow::Borrowed(""),
+                version: Cow::Borrowed(""),
+                settings: Settings::default(),
+                connection_window_increment: None,
+                pseudo_order: PseudoOrder::default(),
+                stream_dependency: None,
+                priorities: None,
+            },
+        }
+    }
+
+    /// Returns a `ProfileBuilder` initialized with the values of this profile.
+    pub fn into_builder(self) -> ProfileBuilder {
+        ProfileBuilder { profile: self }
+    }
+
+    /// The profile of Chrome 131 on desktop.
+    pub fn chrome() -> Profile {
+        Self::chromium("chrome", "131")
+    }
+
+    /// The profile of Edge 131 on desktop.
+    ///
+    /// Edge is based on Chromium and shares its HTTP/2 fingerprint with
+    /// [`Profile::chrome`].
+    pub fn edge() -> Profile {
+        Self::chromium("edge", "131")
+    }
+
+    fn chromium(name: &'static str, version: &'static str) -> Profile {
+        Profile::builder()
+            .name(name)
+            .version(version)
+            .header_table_size(65_536)
+            .enable_push(false)
+            .initial_window_size(6_291_456)
+            .max_header_list_size(262_144)
+            .settings_order(
+                SettingsOrder::builder()
+                    .extend([
+                        SettingId::HeaderTableSize,
+                        SettingId::EnablePush,
+                        SettingId::InitialWindowSize,
+                        SettingId::MaxHeaderListSize,
+                    ])
+                    .build(),
+            )
+            .connection_window_increment(15_663_105)
+            .pseudo_order(
+                PseudoOrder::builder()
+                    .extend([
+                        PseudoId::Method,
+                        PseudoId::Authority,
+                        PseudoId::Scheme,
+                        PseudoId::Path,
+                    ])
+                    .build(),
+            )
+            .stream_dependency(StreamDependency::new(StreamId::zero(), 255, true))
+            .build()
+    }
+
+    /// The profile of Firefox 133 on desktop.
+    pub fn firefox() -> Profile {
+        Profile::builder()
+            .name("firefox")
+            .version("133")
+            .header_table_size(65_536)
+            .enable_push(false)
+            .initial_window_size(131_072)
+            .max_frame_size(16_384)
+            .settings_order(
+                SettingsOrder::builder()
+                    .extend([
+                        SettingId::HeaderTableSize,
+                        SettingId::EnablePush,
+                        SettingId::InitialWindowSize,
+                        SettingId::MaxFrameSize,
+                    ])
+                    .build(),
+            )
+            .connection_window_increment(12_517_377)
+            .pseudo_order(
+                PseudoOrder::builder()
+                    .extend([
+                        PseudoId::Method,
+                        PseudoId::Path,
+                        PseudoId::Authority,
+                        PseudoId::Scheme,
+                    ])
+                    .build(),
+            )
+            .stream_dependency(StreamDependency::new(StreamId::zero(), 41, false))
+            .build()
+    }
+
+    /// The profile of Safari 18 on macOS.
+    pub fn safari() -> Profile {
+        Profile::builder()
+            .name("safari")
+            .version("18")
+            .enable_push(false)
+            .max_concurrent_streams(100)
+            .initial_window_size(2_097_152)
+            .no_rfc7540_priorities(true)
+            .settings_order(
+                SettingsOrder::builder()
+                    .extend([
+                        SettingId::EnablePush,
+                        SettingId::MaxConcurrentStreams,
+                        SettingId::InitialWindowSize,
+                        SettingId::NoRfc7540Priorities,
+                    ])
+                    .build(),
+            )
+            .connection_window_increment(10_420_225)
+            .pseudo_order(
+                PseudoOrder::builder()
+                    .extend([
+                        PseudoId::Method,
+                        PseudoId::Scheme,
+                        PseudoId::Authority,
+                        PseudoId::Path,
+                    ])
+                    .build(),
+            )
+            .build()
+    }
+
+    /// Returns the name of the profile, e.g. `"chrome"`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the version of the profile, e.g. `"131"`.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns the `SETTINGS_HEADER_TABLE_SIZE` value, if sent.
+    pub fn header_table_size(&self) -> Option<u32> {
+        self.settings.header_table_size()
+    }
+
+    /// Returns the `SETTINGS_ENABLE_PUSH` value, if sent.
+    pub fn enable_push(&self) -> Option<bool> {
+        self.settings.is_push_enabled()
+    }
+
+    /// Returns the `SETTINGS_MAX_CONCURRENT_STREAMS` value, if sent.
+    pub fn max_concurrent_streams(&self) -> Option<u32> {
+        self.settings.max_concurrent_streams()
+    }
+
+    /// Returns the `SETTINGS_INITIAL_WINDOW_SIZE` value, if sent.
+    pub fn initial_window_size(&self) -> Option<u32> {
+        self.settings.initial_window_size()
+    }
+
+    /// Returns the `SETTINGS_MAX_FRAME_SIZE` value, if sent.
+    pub fn max_frame_size(&self) -> Option<u32> {
+        self.settings.max_frame_size()
+    }
+
+    /// Returns the `SETTINGS_MAX_HEADER_LIST_SIZE` value, if sent.
+    pub fn max_header_list_size(&self) -> Option<u32> {
+        self.settings.max_header_list_size()
+    }
+
+    /// Returns the `SETTINGS_ENABLE_CONNECT_PROTOCOL` value, if sent.
+    pub fn enable_connect_protocol(&self) -> Option<bool> {
+        self.settings.is_extended_connect_protocol_enabled()
+    }
+
+    /// Returns the `SETTINGS_NO_RFC7540_PRIORITIES` value, if sent.
+    pub fn no_rfc7540_priorities(&self) -> Option<bool> {
+        self.settings.is_no_rfc7540_priorities()
+    }
+
+    /// Returns the order of the parameters in the initial SETTINGS frame.
+    pub fn settings_order(&self) -> &SettingsOrder {
+        self.settings.settings_order()
+    }
+
+    /// Returns the increment of the connection level WINDOW_UPDATE sent after
+    /// the initial SETTINGS frame, if any.
+    pub fn connection_window_increment(&self) -> Option<u32> {
+        self.connection_window_increment
+    }
+
+    /// Returns the pseudo-header order of outgoing HEADERS frames.
+    pub fn pseudo_order(&self) -> &PseudoOrder {
+        &self.pseudo_order
+    }
+
+    /// Returns the stream dependency of outgoing HEADERS frames, if any.
+    pub fn stream_dependency(&self) -> Option<StreamDependency> {
+        self.stream_dependency
+    }
+
+    /// Returns the PRIORITY frames sent before the first request, if any.
+    pub fn priorities(&self) -> Option<&Priorities> {
+        self.priorities.as_ref()
+    }
+
+    pub(crate) fn settings(&self) -> &Settings {
+        &self.settings
+    }
+}
+
+// ===== impl ProfileBuilder =====
+
+impl ProfileBuilder {
+    /// Sets the name of the profile.
+    pub fn name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.profile.name = name.into();
+        self
+    }
+
+    /// Sets the version of the profile.
+    pub fn version(mut self, version: impl Into<Cow<'static, str>>) -> Self {
+        self.profile.version = version.into();
+        self
+    }
+
+    /// Sets the `SETTINGS_HEADER_TABLE_SIZE` value.
+    pub fn header_table_size(mut self, size: u32) -> Self {
+        self.profile.settings.set_header_table_size(Some(size));
+        self
+    }
+
+    /// Sets the `SETTINGS_ENABLE_PUSH` value.
+    pub fn enable_push(mut self, enabled: bool) -> Self {
+        self.profile.settings.set_enable_push(enabled);
+        self
+    }
+
+    /// Sets the `SETTINGS_MAX_CONCURRENT_STREAMS` value.
+    pub fn max_concurrent_streams(mut self, max: u32) -> Self {
+        self.profile.settings.set_max_concurrent_streams(Some(max));
+        self
+    }
+
+    /// Sets the `SETTINGS_INITIAL_WINDOW_SIZE` value.
+    pub fn initial_window_size(mut self, size: u32) -> Self {
+        self.profile.settings.set_initial_window_size(Some(size));
+        self
+    }
+
+    /// Sets the `SETTINGS_MAX_FRAME_SIZE` value.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `max` is not between 16,384 and 16,777,215.
+    pub fn max_frame_size(mut self, max: u32) -> Self {
+        self.profile.settings.set_max_frame_size(Some(max));
+        self
+    }
+
+    /// Sets the `SETTINGS_MAX_HEADER_LIST_SIZE` value.
+    pub fn max_header_list_size(mut self, max: u32) -> Self {
+        self.profile.settings.set_max_header_list_size(Some(max));
+        self
+    }
+
+    /// Sets the `SETTINGS_ENABLE_CONNECT_PROTOCOL` value.
+    pub fn enable_connect_protocol(mut self, enabled: bool) -> Self {
+        self.profile
+            .settings
+            .set_enable_connect_protocol(Some(enabled as _));
+        self
+    }
+
+    /// Sets the `SETTINGS_NO_RFC7540_PRIORITIES` value.
+    pub fn no_rfc7540_priorities(mut self, enabled: bool) -> Self {
+        self.profile.settings.set_no_rfc7540_priorities(enabled);
+        self
+    }
+
+    /// Sets experimental SETTINGS parameters with unknown ids.
+    #[cfg(feature = "unstable")]
+    pub fn experimental_settings(mut self, experimental_settings: ExperimentalSettings) -> Self {
+        self.profile
+            .settings
+            .set_experimental_settings(experimental_settings);
+        self
+    }
+
+    /// Sets the order of the parameters in the initial SETTINGS frame.
+    pub fn settings_order(mut self, order: SettingsOrder) -> Self {
+        self.profile.settings.set_settings_order(order);
+        self
+    }
+
+    /// Sets the increment of the connection level WINDOW_UPDATE sent after the
+    /// initial SETTINGS frame.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the resulting connection window would exceed
+    /// the maximum window size of 2^31-1.
+    pub fn connection_window_increment(mut self, increment: u32) -> Self {
+        assert!(increment <= MAX_WINDOW_SIZE - DEFAULT_INITIAL_WINDOW_SIZE);
+        self.profile.connection_window_increment = Some(increment);
+        self
+    }
+
+    /// Sets the pseudo-header order of outgoing HEADERS frames.
+    pub fn pseudo_order(mut self, order: PseudoOrder) -> Self {
+        self.profile.pseudo_order = order;
+        self
+    }
+
+    /// Sets the stream dependency of outgoing HEADERS frames.
+    pub fn stream_dependency(mut self, stream_dependency: StreamDependency) -> Self {
+        self.profile.stream_dependency = Some(stream_dependency);
+        self
+    }
+
+    /// Sets the PRIORITY frames sent before the first request.
+    pub fn priorities(mut self, priorities: Priorities) -> Self {
+        self.profile.priorities = Some(priorities);
+        self
+    }
+
+    /// Returns the configured `Profile`.
+    pub fn build(self) -> Profile {
+        self.profile
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_chrome_settings_wire_order() {
+        let profile = Profile::chrome();
+        let mut buf = BytesMut::new();
+        profile.settings().encode(&mut buf);
+
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            0, 0, 24, 4, 0, 0, 0, 0, 0,
+            0, 1, 0, 1, 0, 0,
+            0, 2, 0, 0, 0, 0,
+            0, 4, 0, 0x60, 0, 0,
+            0, 6, 0, 4, 0, 0,
+        ];
+        assert_eq!(&buf[..], expected);
+    }
+
+    #[test]
+    fn test_into_builder_keeps_values() {
+        let profile = Profile::firefox().into_builder().version("134").build();
+
+        assert_eq!(profile.name(), "firefox");
+        assert_eq!(profile.version(), "134");
+        assert_eq!(profile.initial_window_size(), Some(131_072));
+        assert_eq!(profile.connection_window_increment(), Some(12_517_377));
+        assert_eq!(profile.pseudo_order(), Profile::firefox().pseudo_order());
+    }
+
+    #[test]
+    fn test_empty_builder() {
+        let profile = Profile::builder().build();
+
+        assert_eq!(profile.header_table_size(), None);
+        assert_eq!(profile.connection_window_increment(), None);
+        assert_eq!(profile.stream_dependency(), None);
+        assert!(profile.priorities().is_none());
+        assert_eq!(profile.pseudo_order(), &PseudoOrder::default());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::proto::WindowSize;
+
+use std::time::{Duration, Instant};
+
+/// Time to wait after a PONG before sending the next BDP PING.
+const INITIAL_PING_DELAY: Duration = Duration::from_millis(100);
+
+/// The delay is increased while the estimate is stable, up to this value.
+const MAX_PING_DELAY: Duration = Duration::from_secs(10);
+
+/// Estimates the bandwidth-delay product of the connection.
+///
+/// A PING is sent when DATA is received, and the DATA received until its
+/// PONG arrives is counted. When that count nears the current window, the
+/// window is what limits the throughput, and it is doubled, as long as the
+/// measured bandwidth keeps increasing.
+#[derive(Debug)]
+pub(crate) struct Bdp {
+    /// The current estimate, which is used as the receive window.
+    window: WindowSize,
+    /// The estimate is never increased above this value.
+    max_window: WindowSize,
+    /// DATA bytes received since the PING was sent.
+    bytes: usize,
+    /// Smoothed round trip time, in seconds.
+    rtt: f64,
+    /// Highest bandwidth measured so far, in bytes per second.
+    max_bandwidth: f64,
+    ping_delay: Duration,
+    next_ping_at: Option<Instant>,
+    ping: PingState,
+    /// Window increase which the connection has not applied yet.
+    pending_window: Option<WindowSize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PingState {
+    Idle,
+    Pending,
+    Sent(Instant),
+}
+
+// ===== impl Bdp =====
+
+impl Bdp {
+    pub(crate) fn new(initial_window: WindowSize, max_window: WindowSize) -> Bdp {
+        Bdp {
+            window: initial_window,
+            max_window,
+            bytes: 0,
+            rtt: 0.0,
+            max_bandwidth: 0.0,
+            ping_delay: INITIAL_PING_DELAY,
+            next_ping_at: None,
+            ping: PingState::Idle,
+            pending_window: None,
+        }
+    }
+
+    /// Records received DATA, scheduling a PING if none is in flight.
+    pub(crate) fn record_data(&mut self, len: usize) {
+        if self.window >= self.max_window {
+            return;
+        }
+
+        if let Some(next_ping_at) = self.next_ping_at {
+            if Instant::now() < next_ping_at {
+                return;
+            }
+            self.next_ping_at = None;
+        }
+
+        self.bytes += len;
+
+        if self.ping == PingState::Idle {
+            self.ping = PingState::Pending;
+        }
+    }
+
+    pub(crate) fn is_ping_pending(&self) -> bool {
+        self.ping == PingState::Pending
+    }
+
+    pub(crate) fn ping_sent(&mut self) {
+        self.ping = PingState::Sent(Instant::now());
+    }
+
+    /// Updates the estimate with the round trip of the PING. Returns `false`
+    /// if no PING was in flight.
+    pub(crate) fn recv_pong(&mut self) -> bool {
+        let sent_at = match self.ping {
+            PingState::Sent(sent_at) => sent_at,
+            _ => return false,
+        };
+
+        let bytes = self.bytes;
+        self.bytes = 0;
+        self.ping = PingState::Idle;
+
+        if let Some(window) = self.calculate(bytes, sent_at.elapsed()) {
+            tracing::trace!("BDP estimate increased; window={}", window);
+            self.pending_window = Some(window);
+        }
+
+        self.next_ping_at = Some(Instant::now() + self.ping_delay);
+        true
+    }
+
+    /// Takes the window increase which the connection should apply.
+    pub(crate) fn take_window(&mut self) -> Option<WindowSize> {
+        self.pending_window.take()
+    }
+
+    fn calculate(&mut self, bytes: usize, rtt: Duration) -> Option<WindowSize> {
+        if self.window >= self.max_window {
+            self.stabilize_delay();
+            return None;
+        }
+
+        let rtt = rtt.as_secs_f64();
+        if self.rtt == 0.0 {
+            self.rtt = rtt;
+        } else {
+            self.rtt += (rtt - self.rtt) * 0.125;
+        }
+
+        let bandwidth = bytes as f64 / (self.rtt * 1.5);
+        if bandwidth < self.max_bandwidth {
+            self.stabilize_delay();
+            return None;
+        }
+        self.max_bandwidth = bandwidth;
+
+        // The window is nearly used up within a round trip.
+        if bytes >= self.window as usize * 2 / 3 {
+            let window = (bytes * 2).min(self.max_window as usize);
+            self.window = window as WindowSize;
+            Some(self.window)
+        } else {
+            self.stabilize_delay();
+            None
+        }
+    }
+
+    fn stabilize_delay(&mut self) {
+        if self.ping_delay < MAX_PING_DELAY {
+            self.ping_delay = (self.ping_delay * 4).min(MAX_PING_DELAY);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_calculate() {
+        let rtt = Duration::from_millis(100);
+        let mut bdp = Bdp::new(65_535, 200_000);
+
+        // The window is not used up.
+        assert_eq!(bdp.calculate(10_000, rtt), None);
+
+        assert_eq!(bdp.calculate(50_000, rtt), Some(100_000));
+        assert_eq!(bdp.calculate(80_000, rtt), Some(160_000));
+
+        // Lower bandwidth than before.
+        assert_eq!(bdp.calculate(60_000, rtt), None);
+
+        assert_eq!(bdp.calculate(150_000, rtt), Some(200_000));
+        assert_eq!(bdp.calculate(200_000, rtt), None);
+    }
+
+    #[test]
+    fn test_record_data() {
+        let mut bdp = Bdp::new(65_535, 200_000);
+        assert!(!bdp.recv_pong());
+
+        bdp.record_data(1_000);
+        assert!(bdp.is_ping_pending());
+        bdp.ping_sent();
+        bdp.record_data(60_000);
+        assert!(bdp.recv_pong());
+        assert_eq!(bdp.take_window(), Some(122_000));
+        assert_eq!(bdp.take_window(), None);
+
+        // The next PING is delayed.
+        bdp.record_data(1_000);
+        assert!(!bdp.is_ping_pending());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::codec::UserError;
-use crate::frame::{Priorities, PseudoOrder, Reason, StreamDependency, StreamId};
-use crate::{client, server};
+use crate::frame::{ExtensionFrame, Priorities, PseudoOrder, Reason, StreamDependency, StreamId};
+use crate::{client, server, ConnectionStats, Fingerprint, SettingsSnapshot, Timer};
 
 use crate::frame::DEFAULT_INITIAL_WINDOW_SIZE;
 use crate::proto::*;
@@ -10,10 +10,16 @@ use futures_core::Stream;
 use std::io;
 use std::marker::PhantomData;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 use tokio::io::AsyncRead;
 
+/// The maximum number of received connection level ALTSVC frames buffered
+/// until the application polls them. Older frames are dropped first, as newer
+/// advertisements replace them.
+const MAX_BUFFERED_ALTSVCS: usize = 16;
+
 /// An H2 connection
 #[derive(Debug)]
 pub(crate) struct Connection<T, P, B: Buf = Bytes>
@@ -48,6 +54,12 @@ where
     /// Ping/pong handler
     ping_pong: PingPong,
 
+    /// Extension frames sent and received by the application
+    extension_frames: ExtensionFrames,
+
+    /// Connection level ALTSVC frames received by a client
+    altsvcs: RecvQueue<frame::AltSvc>,
+
     /// Connection settings
     settings: Settings,
 
@@ -71,6 +83,10 @@ struct DynConnection<'a, B: Buf = Bytes> {
     error: &'a mut Option<frame::GoAway>,
 
     ping_pong: &'a mut PingPong,
+
+    extension_frames: &'a mut ExtensionFrames,
+
+    altsvcs: &'a mut RecvQueue<frame::AltSvc>,
 }
 
 #[derive(Debug, Clone)]
@@ -79,6 +95,10 @@ pub(crate) struct Config {
     pub initial_max_send_streams: usize,
     pub max_send_buffer_size: usize,
     pub reset_stream_duration: Duration,
+    pub settings_ack_timeout: Option<Duration>,
+    pub keep_alive: Option<KeepAliveConfig>,
+    pub adaptive_window: Option<WindowSize>,
+    pub timer: Arc<dyn Timer>,
     pub reset_stream_max: usize,
     pub remote_reset_stream_max: usize,
     pub local_error_reset_streams_max: Option<usize>,
@@ -86,6 +106,7 @@ pub(crate) struct Config {
     pub headers_pseudo_order: Option<PseudoOrder>,
     pub headers_stream_dependency: Option<StreamDependency>,
     pub priorities: Option<Priorities>,
+    pub dependency_tree_scheduling: bool,
 }
 
 #[derive(Debug)]
@@ -129,6 +150,7 @@ where
                 headers_stream_dependency: config.headers_stream_dependency,
                 headers_pseudo_order: config.headers_pseudo_order.clone(),
                 priorities: config.priorities.clone(),
+                dependency_tree_scheduling: config.dependency_tree_scheduling,
             }
         }
         let streams = Streams::new(streams_config(&config));
@@ -138,8 +160,20 @@ where
                 state: State::Open,
                 error: None,
                 go_away: GoAway::new(),
-                ping_pong: PingPong::new(),
-                settings: Settings::new(config.settings),
+                ping_pong: PingPong::new(
+                    config.keep_alive.clone(),
+                    config.adaptive_window.map(|max| {
+                        let initial = config
+                            .settings
+                            .initial_window_size()
+                            .unwrap_or(DEFAULT_INITIAL_WINDOW_SIZE);
+                        Bdp::new(initial, max)
+                    }),
+                    config.timer.clone(),
+                ),
+                extension_frames: ExtensionFrames::new(),
+                altsvcs: RecvQueue::new(MAX_BUFFERED_ALTSVCS, Overflow::DropOldest),
+                settings: Settings::new(config.settings, config.settings_ack_timeout, config.timer),
                 streams,
                 span: tracing::debug_span!("Connection", peer = %P::NAME),
                 _phantom: PhantomData,
@@ -154,6 +188,13 @@ where
         debug_assert!(_res.is_ok());
     }
 
+    /// Records a connection WINDOW_UPDATE written outside of the connection,
+    /// during the handshake.
+    pub(crate) fn sent_connection_window_update(&mut self, increment: WindowSize) {
+        let _res = self.inner.streams.sent_connection_window_update(increment);
+        debug_assert!(_res.is_ok());
+    }
+
     /// Send a new SETTINGS frame with an updated initial window size.
     pub(crate) fn set_initial_window_size(&mut self, size: WindowSize) -> Result<(), UserError> {
         let mut settings = frame::Settings::default();
@@ -180,6 +221,66 @@ where
         self.inner.streams.max_recv_streams()
     }
 
+    /// Returns a snapshot of the statistics of the connection.
+    pub(crate) fn stats(&self) -> ConnectionStats {
+        let (send_window, recv_window) = self.inner.streams.connection_windows();
+        ConnectionStats::new(
+            self.codec.send_stats().clone(),
+            self.codec.recv_stats().clone(),
+            *self.codec.send_hpack_stats(),
+            *self.codec.recv_hpack_stats(),
+            send_window,
+            recv_window,
+            self.inner.ping_pong.rtt(),
+        )
+    }
+
+    /// Returns the local SETTINGS acknowledged by the remote peer.
+    pub(crate) fn local_settings(&self) -> SettingsSnapshot {
+        self.inner.streams.local_settings()
+    }
+
+    /// Returns the SETTINGS sent by the remote peer.
+    pub(crate) fn remote_settings(&self) -> SettingsSnapshot {
+        self.inner.streams.remote_settings()
+    }
+
+    /// Polls until the remote peer sends new SETTINGS, returning them, or
+    /// `None` once the connection is closed.
+    pub(crate) fn poll_remote_settings(&mut self, cx: &Context) -> Poll<Option<SettingsSnapshot>> {
+        if ready!(self.inner.settings.poll_remote_settings(cx)) {
+            Poll::Ready(Some(self.remote_settings()))
+        } else {
+            Poll::Ready(None)
+        }
+    }
+
+    /// Polls for the next extension frame received from the remote peer.
+    pub(crate) fn poll_extension_frame(&mut self, cx: &Context) -> Poll<Option<ExtensionFrame>> {
+        self.inner.extension_frames.poll_recv(cx)
+    }
+
+    /// Queues an extension frame, written the next time the connection is
+    /// polled.
+    pub(crate) fn send_extension_frame(
+        &mut self,
+        kind: u8,
+        flags: u8,
+        stream_id: u32,
+        payload: Bytes,
+    ) -> Result<(), UserError> {
+        let max_frame_size = self.codec.max_send_frame_size();
+        self.inner
+            .extension_frames
+            .send_frame(kind, flags, stream_id, payload, max_frame_size)
+    }
+
+    /// Returns the fingerprint of the remote peer, if it was recorded and its
+    /// first HEADERS frame has been received.
+    pub(crate) fn peer_fingerprint(&self) -> Option<&Fingerprint> {
+        self.codec.peer_fingerprint()
+    }
+
     #[cfg(feature = "unstable")]
     pub fn num_wired_streams(&self) -> usize {
         self.inner.streams.num_wired_streams()
@@ -194,12 +295,46 @@ where
         let span = tracing::trace_span!("poll_ready");
         let _e = span.enter();
         // The order of these calls don't really matter too much
+        let is_idle = !self.inner.streams.has_streams();
+        ready!(self
+            .inner
+            .ping_pong
+            .poll_keep_alive(cx, &mut self.codec, is_idle))?;
         ready!(self.inner.ping_pong.send_pending_pong(cx, &mut self.codec))?;
         ready!(self.inner.ping_pong.send_pending_ping(cx, &mut self.codec))?;
+
+        // A new initial window size can only be sent once the previous
+        // SETTINGS are acknowledged.
+        if !self.inner.settings.is_pending() {
+            if let Some(window) = self.inner.ping_pong.take_bdp_window() {
+                tracing::debug!("applying BDP window; window={}", window);
+
+                // The estimate only grows the windows: it starts from the
+                // stream window, and may be below windows the user
+                // configured larger.
+                let (connection_window, stream_window) = self.inner.streams.recv_window_targets();
+
+                if window > connection_window {
+                    let _res = self.inner.streams.set_target_connection_window_size(window);
+                    debug_assert!(_res.is_ok());
+                }
+
+                if window > stream_window {
+                    let mut settings = frame::Settings::default();
+                    settings.set_initial_window_size(Some(window));
+                    self.inner
+                        .settings
+                        .send_settings(settings)
+                        .expect("settings are not pending");
+                }
+            }
+        }
+
         ready!(self
             .inner
             .settings
             .poll_send(cx, &mut self.codec, &mut self.inner.streams))?;
+        self.inner.settings.poll_ack_timeout(cx)?;
         ready!(self.inner.streams.send_pending_refusal(cx, &mut self.codec))?;
 
         Poll::Ready(Ok(()))
@@ -213,8 +348,32 @@ where
         self.inner.go_away.send_pending_go_away(cx, &mut self.codec)
     }
 
-    pub fn go_away_from_user(&mut self, e: Reason) {
-        self.inner.as_dyn().go_away_from_user(e)
+    pub fn go_away_from_user(&mut self, e: Reason, debug_data: Bytes) {
+        self.inner.as_dyn().go_away_from_user(e, debug_data)
+    }
+
+    pub fn go_away_gracefully(&mut self) {
+        if self.inner.go_away.is_going_away() {
+            // No reason to start a new one.
+            return;
+        }
+
+        // According to http://httpwg.org/specs/rfc7540.html#GOAWAY:
+        //
+        // > A server that is attempting to gracefully shut down a connection
+        // > SHOULD send an initial GOAWAY frame with the last stream
+        // > identifier set to 2^31-1 and a NO_ERROR code. This signals to the
+        // > client that a shutdown is imminent and that initiating further
+        // > requests is prohibited. After allowing time for any in-flight
+        // > stream creation (at least one round-trip time), the server can
+        // > send another GOAWAY frame with an updated last stream identifier.
+        // > This ensures that a connection can be cleanly shut down without
+        // > losing requests.
+        self.inner.as_dyn().go_away(StreamId::MAX, Reason::NO_ERROR);
+
+        // We take the advice of waiting 1 RTT literally, and wait
+        // for a pong before proceeding.
+        self.inner.ping_pong.ping_shutdown();
     }
 
     fn take_error(&mut self, ours: Reason, initiator: Initiator) -> Result<(), Error> {
@@ -229,7 +388,7 @@ where
 
         match (ours, theirs) {
             (Reason::NO_ERROR, Reason::NO_ERROR) => Ok(()),
-            (ours, Reason::NO_ERROR) => Err(Error::GoAway(Bytes::new(), ours, initiator)),
+            (ours, Reason::NO_ERROR) => Err(Error::GoAway(Bytes::new(), ours, initiator, None)),
             // If both sides reported an error, give their
             // error back to th user. We assume our error
             // was a consequence of their error, and less
@@ -313,6 +472,9 @@ where
                     self.inner.state = State::Closed(reason, initiator);
                 }
                 State::Closed(reason, initiator) => {
+                    self.inner.extension_frames.close();
+                    self.inner.altsvcs.close();
+                    self.inner.settings.close();
                     return Poll::Ready(self.take_error(reason, initiator));
                 }
             }
@@ -326,6 +488,13 @@ where
         self.clear_expired_reset_streams();
 
         loop {
+            // Extension frames queued by the user are written before any
+            // GOAWAY, so that they are not lost when the connection closes.
+            ready!(self
+                .inner
+                .extension_frames
+                .send_pending(cx, &mut self.codec))?;
+
             // First, ensure that the `Connection` is able to receive a frame
             //
             // The order here matters:
@@ -357,7 +526,7 @@ where
             {
                 ReceivedFrame::Settings(frame) => {
                     self.inner.settings.recv_settings(
-                        frame,
+                        *frame,
                         &mut self.codec,
                         &mut self.inner.streams,
                     )?;
@@ -387,6 +556,8 @@ where
             streams,
             error,
             ping_pong,
+            extension_frames,
+            altsvcs,
             ..
         } = self;
         let streams = streams.as_dyn();
@@ -396,6 +567,8 @@ where
             streams,
             error,
             ping_pong,
+            extension_frames,
+            altsvcs,
         }
     }
 }
@@ -422,9 +595,9 @@ where
         self.go_away.go_away_now(frame);
     }
 
-    fn go_away_from_user(&mut self, e: Reason) {
+    fn go_away_from_user(&mut self, e: Reason, debug_data: Bytes) {
         let last_processed_id = self.streams.last_processed_id();
-        let frame = frame::GoAway::new(last_processed_id, e);
+        let frame = frame::GoAway::with_debug_data(last_processed_id, e, debug_data);
         self.go_away.go_away_from_user(frame);
 
         // Notify all streams of reason we're abruptly closing.
@@ -441,7 +614,7 @@ where
             // Attempting to read a frame resulted in a connection level
             // error. This is handled by setting a GOAWAY frame followed by
             // terminating the connection.
-            Err(Error::GoAway(debug_data, reason, initiator)) => {
+            Err(Error::GoAway(debug_data, reason, initiator, _)) => {
                 self.handle_go_away(reason, debug_data, initiator);
                 Ok(())
             }
@@ -489,11 +662,18 @@ where
                 // Return the error
                 Err(e)
             }
+            // The remote stopped answering keep-alive PINGs. All active
+            // streams must be reset.
+            Err(e @ Error::KeepAliveTimeout) => {
+                tracing::debug!("Connection::poll; keep-alive timed out");
+                self.streams.handle_error(e.clone());
+                Err(e)
+            }
         }
     }
 
     fn handle_go_away(&mut self, reason: Reason, debug_data: Bytes, initiator: Initiator) {
-        let e = Error::GoAway(debug_data.clone(), reason, initiator);
+        let e = Error::GoAway(debug_data.clone(), reason, initiator, None);
         tracing::debug!(error = ?e, "Connection::poll; connection error");
 
         // We may have already sent a GOAWAY for this error,
@@ -515,6 +695,11 @@ where
 
     fn recv_frame(&mut self, frame: Option<Frame>) -> Result<ReceivedFrame, Error> {
         use crate::frame::Frame::*;
+
+        if frame.is_some() {
+            self.ping_pong.record_activity();
+        }
+
         match frame {
             Some(Headers(frame)) => {
                 tracing::trace!(?frame, "recv HEADERS");
@@ -522,6 +707,7 @@ where
             }
             Some(Data(frame)) => {
                 tracing::trace!(?frame, "recv DATA");
+                self.ping_pong.record_data(frame.payload().len());
                 self.streams.recv_data(frame)?;
             }
             Some(Reset(frame)) => {
@@ -534,7 +720,7 @@ where
             }
             Some(Settings(frame)) => {
                 tracing::trace!(?frame, "recv SETTINGS");
-                return Ok(ReceivedFrame::Settings(frame));
+                return Ok(ReceivedFrame::Settings(Box::new(frame)));
             }
             Some(GoAway(frame)) => {
                 tracing::trace!(?frame, "recv GOAWAY");
@@ -564,7 +750,35 @@ where
             }
             Some(Priority(frame)) => {
                 tracing::trace!(?frame, "recv PRIORITY");
-                // TODO: handle
+                self.streams.recv_priority(&frame);
+            }
+            Some(Extension(frame)) => {
+                tracing::trace!(?frame, "recv extension frame");
+                self.extension_frames.recv_frame(frame);
+            }
+            Some(AltSvc(frame)) => {
+                tracing::trace!(?frame, "recv ALTSVC");
+                // Servers ignore ALTSVC frames.
+                if !self.streams.is_server() {
+                    if frame.stream_id().as_u32() == 0 {
+                        if self.altsvcs.recv(frame).is_some() {
+                            tracing::debug!("dropping oldest ALTSVC frame; buffer full");
+                        }
+                    } else {
+                        self.streams.recv_altsvc(frame);
+                    }
+                }
+            }
+            Some(Origin(frame)) => {
+                tracing::trace!(?frame, "recv ORIGIN");
+                // Servers ignore ORIGIN frames.
+                if !self.streams.is_server() {
+                    self.streams.recv_origin(&frame);
+                }
+            }
+            Some(PriorityUpdate(frame)) => {
+                tracing::trace!(?frame, "recv PRIORITY_UPDATE");
+                self.streams.recv_priority_update(frame)?;
             }
             None => {
                 tracing::trace!("codec closed");
@@ -577,7 +791,7 @@ where
 }
 
 enum ReceivedFrame {
-    Settings(frame::Settings),
+    Settings(Box<frame::Settings>),
     Continue,
     Done,
 }
@@ -590,6 +804,16 @@ where
     pub(crate) fn streams(&self) -> &Streams<B, client::Peer> {
         &self.inner.streams
     }
+
+    /// Polls for the next connection level ALTSVC frame.
+    pub(crate) fn poll_altsvc(&mut self, cx: &Context) -> Poll<Option<frame::AltSvc>> {
+        self.inner.altsvcs.poll_recv(cx)
+    }
+
+    /// Refuses to send new requests, for a graceful shutdown.
+    pub(crate) fn refuse_new_streams(&mut self) {
+        self.inner.streams.refuse_new_streams();
+    }
 }
 
 impl<T, B> Connection<T, server::Peer, B>
@@ -601,29 +825,33 @@ where
         self.inner.streams.next_incoming()
     }
 
-    // Graceful shutdown only makes sense for server peers.
-    pub fn go_away_gracefully(&mut self) {
-        if self.inner.go_away.is_going_away() {
-            // No reason to start a new one.
-            return;
-        }
+    /// Applies the SETTINGS of an HTTP/1.1 request upgraded to HTTP/2, then
+    /// opens stream 1 with the request, half-closed (remote).
+    pub(crate) fn recv_upgrade(
+        &mut self,
+        settings: &frame::Settings,
+        request: frame::Headers,
+    ) -> Result<(), Error> {
+        self.inner.settings.recv_upgrade_settings(
+            settings,
+            &mut self.codec,
+            &mut self.inner.streams,
+        )?;
+        self.inner.streams.as_dyn().recv_headers(request)
+    }
 
-        // According to http://httpwg.org/specs/rfc7540.html#GOAWAY:
-        //
-        // > A server that is attempting to gracefully shut down a connection
-        // > SHOULD send an initial GOAWAY frame with the last stream
-        // > identifier set to 2^31-1 and a NO_ERROR code. This signals to the
-        // > client that a shutdown is imminent and that initiating further
-        // > requests is prohibited. After allowing time for any in-flight
-        // > stream creation (at least one round-trip time), the server can
-        // > send another GOAWAY frame with an updated last stream identifier.
-        // > This ensures that a connection can be cleanly shut down without
-        // > losing requests.
-        self.inner.as_dyn().go_away(StreamId::MAX, Reason::NO_ERROR);
+    /// Queues an ORIGIN frame.
+    pub(crate) fn send_origin(&mut self, origins: Vec<Bytes>) -> Result<(), UserError> {
+        self.inner.streams.send_origin(origins)
+    }
 
-        // We take the advice of waiting 1 RTT literally, and wait
-        // for a pong before proceeding.
-        self.inner.ping_pong.ping_shutdown();
+    /// Queues a connection level ALTSVC frame.
+    pub(crate) fn send_altsvc(
+        &mut self,
+        origin: Bytes,
+        field_value: Bytes,
+    ) -> Result<(), UserError> {
+        self.inner.streams.send_altsvc(origin, field_value)
     }
 }
 
@@ -9,8 +9,12 @@ use std::io;
 #[derive(Clone, Debug)]
 pub enum Error {
     Reset(StreamId, Reason, Initiator),
-    GoAway(Bytes, Reason, Initiator),
+    /// The last `StreamId` is set if the error is from a received GOAWAY, and
+    /// the stream was not processed by the remote.
+    GoAway(Bytes, Reason, Initiator, Option<StreamId>),
     Io(io::ErrorKind, Option<String>),
+    /// A keep-alive PING was not acknowledged in time.
+    KeepAliveTimeout,
 }
 
 pub struct GoAway {
@@ -28,13 +32,13 @@ pub enum Initiator {
 impl Error {
     pub(crate) fn is_local(&self) -> bool {
         match *self {
-            Self::Reset(_, _, initiator) | Self::GoAway(_, _, initiator) => initiator.is_local(),
-            Self::Io(..) => true,
+            Self::Reset(_, _, initiator) | Self::GoAway(_, _, initiator, _) => initiator.is_local(),
+            Self::Io(..) | Self::KeepAliveTimeout => true,
         }
     }
 
     pub(crate) fn user_go_away(reason: Reason) -> Self {
-        Self::GoAway(Bytes::new(), reason, Initiator::User)
+        Self::GoAway(Bytes::new(), reason, Initiator::User, None)
     }
 
     pub(crate) fn library_reset(stream_id: StreamId, reason: Reason) -> Self {
@@ -42,11 +46,11 @@ impl Error {
     }
 
     pub(crate) fn library_go_away(reason: Reason) -> Self {
-        Self::GoAway(Bytes::new(), reason, Initiator::Library)
+        Self::GoAway(Bytes::new(), reason, Initiator::Library, None)
     }
 
     pub(crate) fn library_go_away_data(reason: Reason, debug_data: impl Into<Bytes>) -> Self {
-        Self::GoAway(debug_data.into(), reason, Initiator::Library)
+        Self::GoAway(debug_data.into(), reason, Initiator::Library, None)
     }
 
     pub(crate) fn remote_reset(stream_id: StreamId, reason: Reason) -> Self {
@@ -54,7 +58,17 @@ impl Error {
     }
 
     pub(crate) fn remote_go_away(debug_data: Bytes, reason: Reason) -> Self {
-        Self::GoAway(debug_data, reason, Initiator::Remote)
+        Self::GoAway(debug_data, reason, Initiator::Remote, None)
+    }
+
+    /// The error of streams which were not processed by the remote before it
+    /// sent a GOAWAY.
+    pub(crate) fn remote_go_away_refused(
+        debug_data: Bytes,
+        reason: Reason,
+        last_stream_id: StreamId,
+    ) -> Self {
+        Self::GoAway(debug_data, reason, Initiator::Remote, Some(last_stream_id))
     }
 }
 
@@ -74,9 +88,10 @@ impl Initiator {
 impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Self::Reset(_, reason, _) | Self::GoAway(_, reason, _) => reason.fmt(fmt),
+            Self::Reset(_, reason, _) | Self::GoAway(_, reason, _, _) => reason.fmt(fmt),
             Self::Io(_, Some(ref inner)) => inner.fmt(fmt),
             Self::Io(kind, None) => io::Error::from(kind).fmt(fmt),
+            Self::KeepAliveTimeout => fmt.write_str("keep-alive timed out"),
         }
     }
 }
@@ -0,0 +1,95 @@
+use crate::codec::{Codec, UserError};
+use crate::frame::{self, ExtensionFrame};
+
+use super::recv_queue::{Overflow, RecvQueue};
+
+use bytes::Buf;
+use std::collections::VecDeque;
+use std::io;
+use std::task::{Context, Poll};
+use tokio::io::AsyncWrite;
+
+/// The maximum number of received extension frames buffered until the
+/// application polls them. Frames received past this limit are dropped.
+const MAX_BUFFERED_RECV: usize = 64;
+
+/// Buffers extension frames between the connection and the application.
+#[derive(Debug)]
+pub(crate) struct ExtensionFrames {
+    /// Frames received, waiting to be polled.
+    received: RecvQueue<ExtensionFrame>,
+
+    /// Frames waiting to be written.
+    pending_send: VecDeque<ExtensionFrame>,
+}
+
+// ===== impl ExtensionFrames =====
+
+impl ExtensionFrames {
+    pub(crate) fn new() -> Self {
+        ExtensionFrames {
+            received: RecvQueue::new(MAX_BUFFERED_RECV, Overflow::DropNewest),
+            pending_send: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn recv_frame(&mut self, frame: ExtensionFrame) {
+        if let Some(frame) = self.received.recv(frame) {
+            tracing::debug!(kind = frame.kind(), "dropping extension frame; buffer full");
+        }
+    }
+
+    pub(crate) fn poll_recv(&mut self, cx: &Context) -> Poll<Option<ExtensionFrame>> {
+        self.received.poll_recv(cx)
+    }
+
+    pub(crate) fn send_frame(
+        &mut self,
+        kind: u8,
+        flags: u8,
+        stream_id: u32,
+        payload: bytes::Bytes,
+        max_frame_size: usize,
+    ) -> Result<(), UserError> {
+        if !frame::is_extension_type(kind) {
+            return Err(UserError::UnexpectedFrameType);
+        }
+
+        if payload.len() > max_frame_size {
+            return Err(UserError::PayloadTooBig);
+        }
+
+        self.pending_send
+            .push_back(ExtensionFrame::new(kind, flags, stream_id, payload));
+        Ok(())
+    }
+
+    /// Send any pending extension frames.
+    pub(crate) fn send_pending<T, B>(
+        &mut self,
+        cx: &mut Context,
+        dst: &mut Codec<T, B>,
+    ) -> Poll<io::Result<()>>
+    where
+        T: AsyncWrite + Unpin,
+        B: Buf,
+    {
+        while !self.pending_send.is_empty() {
+            ready!(dst.poll_ready(cx))?;
+
+            let frame = self.pending_send.pop_front().unwrap();
+            if let Err(e) = dst.buffer(frame.into()) {
+                // The peer lowered its max frame size since the frame was
+                // queued.
+                tracing::debug!(error = ?e, "dropping extension frame");
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Notifies the application that no more frames will be received.
+    pub(crate) fn close(&mut self) {
+        self.received.close();
+    }
+}
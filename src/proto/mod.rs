@@ -1,22 +1,28 @@
+mod bdp;
 mod connection;
 mod error;
+mod extension;
 mod go_away;
 mod peer;
 mod ping_pong;
+mod recv_queue;
 mod settings;
 mod streams;
 
 pub(crate) use self::connection::{Config, Connection};
 pub use self::error::{Error, Initiator};
 pub(crate) use self::peer::{Dyn as DynPeer, Peer};
-pub(crate) use self::ping_pong::UserPings;
+pub(crate) use self::ping_pong::{KeepAliveConfig, UserPings};
 pub(crate) use self::streams::{DynStreams, OpaqueStreamRef, StreamRef, Streams};
 pub(crate) use self::streams::{Open, PollReset, Prioritized};
 
 use crate::codec::Codec;
 
+use self::bdp::Bdp;
+use self::extension::ExtensionFrames;
 use self::go_away::GoAway;
 use self::ping_pong::PingPong;
+use self::recv_queue::{Overflow, RecvQueue};
 use self::settings::Settings;
 
 use crate::frame::{self, Frame};
@@ -39,4 +45,5 @@ pub const DEFAULT_RESET_STREAM_MAX: usize = 50;
 // RFC 9113#5.4.2 suggests ~1 RTT. We don't track that closely, but use a
 // reasonable guess of the average here.
 pub const DEFAULT_RESET_STREAM_SECS: u64 = 1;
+pub const DEFAULT_KEEP_ALIVE_TIMEOUT_SECS: u64 = 20;
 pub const DEFAULT_MAX_SEND_BUFFER_SIZE: usize = 1024 * 400;
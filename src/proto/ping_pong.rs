@@ -1,13 +1,17 @@
 use crate::codec::Codec;
 use crate::frame::Ping;
-use crate::proto::{self, PingPayload};
+use crate::proto::bdp::Bdp;
+use crate::proto::{self, PingPayload, WindowSize};
+use crate::{Sleep, Timer};
 
 use atomic_waker::AtomicWaker;
 use bytes::Buf;
-use std::io;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use std::{fmt, io};
 use tokio::io::AsyncWrite;
 
 /// Acknowledges ping requests from the remote.
@@ -16,6 +20,45 @@ pub(crate) struct PingPong {
     pending_ping: Option<PendingPing>,
     pending_pong: Option<PingPayload>,
     user_pings: Option<UserPingsRx>,
+    keep_alive: Option<KeepAlive>,
+    bdp: Option<Bdp>,
+    /// When each PING awaiting its PONG was sent.
+    sent_at: Vec<(PingPayload, Instant)>,
+    /// Round trip time of the last PING acknowledged by the remote.
+    rtt: Option<Duration>,
+}
+
+/// Keep-alive configuration.
+#[derive(Debug, Clone)]
+pub(crate) struct KeepAliveConfig {
+    /// Time without receiving a frame before a PING is sent.
+    pub interval: Duration,
+    /// Time to wait for the PONG before closing the connection.
+    pub timeout: Duration,
+    /// Whether PINGs are sent while there are no open streams.
+    pub while_idle: bool,
+}
+
+/// Sends PINGs when the connection is quiet, and closes it if the remote does
+/// not answer them in time.
+struct KeepAlive {
+    config: KeepAliveConfig,
+    timer: Arc<dyn Timer>,
+    state: KeepAliveState,
+    /// Whether a frame was received since the interval timer was started.
+    has_activity: bool,
+    /// The interval timer, or the PONG timeout once the PING is sent.
+    sleep: Option<Pin<Box<dyn Sleep>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeepAliveState {
+    /// Waiting for the interval to elapse.
+    Scheduled,
+    /// The PING must be written.
+    PingPending,
+    /// The PING was written, waiting for the PONG.
+    PingSent,
 }
 
 #[derive(Debug)]
@@ -61,11 +104,25 @@ const USER_STATE_CLOSED: usize = 4;
 // ===== impl PingPong =====
 
 impl PingPong {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(
+        keep_alive: Option<KeepAliveConfig>,
+        bdp: Option<Bdp>,
+        timer: Arc<dyn Timer>,
+    ) -> Self {
         PingPong {
             pending_ping: None,
             pending_pong: None,
             user_pings: None,
+            keep_alive: keep_alive.map(|config| KeepAlive {
+                config,
+                timer,
+                state: KeepAliveState::Scheduled,
+                has_activity: false,
+                sleep: None,
+            }),
+            bdp,
+            sent_at: Vec::new(),
+            rtt: None,
         }
     }
 
@@ -100,6 +157,8 @@ impl PingPong {
         assert!(self.pending_pong.is_none());
 
         if ping.is_ack() {
+            self.record_pong(ping.payload());
+
             if let Some(pending) = self.pending_ping.take() {
                 if &pending.payload == ping.payload() {
                     assert_eq!(
@@ -122,6 +181,20 @@ impl PingPong {
                 }
             }
 
+            if let Some(ref mut keep_alive) = self.keep_alive {
+                if ping.payload() == &Ping::KEEP_ALIVE && keep_alive.receive_pong() {
+                    tracing::trace!("recv PING KEEP_ALIVE ack");
+                    return ReceivedPing::Unknown;
+                }
+            }
+
+            if let Some(ref mut bdp) = self.bdp {
+                if ping.payload() == &Ping::BDP && bdp.recv_pong() {
+                    tracing::trace!("recv PING BDP ack");
+                    return ReceivedPing::Unknown;
+                }
+            }
+
             // else we were acked a ping we didn't send?
             // The spec doesn't require us to do anything about this,
             // so for resiliency, just ignore it for now.
@@ -176,6 +249,8 @@ impl PingPong {
                 dst.buffer(Ping::new(ping.payload).into())
                     .expect("invalid ping frame");
                 ping.sent = true;
+                let payload = ping.payload;
+                self.record_ping(payload);
             }
         } else if let Some(ref users) = self.user_pings {
             if users.0.state.load(Ordering::Acquire) == USER_STATE_PENDING_PING {
@@ -189,13 +264,173 @@ impl PingPong {
                     .0
                     .state
                     .store(USER_STATE_PENDING_PONG, Ordering::Release);
+                self.record_ping(Ping::USER);
             } else {
                 users.0.ping_task.register(cx.waker());
             }
         }
 
+        if let Some(ref mut bdp) = self.bdp {
+            if bdp.is_ping_pending() {
+                if !dst.poll_ready(cx)?.is_ready() {
+                    return Poll::Pending;
+                }
+
+                dst.buffer(Ping::new(Ping::BDP).into())
+                    .expect("invalid ping frame");
+                bdp.ping_sent();
+                self.record_ping(Ping::BDP);
+            }
+        }
+
         Poll::Ready(Ok(()))
     }
+
+    /// Records received DATA for the bandwidth-delay product estimate.
+    pub(crate) fn record_data(&mut self, len: usize) {
+        if let Some(ref mut bdp) = self.bdp {
+            bdp.record_data(len);
+        }
+    }
+
+    /// Takes the receive window to use, if the bandwidth-delay product
+    /// estimate increased.
+    pub(crate) fn take_bdp_window(&mut self) -> Option<WindowSize> {
+        self.bdp.as_mut().and_then(Bdp::take_window)
+    }
+
+    /// Notes that a frame was received, which postpones the next keep-alive
+    /// PING.
+    pub(crate) fn record_activity(&mut self) {
+        if let Some(ref mut keep_alive) = self.keep_alive {
+            keep_alive.has_activity = true;
+        }
+    }
+
+    /// Drives the keep-alive timers, and sends the keep-alive PING when due.
+    ///
+    /// Returns an error if the remote did not answer a keep-alive PING in
+    /// time.
+    pub(crate) fn poll_keep_alive<T, B>(
+        &mut self,
+        cx: &mut Context,
+        dst: &mut Codec<T, B>,
+        is_idle: bool,
+    ) -> Poll<Result<(), proto::Error>>
+    where
+        T: AsyncWrite + Unpin,
+        B: Buf,
+    {
+        let keep_alive = match self.keep_alive {
+            Some(ref mut keep_alive) => keep_alive,
+            None => return Poll::Ready(Ok(())),
+        };
+
+        let was_sent = keep_alive.state == KeepAliveState::PingSent;
+        let res = keep_alive.poll(cx, dst, is_idle);
+        if !was_sent && keep_alive.state == KeepAliveState::PingSent {
+            self.record_ping(Ping::KEEP_ALIVE);
+        }
+        res
+    }
+
+    /// Returns the round trip time of the last PING acknowledged by the
+    /// remote.
+    pub(crate) fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+
+    fn record_ping(&mut self, payload: PingPayload) {
+        let now = Instant::now();
+        match self.sent_at.iter_mut().find(|(p, _)| *p == payload) {
+            Some(entry) => entry.1 = now,
+            None => self.sent_at.push((payload, now)),
+        }
+    }
+
+    fn record_pong(&mut self, payload: &PingPayload) {
+        if let Some(i) = self.sent_at.iter().position(|(p, _)| p == payload) {
+            let (_, sent_at) = self.sent_at.swap_remove(i);
+            self.rtt = Some(sent_at.elapsed());
+        }
+    }
+}
+
+// ===== impl KeepAlive =====
+
+impl KeepAlive {
+    fn poll<T, B>(
+        &mut self,
+        cx: &mut Context,
+        dst: &mut Codec<T, B>,
+        is_idle: bool,
+    ) -> Poll<Result<(), proto::Error>>
+    where
+        T: AsyncWrite + Unpin,
+        B: Buf,
+    {
+        loop {
+            let duration = match self.state {
+                KeepAliveState::Scheduled => self.config.interval,
+                KeepAliveState::PingPending => {
+                    if !dst.poll_ready(cx)?.is_ready() {
+                        return Poll::Pending;
+                    }
+
+                    dst.buffer(Ping::new(Ping::KEEP_ALIVE).into())
+                        .expect("invalid ping frame");
+                    tracing::trace!("keep-alive PING sent");
+                    self.state = KeepAliveState::PingSent;
+                    continue;
+                }
+                KeepAliveState::PingSent => self.config.timeout,
+            };
+
+            let timer = &self.timer;
+            let sleep = self.sleep.get_or_insert_with(|| timer.sleep(duration));
+
+            if sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Ready(Ok(()));
+            }
+
+            self.sleep = None;
+
+            if self.state == KeepAliveState::PingSent {
+                tracing::debug!("keep-alive PING timed out");
+                return Poll::Ready(Err(proto::Error::KeepAliveTimeout));
+            }
+
+            if self.has_activity || (is_idle && !self.config.while_idle) {
+                // Restart the interval
+                self.has_activity = false;
+                continue;
+            }
+
+            self.state = KeepAliveState::PingPending;
+        }
+    }
+
+    fn receive_pong(&mut self) -> bool {
+        if self.state != KeepAliveState::PingSent {
+            return false;
+        }
+
+        self.state = KeepAliveState::Scheduled;
+        self.has_activity = false;
+        self.sleep = None;
+        true
+    }
+}
+
+impl fmt::Debug for KeepAlive {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("KeepAlive")
+            .field("config", &self.config)
+            .field("timer", &self.timer)
+            .field("state", &self.state)
+            .field("has_activity", &self.has_activity)
+            .finish()
+    }
 }
 
 impl ReceivedPing {
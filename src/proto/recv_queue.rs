@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use std::task::{Context, Poll, Waker};
+
+/// Buffers connection level frames received from the remote peer until the
+/// application polls them.
+#[derive(Debug)]
+pub(crate) struct RecvQueue<T> {
+    /// Frames received, waiting to be polled.
+    received: VecDeque<T>,
+
+    /// The maximum number of buffered frames.
+    capacity: usize,
+
+    /// Which frame is dropped when a frame is received while full.
+    overflow: Overflow,
+
+    /// Task waiting for a received frame.
+    recv_task: Option<Waker>,
+
+    /// True once the connection is closed.
+    is_closed: bool,
+}
+
+/// Which frame a full `RecvQueue` drops.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Overflow {
+    /// Drop the frame being received.
+    DropNewest,
+
+    /// Drop the oldest buffered frame, to make room for the received one.
+    DropOldest,
+}
+
+// ===== impl RecvQueue =====
+
+impl<T> RecvQueue<T> {
+    pub(crate) fn new(capacity: usize, overflow: Overflow) -> Self {
+        debug_assert!(capacity > 0);
+
+        RecvQueue {
+            received: VecDeque::new(),
+            capacity,
+            overflow,
+            recv_task: None,
+            is_closed: false,
+        }
+    }
+
+    /// Buffers a received frame, returning the frame dropped if the queue was
+    /// full.
+    pub(crate) fn recv(&mut self, frame: T) -> Option<T> {
+        let mut dropped = None;
+
+        if self.received.len() == self.capacity {
+            match self.overflow {
+                Overflow::DropNewest => return Some(frame),
+                Overflow::DropOldest => dropped = self.received.pop_front(),
+            }
+        }
+
+        self.received.push_back(frame);
+
+        if let Some(task) = self.recv_task.take() {
+            task.wake();
+        }
+
+        dropped
+    }
+
+    pub(crate) fn poll_recv(&mut self, cx: &Context) -> Poll<Option<T>> {
+        if let Some(frame) = self.received.pop_front() {
+            return Poll::Ready(Some(frame));
+        }
+
+        if self.is_closed {
+            return Poll::Ready(None);
+        }
+
+        self.recv_task = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Notifies the application that no more frames will be received.
+    pub(crate) fn close(&mut self) {
+        self.is_closed = true;
+
+        if let Some(task) = self.recv_task.take() {
+            task.wake();
+        }
+    }
+}
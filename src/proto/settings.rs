@@ -1,9 +1,14 @@
 use crate::codec::UserError;
 use crate::error::Reason;
 use crate::proto::*;
-use std::task::{Context, Poll};
+use crate::{Sleep, Timer};
+
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 
-#[derive(Debug)]
 pub(crate) struct Settings {
     /// Our local SETTINGS sync state with the remote.
     local: Local,
@@ -14,6 +19,18 @@ pub(crate) struct Settings {
     /// Whether the connection has received the initial SETTINGS frame from the
     /// remote peer.
     has_received_remote_initial_settings: bool,
+    /// Whether remote SETTINGS were applied since the user last polled them.
+    has_new_remote_settings: bool,
+    /// Task waiting for the remote to send SETTINGS.
+    remote_task: Option<Waker>,
+    /// Whether the connection is closed.
+    is_closed: bool,
+    /// Time to wait for the remote to ACK our SETTINGS.
+    ack_timeout: Option<Duration>,
+    /// Fires when the SETTINGS we are waiting on an ACK for time out. Armed
+    /// lazily, the first time the connection is polled after sending them.
+    ack_deadline: Option<Pin<Box<dyn Sleep>>>,
+    timer: Arc<dyn Timer>,
 }
 
 #[derive(Debug)]
@@ -28,13 +45,23 @@ enum Local {
 }
 
 impl Settings {
-    pub(crate) fn new(local: frame::Settings) -> Self {
+    pub(crate) fn new(
+        local: frame::Settings,
+        ack_timeout: Option<Duration>,
+        timer: Arc<dyn Timer>,
+    ) -> Self {
         Settings {
             // We assume the initial local SETTINGS were flushed during
             // the handshake process.
             local: Local::WaitingAck(local),
             remote: None,
             has_received_remote_initial_settings: false,
+            has_new_remote_settings: false,
+            remote_task: None,
+            is_closed: false,
+            ack_timeout,
+            ack_deadline: None,
+            timer,
         }
     }
 
@@ -69,6 +96,7 @@ impl Settings {
 
                     streams.apply_local_settings(local)?;
                     self.local = Local::Synced;
+                    self.ack_deadline = None;
                     Ok(())
                 }
                 Local::ToSend(..) | Local::Synced => {
@@ -87,6 +115,57 @@ impl Settings {
         }
     }
 
+    /// Applies the SETTINGS of the `HTTP2-Settings` header of an HTTP/1.1
+    /// request upgraded to HTTP/2. The 101 response acknowledges them, so no
+    /// ACK is sent, and the SETTINGS frame following the client preface is
+    /// still the initial one.
+    pub(crate) fn recv_upgrade_settings<T, B, C, P>(
+        &mut self,
+        settings: &frame::Settings,
+        codec: &mut Codec<T, B>,
+        streams: &mut Streams<C, P>,
+    ) -> Result<(), Error>
+    where
+        T: AsyncWrite + Unpin,
+        B: Buf,
+        C: Buf,
+        P: Peer,
+    {
+        tracing::debug!("applying upgrade settings {:?}", settings);
+        self.apply_remote(settings, true, codec, streams)
+    }
+
+    fn apply_remote<T, B, C, P>(
+        &mut self,
+        settings: &frame::Settings,
+        is_initial: bool,
+        dst: &mut Codec<T, B>,
+        streams: &mut Streams<C, P>,
+    ) -> Result<(), Error>
+    where
+        T: AsyncWrite + Unpin,
+        B: Buf,
+        C: Buf,
+        P: Peer,
+    {
+        streams.apply_remote_settings(settings, is_initial)?;
+
+        if let Some(val) = settings.header_table_size() {
+            dst.set_send_header_table_size(val as usize);
+        }
+
+        if let Some(val) = settings.max_frame_size() {
+            dst.set_max_send_frame_size(val as usize);
+        }
+
+        self.has_new_remote_settings = true;
+        if let Some(task) = self.remote_task.take() {
+            task.wake();
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn send_settings(&mut self, frame: frame::Settings) -> Result<(), UserError> {
         assert!(!frame.is_ack());
         match &self.local {
@@ -99,6 +178,60 @@ impl Settings {
         }
     }
 
+    /// Returns `true` if local SETTINGS are waiting to be sent or to be
+    /// acknowledged.
+    pub(crate) fn is_pending(&self) -> bool {
+        !matches!(self.local, Local::Synced)
+    }
+
+    /// Returns an error if the remote did not ACK our SETTINGS within the
+    /// configured timeout.
+    pub(crate) fn poll_ack_timeout(&mut self, cx: &mut Context) -> Result<(), Error> {
+        let timeout = match (&self.local, self.ack_timeout) {
+            (Local::WaitingAck(..), Some(timeout)) => timeout,
+            _ => return Ok(()),
+        };
+
+        let timer = &self.timer;
+        let deadline = self
+            .ack_deadline
+            .get_or_insert_with(|| timer.sleep(timeout));
+
+        if deadline.as_mut().poll(cx).is_ready() {
+            tracing::debug!("connection error SETTINGS_TIMEOUT -- settings ack timed out");
+            return Err(Error::library_go_away(Reason::SETTINGS_TIMEOUT));
+        }
+
+        Ok(())
+    }
+
+    /// Returns `Ready(true)` once remote SETTINGS were applied since the last
+    /// call returned `Ready(true)`, or `Ready(false)` if the connection is
+    /// closed.
+    pub(crate) fn poll_remote_settings(&mut self, cx: &Context) -> Poll<bool> {
+        if self.has_new_remote_settings {
+            self.has_new_remote_settings = false;
+            return Poll::Ready(true);
+        }
+
+        if self.is_closed {
+            return Poll::Ready(false);
+        }
+
+        self.remote_task = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Notifies the task waiting for remote SETTINGS that the connection is
+    /// closed.
+    pub(crate) fn close(&mut self) {
+        self.is_closed = true;
+
+        if let Some(task) = self.remote_task.take() {
+            task.wake();
+        }
+    }
+
     /// Sets `true` to `self.has_received_remote_initial_settings`.
     /// Returns `true` if this method is called for the first time.
     /// (i.e. it is the initial SETTINGS frame from the remote peer)
@@ -134,15 +267,7 @@ impl Settings {
             tracing::trace!("ACK sent; applying settings");
 
             let is_initial = self.mark_remote_initial_settings_as_received();
-            streams.apply_remote_settings(&settings, is_initial)?;
-
-            if let Some(val) = settings.header_table_size() {
-                dst.set_send_header_table_size(val as usize);
-            }
-
-            if let Some(val) = settings.max_frame_size() {
-                dst.set_max_send_frame_size(val as usize);
-            }
+            self.apply_remote(&settings, is_initial, dst, streams)?;
         }
 
         self.remote = None;
@@ -166,3 +291,19 @@ impl Settings {
         Poll::Ready(Ok(()))
     }
 }
+
+impl fmt::Debug for Settings {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Settings")
+            .field("local", &self.local)
+            .field("remote", &self.remote)
+            .field(
+                "has_received_remote_initial_settings",
+                &self.has_received_remote_initial_settings,
+            )
+            .field("has_new_remote_settings", &self.has_new_remote_settings)
+            .field("is_closed", &self.is_closed)
+            .field("ack_timeout", &self.ack_timeout)
+            .finish()
+    }
+}
@@ -81,6 +81,10 @@ pub struct Config {
 
     /// Priorities stream
     pub priorities: Option<Priorities>,
+
+    /// If remote initiated streams are scheduled by the RFC 7540 dependency
+    /// tree.
+    pub dependency_tree_scheduling: bool,
 }
 
 trait DebugStructExt<'a, 'b> {
@@ -1,7 +1,8 @@
 use super::store::Resolve;
 use super::*;
 
-use crate::frame::Reason;
+use crate::ext::ExtensiblePriority;
+use crate::frame::{Reason, StreamDependency};
 
 use crate::codec::UserError;
 use crate::codec::UserError::*;
@@ -9,6 +10,7 @@ use crate::codec::UserError::*;
 use bytes::buf::Take;
 use std::{
     cmp::{self, Ordering},
+    collections::{HashMap, VecDeque},
     fmt, io, mem,
     task::{Context, Poll, Waker},
 };
@@ -22,10 +24,22 @@ use std::{
 /// frame on a higher stream ID. If these queues was not ordered by stream
 /// IDs, some mechanism would be necessary to ensure that the lowest-numbered]
 /// idle stream is opened first.
+///
+/// Streams are additionally scheduled by their RFC 9218 urgency. Locally
+/// initiated streams always keep the default urgency, so the ordering above
+/// still holds within a single urgency queue.
+///
+/// When the RFC 7540 dependency tree is used, remote initiated streams are
+/// scheduled by the tree instead. Locally initiated streams never enter the
+/// tree, for the same reason.
 #[derive(Debug)]
 pub(super) struct Prioritize {
-    /// Queue of streams waiting for socket capacity to send a frame.
-    pending_send: store::Queue<stream::NextSend>,
+    /// Queues of streams waiting for socket capacity to send a frame.
+    pending_send: PendingSend,
+
+    /// Connection level control frames (PRIORITY_UPDATE, ALTSVC and ORIGIN)
+    /// waiting to be sent, in the order they were queued.
+    pending_control: VecDeque<Frame>,
 
     /// Queue of streams waiting for window capacity to produce data.
     pending_capacity: store::Queue<stream::NextSendCapacity>,
@@ -58,6 +72,51 @@ pub(super) struct Prioritize {
     max_buffer_size: usize,
 }
 
+/// Streams waiting for socket capacity, with one queue per urgency level.
+#[derive(Debug)]
+struct PendingSend {
+    queues: [store::Queue<stream::NextSend>; ExtensiblePriority::MAX_URGENCY as usize + 1],
+
+    /// Schedules remote initiated streams when RFC 7540 priorities are used.
+    tree: Option<DependencyTree>,
+}
+
+/// The RFC 7540 dependency tree of the streams.
+///
+/// A stream is only scheduled when none of its ancestors have frames queued.
+/// Siblings share their parent's bandwidth in proportion to their weights,
+/// using stride scheduling: each DATA frame charges the stream and all of its
+/// ancestors `len / weight`, and the sibling charged the least goes next.
+///
+/// See <https://www.rfc-editor.org/rfc/rfc7540#section-5.3>.
+#[derive(Debug)]
+struct DependencyTree {
+    nodes: HashMap<StreamId, Node>,
+}
+
+#[derive(Debug)]
+struct Node {
+    parent: StreamId,
+
+    children: Vec<StreamId>,
+
+    /// The weight of the node, between 1 and 256.
+    weight: u32,
+
+    /// The stream's key, if it has frames queued.
+    queued: Option<store::Key>,
+
+    /// Number of streams with frames queued in the subtree, including the
+    /// node itself.
+    num_queued: usize,
+
+    /// How much the node was charged, relative to its siblings.
+    pass: u64,
+
+    /// The pass of the child that was scheduled last.
+    last_pass: u64,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 enum InFlightData {
     /// There is no `DATA` frame in flight.
@@ -94,7 +153,8 @@ impl Prioritize {
         tracing::trace!("Prioritize::new; flow={:?}", flow);
 
         Prioritize {
-            pending_send: store::Queue::new(),
+            pending_send: PendingSend::new(config.dependency_tree_scheduling),
+            pending_control: VecDeque::new(),
             pending_capacity: store::Queue::new(),
             pending_open: store::Queue::new(),
             flow,
@@ -104,6 +164,52 @@ impl Prioritize {
         }
     }
 
+    /// Updates the dependency tree with a stream dependency received in a
+    /// HEADERS or PRIORITY frame.
+    pub fn recv_stream_dependency(&mut self, id: StreamId, dependency: &StreamDependency) {
+        if let Some(ref mut tree) = self.pending_send.tree {
+            tracing::trace!(?id, ?dependency, "reprioritize");
+            tree.reprioritize(id, dependency);
+        }
+    }
+
+    /// Sets the RFC 9218 priority of a stream, moving it to the queue of its
+    /// new urgency if it is waiting to send frames.
+    pub fn set_priority(&mut self, stream: &mut store::Ptr, priority: ExtensiblePriority) {
+        let requeue =
+            stream.priority.urgency() != priority.urgency() && self.pending_send.remove(stream);
+
+        stream.priority = priority;
+
+        if requeue {
+            tracing::trace!(?stream.id, urgency = priority.urgency(), "requeue");
+            self.pending_send.push(stream);
+        }
+    }
+
+    /// Stops using the dependency tree, when the peer disabled RFC 7540
+    /// priorities.
+    pub fn disable_dependency_tree(&mut self, store: &mut Store) {
+        if let Some(mut tree) = self.pending_send.tree.take() {
+            while let Some(key) = tree.pop() {
+                let mut stream = store.resolve(key);
+                stream.is_pending_send = false;
+                self.pending_send.push(&mut stream);
+            }
+        }
+    }
+
+    /// Queue a control frame, that carries no data and is not subject to
+    /// flow control, to be sent to the remote
+    pub fn queue_control_frame(&mut self, frame: Frame, task: &mut Option<Waker>) {
+        debug_assert!(!matches!(frame, Frame::Data(_)));
+        self.pending_control.push_back(frame);
+
+        if let Some(task) = task.take() {
+            task.wake();
+        }
+    }
+
     pub(crate) fn max_buffer_size(&self) -> usize {
         self.max_buffer_size
     }
@@ -318,6 +424,11 @@ impl Prioritize {
         Ok(())
     }
 
+    /// Returns the connection-level send window
+    pub fn connection_window(&self) -> WindowSize {
+        self.flow.window_size()
+    }
+
     pub fn recv_connection_window_update(
         &mut self,
         inc: WindowSize,
@@ -532,6 +643,13 @@ impl Prioritize {
 
         tracing::trace!("poll_complete");
 
+        while let Some(frame) = self.pending_control.pop_front() {
+            tracing::trace!(?frame, "writing");
+            let frame = frame.map(|_| unreachable!("control frames carry no data"));
+            dst.buffer(frame).expect("invalid frame");
+            ready!(dst.poll_ready(cx))?;
+        }
+
         loop {
             if let Some(mut stream) = self.pop_pending_open(store, counts) {
                 self.pending_send.push_front(&mut stream);
@@ -817,6 +935,8 @@ impl Prioritize {
                                     (eos, len)
                                 });
 
+                            self.pending_send.charge(stream.id, len);
+
                             Frame::Data(frame.map(|buf| Prioritized {
                                 inner: buf.take(len),
                                 end_of_stream: eos,
@@ -943,3 +1063,328 @@ impl<B: Buf> fmt::Debug for Prioritized<B> {
             .finish()
     }
 }
+
+// ===== impl PendingSend =====
+
+impl PendingSend {
+    fn new(dependency_tree_scheduling: bool) -> Self {
+        PendingSend {
+            queues: [(); ExtensiblePriority::MAX_URGENCY as usize + 1].map(|_| store::Queue::new()),
+            tree: if dependency_tree_scheduling {
+                Some(DependencyTree::new())
+            } else {
+                None
+            },
+        }
+    }
+
+    fn queue(&mut self, stream: &store::Ptr) -> &mut store::Queue<stream::NextSend> {
+        &mut self.queues[stream.priority.urgency() as usize]
+    }
+
+    fn push(&mut self, stream: &mut store::Ptr) -> bool {
+        if let Some(ref mut tree) = self.tree {
+            // The tree is only used by servers, so client initiated streams
+            // are the remote initiated ones.
+            if stream.id.is_client_initiated() {
+                if stream.is_pending_send {
+                    return false;
+                }
+
+                stream.is_pending_send = true;
+                tree.push(stream.id, stream.key());
+                return true;
+            }
+        }
+
+        self.queue(stream).push(stream)
+    }
+
+    fn push_front(&mut self, stream: &mut store::Ptr) -> bool {
+        if self.tree.is_some() && stream.id.is_client_initiated() {
+            return self.push(stream);
+        }
+
+        self.queue(stream).push_front(stream)
+    }
+
+    /// Removes the stream from its urgency queue, returning whether it was
+    /// queued there. Streams scheduled by the dependency tree are left in it.
+    fn remove(&mut self, stream: &mut store::Ptr) -> bool {
+        if self.tree.is_some() && stream.id.is_client_initiated() {
+            return false;
+        }
+
+        self.queue(stream).remove(stream)
+    }
+
+    /// Pops the next stream of the dependency tree, or else of the most urgent
+    /// non-empty queue.
+    fn pop<'a, R>(&mut self, store: &'a mut R) -> Option<store::Ptr<'a>>
+    where
+        R: Resolve,
+    {
+        if let Some(key) = self.tree.as_mut().and_then(DependencyTree::pop) {
+            let mut stream = store.resolve(key);
+            stream.is_pending_send = false;
+            return Some(stream);
+        }
+
+        let queue = self.queues.iter_mut().find(|queue| !queue.is_empty())?;
+        queue.pop(store)
+    }
+
+    /// Accounts for `len` bytes of DATA sent on the stream.
+    fn charge(&mut self, id: StreamId, len: usize) {
+        if let Some(ref mut tree) = self.tree {
+            tree.charge(id, len);
+        }
+    }
+}
+
+// ===== impl DependencyTree =====
+
+/// The weight of streams without a stream dependency.
+const DEFAULT_WEIGHT: u32 = 16;
+
+/// The maximum number of nodes kept in the dependency tree.
+///
+/// Nodes of closed and idle streams are kept as long as possible, since
+/// clients may depend on them. Once the tree is full, the oldest node without
+/// queued streams is removed.
+const MAX_TREE_NODES: usize = 1024;
+
+/// Scales the pass increments, so small frames of heavy streams still count.
+const STRIDE: u64 = 1 << 8;
+
+impl DependencyTree {
+    fn new() -> Self {
+        let mut nodes = HashMap::new();
+        nodes.insert(StreamId::ZERO, Node::new(StreamId::ZERO, DEFAULT_WEIGHT, 0));
+        DependencyTree { nodes }
+    }
+
+    fn node(&self, id: StreamId) -> &Node {
+        self.nodes.get(&id).expect("node in dependency tree")
+    }
+
+    fn node_mut(&mut self, id: StreamId) -> &mut Node {
+        self.nodes.get_mut(&id).expect("node in dependency tree")
+    }
+
+    /// Inserts the stream as a dependent of the root, unless it already is in
+    /// the tree.
+    fn ensure(&mut self, id: StreamId, keep: StreamId) {
+        if self.nodes.contains_key(&id) {
+            return;
+        }
+
+        if self.nodes.len() > MAX_TREE_NODES {
+            self.evict(keep);
+        }
+
+        let pass = self.node(StreamId::ZERO).last_pass;
+        self.nodes
+            .insert(id, Node::new(StreamId::ZERO, DEFAULT_WEIGHT, pass));
+        self.node_mut(StreamId::ZERO).children.push(id);
+    }
+
+    /// Applies a stream dependency, see RFC 7540 section 5.3.3.
+    fn reprioritize(&mut self, id: StreamId, dependency: &StreamDependency) {
+        let mut parent = dependency.dependency_id();
+        let mut weight = dependency.weight() as u32 + 1;
+        let mut exclusive = dependency.is_exclusive();
+
+        if parent == id {
+            return;
+        }
+
+        // > A dependency on a stream that is not currently in the tree
+        // > [...] results in that stream being given a default priority.
+        if !self.nodes.contains_key(&parent) {
+            parent = StreamId::ZERO;
+            weight = DEFAULT_WEIGHT;
+            exclusive = false;
+        }
+
+        self.ensure(id, parent);
+
+        // > If a stream is made dependent on one of its own dependencies, the
+        // > formerly dependent stream is first moved to be dependent on the
+        // > reprioritized stream's previous parent.
+        if self.is_ancestor(id, parent) {
+            let previous_parent = self.node(id).parent;
+            self.move_node(parent, previous_parent, false);
+        }
+
+        self.move_node(id, parent, exclusive);
+        self.node_mut(id).weight = weight;
+    }
+
+    /// Returns true if `ancestor` is an ancestor of `id`.
+    fn is_ancestor(&self, ancestor: StreamId, mut id: StreamId) -> bool {
+        while !id.is_zero() {
+            id = self.node(id).parent;
+            if id == ancestor {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Moves a node, with its subtree, to depend on `parent`.
+    fn move_node(&mut self, id: StreamId, parent: StreamId, exclusive: bool) {
+        let previous_parent = self.node(id).parent;
+        let num_queued = self.node(id).num_queued;
+
+        self.node_mut(previous_parent).children.retain(|&c| c != id);
+        self.add_queued(previous_parent, -(num_queued as isize));
+
+        if exclusive {
+            let children = mem::take(&mut self.node_mut(parent).children);
+            for &child in &children {
+                let child_queued = self.node(child).num_queued;
+                self.node_mut(child).parent = id;
+                self.node_mut(id).num_queued += child_queued;
+            }
+            self.node_mut(id).children.extend(children);
+        }
+
+        let pass = self.node(parent).last_pass;
+        let node = self.node_mut(id);
+        node.parent = parent;
+        node.pass = pass;
+
+        self.node_mut(parent).children.push(id);
+        self.add_queued(parent, num_queued as isize);
+    }
+
+    /// Adds `delta` to the queued count of `id` and all of its ancestors.
+    fn add_queued(&mut self, mut id: StreamId, delta: isize) {
+        if delta == 0 {
+            return;
+        }
+
+        loop {
+            let node = self.node_mut(id);
+            node.num_queued = (node.num_queued as isize + delta) as usize;
+
+            if id.is_zero() {
+                return;
+            }
+            id = node.parent;
+        }
+    }
+
+    /// Removes the oldest node without queued streams, moving its children to
+    /// its parent as described in RFC 7540 section 5.3.4.
+    fn evict(&mut self, keep: StreamId) {
+        let id = self
+            .nodes
+            .iter()
+            .filter(|&(&id, node)| !id.is_zero() && id != keep && node.num_queued == 0)
+            .map(|(&id, _)| id)
+            .min();
+
+        let id = match id {
+            Some(id) => id,
+            None => return,
+        };
+
+        let node = self.nodes.remove(&id).unwrap();
+        let total: u32 = node
+            .children
+            .iter()
+            .map(|child| self.node(*child).weight)
+            .sum();
+
+        for &child in &node.children {
+            let child_node = self.node_mut(child);
+            child_node.parent = node.parent;
+            child_node.weight = cmp::max(1, node.weight * child_node.weight / total);
+        }
+
+        let parent = self.node_mut(node.parent);
+        parent.children.retain(|&c| c != id);
+        parent.children.extend(node.children);
+    }
+
+    /// Marks the stream as having frames queued.
+    fn push(&mut self, mut id: StreamId, key: store::Key) {
+        self.ensure(id, id);
+
+        debug_assert!(self.node(id).queued.is_none());
+        self.node_mut(id).queued = Some(key);
+
+        // Subtrees that were not scheduled don't keep their credit.
+        while !id.is_zero() {
+            let parent = self.node(id).parent;
+            let last_pass = self.node(parent).last_pass;
+            let node = self.node_mut(id);
+
+            node.num_queued += 1;
+            if node.num_queued == 1 {
+                node.pass = cmp::max(node.pass, last_pass);
+            }
+
+            id = parent;
+        }
+
+        self.node_mut(StreamId::ZERO).num_queued += 1;
+    }
+
+    /// Pops the next stream to send a frame.
+    fn pop(&mut self) -> Option<store::Key> {
+        let mut id = StreamId::ZERO;
+
+        if self.node(id).num_queued == 0 {
+            return None;
+        }
+
+        while id.is_zero() || self.node(id).queued.is_none() {
+            let next = self
+                .node(id)
+                .children
+                .iter()
+                .map(|&child| (child, self.node(child)))
+                .filter(|(_, node)| node.num_queued > 0)
+                .min_by_key(|&(child, node)| (node.pass, child))
+                .map(|(child, node)| (child, node.pass))
+                .expect("queued node in subtree");
+
+            self.node_mut(id).last_pass = next.1;
+            id = next.0;
+        }
+
+        let key = self.node_mut(id).queued.take();
+        self.add_queued(id, -1);
+        key
+    }
+
+    /// Charges the stream and its ancestors for `len` bytes of DATA.
+    fn charge(&mut self, mut id: StreamId, len: usize) {
+        if !self.nodes.contains_key(&id) {
+            return;
+        }
+
+        while !id.is_zero() {
+            let node = self.node_mut(id);
+            node.pass += len as u64 * STRIDE / node.weight as u64;
+            id = node.parent;
+        }
+    }
+}
+
+impl Node {
+    fn new(parent: StreamId, weight: u32, pass: u64) -> Self {
+        Node {
+            parent,
+            children: Vec::new(),
+            weight,
+            queued: None,
+            num_queued: 0,
+            pass,
+            last_pass: 0,
+        }
+    }
+}
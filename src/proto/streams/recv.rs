@@ -1,14 +1,16 @@
 use super::*;
 use crate::codec::UserError;
+use crate::ext::ExtensiblePriority;
 use crate::frame::{PushPromiseHeaderError, Reason, DEFAULT_INITIAL_WINDOW_SIZE};
 use crate::proto;
 
 use http::{HeaderMap, Request, Response};
 
 use std::cmp::Ordering;
-use std::io;
+use std::collections::VecDeque;
 use std::task::{Context, Poll, Waker};
 use std::time::Instant;
+use std::{io, mem};
 
 #[derive(Debug)]
 pub(super) struct Recv {
@@ -59,11 +61,32 @@ pub(super) struct Recv {
 
     /// If extended connect protocol is enabled.
     is_extended_connect_protocol_enabled: bool,
+
+    /// If the peer disabled RFC 7540 priorities (SETTINGS_NO_RFC7540_PRIORITIES).
+    is_rfc7540_priorities_disabled: bool,
+
+    /// PRIORITY_UPDATE frames received for streams that are still idle.
+    pending_priority_updates: VecDeque<(StreamId, ExtensiblePriority)>,
+
+    /// The `https` origins listed in ORIGIN frames, as lowercase host and
+    /// port pairs.
+    origin_set: Vec<(String, u16)>,
 }
 
+/// The maximum number of PRIORITY_UPDATE frames buffered for idle streams.
+const MAX_PENDING_PRIORITY_UPDATES: usize = 32;
+
+/// The maximum number of origins kept from ORIGIN frames.
+const MAX_ORIGIN_SET: usize = 256;
+
+/// The maximum number of informational responses buffered for a stream.
+const MAX_PENDING_INFORMATIONAL: usize = 16;
+
 #[derive(Debug)]
 pub(super) enum Event {
     Headers(peer::PollMessage),
+    /// An informational (1xx) response, received by a client.
+    Informational(Response<()>),
     Data(Bytes),
     Trailers(HeaderMap),
 }
@@ -107,6 +130,9 @@ impl Recv {
             refused: None,
             is_push_enabled: config.local_push_enabled,
             is_extended_connect_protocol_enabled: config.extended_connect_protocol_enabled,
+            is_rfc7540_priorities_disabled: false,
+            pending_priority_updates: VecDeque::new(),
+            origin_set: Vec::new(),
         }
     }
 
@@ -115,6 +141,20 @@ impl Recv {
         self.init_window_sz
     }
 
+    /// Returns the target connection-level receive window: the capacity
+    /// available plus the data reserved by streams.
+    pub fn target_connection_window(&self) -> WindowSize {
+        self.flow
+            .available()
+            .as_size()
+            .saturating_add(self.in_flight_data)
+    }
+
+    /// Returns the connection-level receive window
+    pub fn connection_window(&self) -> WindowSize {
+        self.flow.window_size()
+    }
+
     /// Returns the ID of the last processed stream
     pub fn last_processed_id(&self) -> StreamId {
         self.last_processed_id
@@ -152,6 +192,7 @@ impl Recv {
     /// Transition the stream state based on receiving headers
     ///
     /// The caller ensures that the frame represents headers and not trailers.
+    #[allow(clippy::result_large_err)]
     pub fn recv_headers(
         &mut self,
         frame: frame::Headers,
@@ -231,7 +272,8 @@ impl Recv {
         }
 
         let stream_id = frame.stream_id();
-        let (pseudo, fields) = frame.into_parts();
+        let stream_dependency = frame.stream_dependency().copied();
+        let (mut pseudo, fields) = frame.into_parts();
 
         if pseudo.protocol.is_some()
             && counts.peer().is_server()
@@ -247,10 +289,45 @@ impl Recv {
         }
 
         if !pseudo.is_informational() {
-            let message = counts
+            let pseudo_order = mem::take(&mut pseudo.order);
+
+            // A PRIORITY_UPDATE received while the stream was idle overrides
+            // the `priority` header field.
+            let priority = if counts.peer().is_server() {
+                self.take_pending_priority_update(stream_id).or_else(|| {
+                    fields
+                        .get("priority")
+                        .map(|value| ExtensiblePriority::parse(value.as_bytes()))
+                })
+            } else {
+                None
+            };
+
+            let mut message = counts
                 .peer()
                 .convert_poll_message(pseudo, fields, stream_id)?;
 
+            if let peer::PollMessage::Client(ref mut response) = message {
+                if let Some(altsvc) = stream.altsvc.take() {
+                    response.extensions_mut().insert(altsvc);
+                }
+            }
+
+            // Expose how the client ordered the pseudo-header fields and
+            // prioritized the stream.
+            if let peer::PollMessage::Server(ref mut request) = message {
+                request.extensions_mut().insert(pseudo_order);
+                if let Some(priority) = priority {
+                    stream.priority = priority;
+                    request.extensions_mut().insert(priority);
+                }
+                if !self.is_rfc7540_priorities_disabled {
+                    if let Some(stream_dependency) = stream_dependency {
+                        request.extensions_mut().insert(stream_dependency);
+                    }
+                }
+            }
+
             // Push the frame onto the stream's recv buffer
             stream
                 .pending_recv
@@ -264,6 +341,23 @@ impl Recv {
                 // corresponding headers frame pushed to `stream.pending_recv`.
                 self.pending_accept.push(stream);
             }
+        } else if stream.num_pending_informational < MAX_PENDING_INFORMATIONAL {
+            let message = counts
+                .peer()
+                .convert_poll_message(pseudo, fields, stream_id)?;
+
+            if let peer::PollMessage::Client(response) = message {
+                stream.num_pending_informational += 1;
+                stream
+                    .pending_recv
+                    .push_back(&mut self.buffer, Event::Informational(response));
+                stream.notify_recv();
+            }
+        } else {
+            tracing::debug!(
+                "too many pending informational responses, dropping; stream={:?}",
+                stream_id
+            );
         }
 
         Ok(())
@@ -324,6 +418,16 @@ impl Recv {
     ) -> Poll<Result<Response<()>, proto::Error>> {
         use super::peer::PollMessage::*;
 
+        // Informational responses not polled by the user are dropped.
+        while let Some(event) = stream.pending_recv.pop_front(&mut self.buffer) {
+            if let Event::Informational(_) = event {
+                stream.num_pending_informational -= 1;
+            } else {
+                stream.pending_recv.push_front(&mut self.buffer, event);
+                break;
+            }
+        }
+
         // If the buffer is not empty, then the first frame must be a HEADERS
         // frame or the user violated the contract.
         match stream.pending_recv.pop_front(&mut self.buffer) {
@@ -344,6 +448,34 @@ impl Recv {
         }
     }
 
+    /// Called by the client to get the informational responses received
+    /// before the final response.
+    pub fn poll_informational(
+        &mut self,
+        cx: &Context,
+        stream: &mut store::Ptr,
+    ) -> Poll<Option<Result<Response<()>, proto::Error>>> {
+        match stream.pending_recv.pop_front(&mut self.buffer) {
+            Some(Event::Informational(response)) => {
+                stream.num_pending_informational -= 1;
+                Poll::Ready(Some(Ok(response)))
+            }
+            Some(event) => {
+                // The final response was received.
+                stream.pending_recv.push_front(&mut self.buffer, event);
+                Poll::Ready(None)
+            }
+            None => {
+                if stream.state.ensure_recv_open()? && stream.state.is_recv_headers() {
+                    stream.recv_task = Some(cx.waker().clone());
+                    Poll::Pending
+                } else {
+                    Poll::Ready(None)
+                }
+            }
+        }
+    }
+
     /// Transition the stream based on receiving trailers
     pub fn recv_trailers(
         &mut self,
@@ -498,6 +630,92 @@ impl Recv {
         Ok(())
     }
 
+    /// Updates flow control for a connection WINDOW_UPDATE frame that was
+    /// sent without going through `send_connection_window_update`.
+    pub fn sent_connection_window_update(&mut self, increment: WindowSize) -> Result<(), Reason> {
+        self.flow.inc_window(increment)
+    }
+
+    pub(crate) fn apply_remote_settings(&mut self, settings: &frame::Settings) {
+        if let Some(val) = settings.is_no_rfc7540_priorities() {
+            self.is_rfc7540_priorities_disabled = val;
+        }
+    }
+
+    /// Buffers the priority of a remote initiated stream that is still idle,
+    /// until its HEADERS frame is received.
+    pub fn recv_idle_priority_update(&mut self, id: StreamId, priority: ExtensiblePriority) {
+        match self.next_stream_id {
+            Ok(next_id) if id >= next_id => {}
+            _ => return,
+        }
+
+        self.pending_priority_updates.retain(|&(i, _)| i != id);
+
+        if self.pending_priority_updates.len() == MAX_PENDING_PRIORITY_UPDATES {
+            self.pending_priority_updates.pop_front();
+        }
+
+        self.pending_priority_updates.push_back((id, priority));
+    }
+
+    fn take_pending_priority_update(&mut self, id: StreamId) -> Option<ExtensiblePriority> {
+        let pos = self
+            .pending_priority_updates
+            .iter()
+            .position(|&(i, _)| i == id)?;
+        self.pending_priority_updates
+            .remove(pos)
+            .map(|(_, priority)| priority)
+    }
+
+    /// Adds the origins of an ORIGIN frame to the origin set.
+    pub fn recv_origin(&mut self, frame: &frame::Origin) {
+        for origin in frame.origins() {
+            // Only `https` origins are coalesced.
+            //
+            // See: https://www.rfc-editor.org/rfc/rfc8336#section-2.3
+            let uri = match http::Uri::try_from(&origin[..]) {
+                Ok(uri) if uri.scheme_str() == Some("https") => uri,
+                _ => {
+                    tracing::debug!(?origin, "ignoring ORIGIN entry");
+                    continue;
+                }
+            };
+
+            let authority = match uri.authority() {
+                Some(authority) => authority,
+                None => continue,
+            };
+
+            let entry = (
+                authority.host().to_ascii_lowercase(),
+                authority.port_u16().unwrap_or(443),
+            );
+
+            if self.origin_set.contains(&entry) {
+                continue;
+            }
+
+            if self.origin_set.len() == MAX_ORIGIN_SET {
+                tracing::debug!("ignoring ORIGIN entries; origin set is full");
+                return;
+            }
+
+            self.origin_set.push(entry);
+        }
+    }
+
+    /// Returns true if an ORIGIN frame listed the `https` origin of
+    /// `authority`.
+    pub fn is_authoritative_for(&self, authority: &http::uri::Authority) -> bool {
+        let port = authority.port_u16().unwrap_or(443);
+
+        self.origin_set
+            .iter()
+            .any(|(host, p)| *p == port && host.eq_ignore_ascii_case(authority.host()))
+    }
+
     pub(crate) fn apply_local_settings(
         &mut self,
         settings: &frame::Settings,
@@ -628,6 +846,8 @@ impl Recv {
             return Err(Error::library_reset(stream.id, Reason::FLOW_CONTROL_ERROR));
         }
 
+        stream.data_bytes_recv += frame.payload().len() as u64;
+
         if stream.dec_content_length(frame.payload().len()).is_err() {
             proto_err!(stream:
                 "recv_data: content-length overflow; stream={:?}; len={:?}",
@@ -859,6 +1079,7 @@ impl Recv {
         while stream.pending_recv.pop_front(&mut self.buffer).is_some() {
             // drop it
         }
+        stream.num_pending_informational = 0;
     }
 
     /// Get the max ID of streams we can receive.
@@ -868,6 +1089,12 @@ impl Recv {
         self.max_stream_id
     }
 
+    /// Returns true if frames of the stream are ignored, because the remote
+    /// initiated it after the last stream ID of a GOAWAY we sent.
+    pub fn is_past_go_away(&self, peer: peer::Dyn, id: StreamId) -> bool {
+        id > self.max_stream_id && !peer.is_local_init(id)
+    }
+
     pub fn next_stream_id(&self) -> Result<StreamId, Error> {
         if let Ok(id) = self.next_stream_id {
             Ok(id)
@@ -3,6 +3,7 @@ use super::{
     StreamIdOverflow, WindowSize,
 };
 use crate::codec::UserError;
+use crate::ext::ExtensiblePriority;
 use crate::frame::{self, Priorities, Reason};
 use crate::proto::{self, Error, Initiator};
 
@@ -38,6 +39,10 @@ pub(super) struct Send {
 
     /// If extended connect protocol is enabled.
     is_extended_connect_protocol_enabled: bool,
+
+    /// Whether opening streams is refused because the connection is shutting
+    /// down gracefully.
+    is_shutting_down: bool,
 }
 
 /// A value to detect which public API has called `poll_reset`.
@@ -57,6 +62,7 @@ impl Send {
             prioritize: Prioritize::new(config),
             is_push_enabled: true,
             is_extended_connect_protocol_enabled: false,
+            is_shutting_down: false,
         }
     }
 
@@ -193,6 +199,29 @@ impl Send {
         Ok(())
     }
 
+    /// Send a HEADERS frame of an informational (1xx) response.
+    ///
+    /// Unlike `send_headers`, this does not change the state of the stream, as
+    /// the final response headers are still to be sent.
+    pub fn send_informational_headers<B>(
+        &mut self,
+        frame: frame::Headers,
+        buffer: &mut Buffer<Frame<B>>,
+        stream: &mut store::Ptr,
+        task: &mut Option<Waker>,
+    ) -> Result<(), UserError> {
+        Self::check_headers(frame.fields())?;
+
+        stream.state.ensure_can_send_informational()?;
+
+        tracing::trace!("send_informational_headers; frame={:?}", frame);
+
+        self.prioritize
+            .queue_frame(frame.into(), buffer, stream, task);
+
+        Ok(())
+    }
+
     /// Send an explicit RST_STREAM frame
     pub fn send_reset<B>(
         &mut self,
@@ -259,6 +288,29 @@ impl Send {
         self.prioritize.reclaim_all_capacity(stream, counts);
     }
 
+    pub fn recv_stream_dependency(&mut self, id: StreamId, dependency: &frame::StreamDependency) {
+        self.prioritize.recv_stream_dependency(id, dependency);
+    }
+
+    pub fn recv_priority_update(&mut self, stream: &mut store::Ptr, priority: ExtensiblePriority) {
+        self.prioritize.set_priority(stream, priority);
+    }
+
+    pub fn send_priority_update(&mut self, frame: frame::PriorityUpdate, task: &mut Option<Waker>) {
+        tracing::trace!(?frame, "send_priority_update");
+        self.prioritize.queue_control_frame(frame.into(), task);
+    }
+
+    pub fn send_altsvc(&mut self, frame: frame::AltSvc, task: &mut Option<Waker>) {
+        tracing::trace!(?frame, "send_altsvc");
+        self.prioritize.queue_control_frame(frame.into(), task);
+    }
+
+    pub fn send_origin(&mut self, frame: frame::Origin, task: &mut Option<Waker>) {
+        tracing::trace!(?frame, "send_origin");
+        self.prioritize.queue_control_frame(frame.into(), task);
+    }
+
     pub fn schedule_implicit_reset(
         &mut self,
         stream: &mut store::Ptr,
@@ -382,6 +434,11 @@ impl Send {
         }
     }
 
+    /// Returns the connection-level send window
+    pub fn connection_window(&self) -> WindowSize {
+        self.prioritize.connection_window()
+    }
+
     pub fn recv_connection_window_update(
         &mut self,
         frame: frame::WindowUpdate,
@@ -461,6 +518,10 @@ impl Send {
             self.is_extended_connect_protocol_enabled = val;
         }
 
+        if settings.is_no_rfc7540_priorities() == Some(true) {
+            self.prioritize.disable_dependency_tree(store);
+        }
+
         // Applies an update to the remote endpoint's initial window size.
         //
         // Per RFC 7540 §6.9.2:
@@ -590,6 +651,18 @@ impl Send {
         Ok(())
     }
 
+    pub fn shut_down(&mut self) {
+        self.is_shutting_down = true;
+    }
+
+    pub fn ensure_not_shutting_down(&self) -> Result<(), UserError> {
+        if self.is_shutting_down {
+            return Err(UserError::ShuttingDown);
+        }
+
+        Ok(())
+    }
+
     pub fn ensure_next_stream_id(&self) -> Result<StreamId, UserError> {
         self.next_stream_id
             .map_err(|_| UserError::OverflowedStreamId)
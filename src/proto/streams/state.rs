@@ -129,6 +129,19 @@ impl State {
         Ok(())
     }
 
+    /// Returns an error if an informational response cannot be sent, because
+    /// the final response headers have been sent already.
+    pub fn ensure_can_send_informational(&self) -> Result<(), UserError> {
+        match self.inner {
+            Open {
+                local: AwaitingHeaders,
+                ..
+            }
+            | HalfClosedRemote(AwaitingHeaders) => Ok(()),
+            _ => Err(UserError::UnexpectedFrameType),
+        }
+    }
+
     /// Opens the receive-half of the stream when a HEADERS frame is received.
     ///
     /// Returns true if this transitions the state to Open.
@@ -446,7 +459,7 @@ impl State {
     pub(super) fn ensure_reason(&self, mode: PollReset) -> Result<Option<Reason>, crate::Error> {
         match self.inner {
             Closed(Cause::Error(Error::Reset(_, reason, _)))
-            | Closed(Cause::Error(Error::GoAway(_, reason, _)))
+            | Closed(Cause::Error(Error::GoAway(_, reason, _, _)))
             | Closed(Cause::ScheduledLibraryReset(reason)) => Ok(Some(reason)),
             Closed(Cause::Error(ref e)) => Err(e.clone().into()),
             Open {
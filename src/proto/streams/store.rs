@@ -357,6 +357,48 @@ where
         None
     }
 
+    /// Removes the stream from the queue.
+    ///
+    /// If the stream is not contained by the list, return `false`.
+    pub fn remove(&mut self, stream: &mut store::Ptr) -> bool {
+        if !N::is_queued(stream) {
+            return false;
+        }
+
+        let mut idxs = self.indices.expect("queued stream in an empty queue");
+        let key = stream.key();
+
+        if idxs.head == key {
+            match N::take_next(stream) {
+                Some(next) => {
+                    idxs.head = next;
+                    self.indices = Some(idxs);
+                }
+                None => self.indices = None,
+            }
+        } else {
+            // Find the stream before it, the list is singly linked.
+            let mut prev = idxs.head;
+            loop {
+                match N::next(&stream.resolve(prev)) {
+                    Some(next) if next == key => break,
+                    Some(next) => prev = next,
+                    None => return false,
+                }
+            }
+
+            let next = N::take_next(stream);
+            N::set_next(&mut stream.resolve(prev), next);
+            if idxs.tail == key {
+                idxs.tail = prev;
+            }
+            self.indices = Some(idxs);
+        }
+
+        N::set_queued(stream, false);
+        true
+    }
+
     pub fn is_empty(&self) -> bool {
         self.indices.is_none()
     }
@@ -1,3 +1,4 @@
+use crate::ext::ExtensiblePriority;
 use crate::Reason;
 
 use super::*;
@@ -41,6 +42,9 @@ pub(super) struct Stream {
     /// Send data flow control
     pub send_flow: FlowControl,
 
+    /// Number of DATA payload bytes sent
+    pub data_bytes_sent: u64,
+
     /// Amount of send capacity that has been requested, but not yet allocated.
     pub requested_send_capacity: WindowSize,
 
@@ -85,6 +89,9 @@ pub(super) struct Stream {
 
     pub in_flight_recv_data: WindowSize,
 
+    /// Number of DATA payload bytes received
+    pub data_bytes_recv: u64,
+
     /// Next node in the linked list of streams waiting to send window updates.
     pub next_window_update: Option<store::Key>,
 
@@ -100,6 +107,9 @@ pub(super) struct Stream {
     /// Frames pending for this stream to read
     pub pending_recv: buffer::Deque,
 
+    /// Number of informational responses in `pending_recv`
+    pub num_pending_informational: usize,
+
     /// When the RecvStream drop occurs, no data should be received.
     pub is_recv: bool,
 
@@ -114,6 +124,12 @@ pub(super) struct Stream {
 
     /// Validate content-length headers
     pub content_length: ContentLength,
+
+    /// The RFC 9218 priority used to schedule the stream's frames
+    pub priority: ExtensiblePriority,
+
+    /// ALTSVC frame received before the response, added to its extensions
+    pub altsvc: Option<frame::AltSvc>,
 }
 
 /// State related to validating a stream's content-length
@@ -168,6 +184,7 @@ impl Stream {
             next_pending_send: None,
             is_pending_send: false,
             send_flow,
+            data_bytes_sent: 0,
             requested_send_capacity: 0,
             buffered_send_data: 0,
             send_task: None,
@@ -184,16 +201,20 @@ impl Stream {
             is_pending_accept: false,
             recv_flow,
             in_flight_recv_data: 0,
+            data_bytes_recv: 0,
             next_window_update: None,
             is_pending_window_update: false,
             reset_at: None,
             next_reset_expire: None,
             pending_recv: buffer::Deque::new(),
+            num_pending_informational: 0,
             is_recv: true,
             recv_task: None,
             push_task: None,
             pending_push_promises: store::Queue::new(),
             content_length: ContentLength::Omitted,
+            priority: ExtensiblePriority::default(),
+            altsvc: None,
         }
     }
 
@@ -307,6 +328,8 @@ impl Stream {
         let _res = self.send_flow.send_data(len);
         debug_assert!(_res.is_ok());
 
+        self.data_bytes_sent += u64::from(len);
+
         // Decrement the stream's buffered data counter
         debug_assert!(self.buffered_send_data >= len as usize);
         self.buffered_send_data -= len as usize;
@@ -341,11 +364,7 @@ impl Stream {
                 Some(val) => *rem = val,
                 None => return Err(()),
             },
-            ContentLength::Head => {
-                if len != 0 {
-                    return Err(());
-                }
-            }
+            ContentLength::Head if len != 0 => return Err(()),
             _ => {}
         }
 
@@ -401,6 +420,7 @@ impl fmt::Debug for Stream {
             .h2_field_some("next_pending_send", &self.next_pending_send)
             .h2_field_if("is_pending_send", &self.is_pending_send)
             .field("send_flow", &self.send_flow)
+            .field("data_bytes_sent", &self.data_bytes_sent)
             .field("requested_send_capacity", &self.requested_send_capacity)
             .field("buffered_send_data", &self.buffered_send_data)
             .h2_field_some("send_task", &self.send_task.as_ref().map(|_| ()))
@@ -422,6 +442,7 @@ impl fmt::Debug for Stream {
             .h2_field_if("is_pending_accept", &self.is_pending_accept)
             .field("recv_flow", &self.recv_flow)
             .field("in_flight_recv_data", &self.in_flight_recv_data)
+            .field("data_bytes_recv", &self.data_bytes_recv)
             .h2_field_some("next_window_update", &self.next_window_update)
             .h2_field_if("is_pending_window_update", &self.is_pending_window_update)
             .h2_field_some("reset_at", &self.reset_at)
@@ -431,6 +452,11 @@ impl fmt::Debug for Stream {
                 !self.pending_recv.is_empty(),
                 &self.pending_recv,
             )
+            .h2_field_if_then(
+                "num_pending_informational",
+                self.num_pending_informational > 0,
+                &self.num_pending_informational,
+            )
             .h2_field_if("is_recv", &self.is_recv)
             .h2_field_some("recv_task", &self.recv_task.as_ref().map(|_| ()))
             .h2_field_some("push_task", &self.push_task.as_ref().map(|_| ()))
@@ -440,6 +466,7 @@ impl fmt::Debug for Stream {
                 &self.pending_push_promises,
             )
             .field("content_length", &self.content_length)
+            .h2_field_some("altsvc", &self.altsvc)
             .finish()
     }
 }
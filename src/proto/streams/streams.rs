@@ -3,10 +3,10 @@ use super::recv::RecvHeaderBlockError;
 use super::store::{self, Entry, Resolve, Store};
 use super::{Buffer, Config, Counts, Prioritized, Recv, Send, Stream, StreamId};
 use crate::codec::{Codec, SendError, UserError};
-use crate::ext::Protocol;
+use crate::ext::{ExtensiblePriority, HeaderOrder, Protocol};
 use crate::frame::{self, Frame, Reason};
 use crate::proto::{peer, Error, Initiator, Open, Peer, WindowSize};
-use crate::{client, proto, server};
+use crate::{client, proto, server, SettingsSnapshot, StreamStats};
 
 use bytes::{Buf, Bytes};
 use http::{HeaderMap, Request, Response};
@@ -88,6 +88,12 @@ struct Inner {
 
     /// Priority of the headers stream
     priorities: Option<Priorities>,
+
+    /// The local SETTINGS acknowledged by the remote
+    local_settings: SettingsSnapshot,
+
+    /// The SETTINGS sent by the remote
+    remote_settings: SettingsSnapshot,
 }
 
 #[derive(Debug)]
@@ -137,6 +143,30 @@ where
             .set_target_connection_window(size, &mut me.actions.task)
     }
 
+    /// Returns the target connection-level receive window and the initial
+    /// stream-level receive window.
+    pub fn recv_window_targets(&self) -> (WindowSize, WindowSize) {
+        let me = self.inner.lock().unwrap();
+        (
+            me.actions.recv.target_connection_window(),
+            me.actions.recv.init_window_sz(),
+        )
+    }
+
+    pub fn sent_connection_window_update(&mut self, increment: WindowSize) -> Result<(), Reason> {
+        let mut me = self.inner.lock().unwrap();
+        me.actions.recv.sent_connection_window_update(increment)
+    }
+
+    /// Returns the connection-level send and receive windows.
+    pub fn connection_windows(&self) -> (WindowSize, WindowSize) {
+        let me = self.inner.lock().unwrap();
+        (
+            me.actions.send.connection_window(),
+            me.actions.recv.connection_window(),
+        )
+    }
+
     pub fn next_incoming(&mut self) -> Option<StreamRef<B>> {
         let mut me = self.inner.lock().unwrap();
         let me = &mut *me;
@@ -207,7 +237,9 @@ where
         let mut send_buffer = self.send_buffer.inner.lock().unwrap();
         let send_buffer = &mut *send_buffer;
 
+        me.remote_settings.merge(frame);
         me.counts.apply_remote_settings(frame, is_initial);
+        me.actions.recv.apply_remote_settings(frame);
 
         me.actions.send.apply_remote_settings(
             frame,
@@ -218,13 +250,45 @@ where
         )
     }
 
+    pub fn send_priority_update(
+        &self,
+        id: StreamId,
+        priority: &ExtensiblePriority,
+    ) -> Result<(), UserError> {
+        let mut me = self.inner.lock().unwrap();
+        me.send_priority_update(id, priority)
+    }
+
+    /// Queues a connection level ALTSVC frame.
+    pub fn send_altsvc(&self, origin: Bytes, field_value: Bytes) -> Result<(), UserError> {
+        let mut me = self.inner.lock().unwrap();
+        me.send_altsvc(frame::AltSvc::new(0, origin, field_value))
+    }
+
+    /// Queues an ORIGIN frame.
+    pub fn send_origin(&self, origins: Vec<Bytes>) -> Result<(), UserError> {
+        let mut me = self.inner.lock().unwrap();
+        me.send_origin(frame::Origin::new(origins))
+    }
+
     pub fn apply_local_settings(&mut self, frame: &frame::Settings) -> Result<(), Error> {
         let mut me = self.inner.lock().unwrap();
         let me = &mut *me;
 
+        me.local_settings.merge(frame);
         me.actions.recv.apply_local_settings(frame, &mut me.store)
     }
 
+    /// Returns the local SETTINGS acknowledged by the remote.
+    pub fn local_settings(&self) -> SettingsSnapshot {
+        self.inner.lock().unwrap().local_settings.clone()
+    }
+
+    /// Returns the SETTINGS sent by the remote.
+    pub fn remote_settings(&self) -> SettingsSnapshot {
+        self.inner.lock().unwrap().remote_settings.clone()
+    }
+
     pub fn send_request(
         &mut self,
         mut request: Request<()>,
@@ -235,6 +299,18 @@ where
         use http::Method;
 
         let protocol = request.extensions_mut().remove::<Protocol>();
+        let pseudo_order = request.extensions_mut().remove::<PseudoOrder>();
+        let stream_dependency = request.extensions_mut().remove::<StreamDependency>();
+        let header_order = request.extensions_mut().remove::<HeaderOrder>();
+        let priority = request.extensions_mut().remove::<ExtensiblePriority>();
+
+        if let Some(priority) = priority {
+            if !request.headers().contains_key("priority") {
+                request
+                    .headers_mut()
+                    .insert("priority", priority.to_header_value());
+            }
+        }
 
         // Clear before taking lock, incase extensions contain a StreamRef.
         request.extensions_mut().clear();
@@ -251,6 +327,7 @@ where
         let send_buffer = &mut *send_buffer;
 
         me.actions.ensure_no_conn_error()?;
+        me.actions.send.ensure_not_shutting_down()?;
         me.actions.send.ensure_next_stream_id()?;
 
         // The `pending` argument is provided by the `Client`, and holds
@@ -300,8 +377,9 @@ where
             request,
             protocol,
             end_of_stream,
-            me.headers_pseudo_order.clone(),
-            me.headers_stream_dependency,
+            pseudo_order.or_else(|| me.headers_pseudo_order.clone()),
+            stream_dependency.or(me.headers_stream_dependency),
+            header_order,
         )?;
 
         let mut stream = me.store.insert(stream.id, stream);
@@ -350,6 +428,15 @@ where
             .is_extended_connect_protocol_enabled()
     }
 
+    pub(crate) fn is_authoritative_for(&self, authority: &http::uri::Authority) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .actions
+            .recv
+            .is_authoritative_for(authority)
+    }
+
     pub fn current_max_send_streams(&self) -> usize {
         let me = self.inner.lock().unwrap();
         me.counts.max_send_streams()
@@ -412,6 +499,26 @@ impl<B> DynStreams<'_, B> {
         me.recv_push_promise(self.send_buffer, frame)
     }
 
+    pub fn recv_priority(&mut self, frame: &frame::Priority) {
+        let mut me = self.inner.lock().unwrap();
+        me.recv_priority(frame)
+    }
+
+    pub fn recv_priority_update(&mut self, frame: frame::PriorityUpdate) -> Result<(), Error> {
+        let mut me = self.inner.lock().unwrap();
+        me.recv_priority_update(frame)
+    }
+
+    pub fn recv_altsvc(&mut self, frame: frame::AltSvc) {
+        let mut me = self.inner.lock().unwrap();
+        me.recv_altsvc(frame)
+    }
+
+    pub fn recv_origin(&mut self, frame: &frame::Origin) {
+        let mut me = self.inner.lock().unwrap();
+        me.actions.recv.recv_origin(frame)
+    }
+
     pub fn recv_eof(&mut self, clear_pending_accept: bool) -> Result<(), ()> {
         let mut me = self.inner.lock().map_err(|_| ())?;
         me.recv_eof(self.send_buffer, clear_pending_accept)
@@ -447,6 +554,8 @@ impl Inner {
             headers_stream_dependency: config.headers_stream_dependency,
             headers_pseudo_order: config.headers_pseudo_order,
             priorities: config.priorities,
+            local_settings: SettingsSnapshot::default(),
+            remote_settings: SettingsSnapshot::default(),
         }))
     }
 
@@ -460,7 +569,7 @@ impl Inner {
 
         // The GOAWAY process has begun. All streams with a greater ID than
         // specified as part of GOAWAY should be ignored.
-        if id > self.actions.recv.max_stream_id() {
+        if self.actions.recv.is_past_go_away(self.counts.peer(), id) {
             tracing::trace!(
                 "id ({:?}) > max_stream_id ({:?}), ignoring HEADERS",
                 id,
@@ -518,6 +627,12 @@ impl Inner {
             return Ok(());
         }
 
+        if peer.is_server() {
+            if let Some(dependency) = frame.stream_dependency() {
+                self.actions.send.recv_stream_dependency(id, dependency);
+            }
+        }
+
         let actions = &mut self.actions;
         let mut send_buffer = send_buffer.inner.lock().unwrap();
         let send_buffer = &mut *send_buffer;
@@ -581,7 +696,7 @@ impl Inner {
             None => {
                 // The GOAWAY process has begun. All streams with a greater ID
                 // than specified as part of GOAWAY should be ignored.
-                if id > self.actions.recv.max_stream_id() {
+                if self.actions.recv.is_past_go_away(self.counts.peer(), id) {
                     tracing::trace!(
                         "id ({:?}) > max_stream_id ({:?}), ignoring DATA",
                         id,
@@ -642,7 +757,7 @@ impl Inner {
 
         // The GOAWAY process has begun. All streams with a greater ID than
         // specified as part of GOAWAY should be ignored.
-        if id > self.actions.recv.max_stream_id() {
+        if self.actions.recv.is_past_go_away(self.counts.peer(), id) {
             tracing::trace!(
                 "id ({:?}) > max_stream_id ({:?}), ignoring RST_STREAM",
                 id,
@@ -723,6 +838,112 @@ impl Inner {
         Ok(())
     }
 
+    fn recv_priority(&mut self, frame: &frame::Priority) {
+        // PRIORITY frames may refer to streams in any state, including idle
+        // streams that are only used to group other streams.
+        if self.counts.peer().is_server() && !frame.stream_id().is_zero() {
+            self.actions
+                .send
+                .recv_stream_dependency(frame.stream_id(), &frame.dependency());
+        }
+    }
+
+    fn recv_priority_update(&mut self, frame: frame::PriorityUpdate) -> Result<(), Error> {
+        let id = frame.prioritized_stream_id();
+
+        // Only clients send PRIORITY_UPDATE frames, and only for request
+        // streams.
+        //
+        // See: https://www.rfc-editor.org/rfc/rfc9218#section-7.1
+        if !self.counts.peer().is_server() || self.counts.peer().is_local_init(id) {
+            proto_err!(conn: "recv_priority_update: unexpected PRIORITY_UPDATE; id={:?}", id);
+            return Err(Error::library_go_away(Reason::PROTOCOL_ERROR));
+        }
+
+        let priority = ExtensiblePriority::parse(frame.field_value());
+
+        if let Some(mut stream) = self.store.find_mut(&id) {
+            self.actions
+                .send
+                .recv_priority_update(&mut stream, priority);
+        } else {
+            self.actions.recv.recv_idle_priority_update(id, priority);
+        }
+
+        Ok(())
+    }
+
+    fn send_priority_update(
+        &mut self,
+        id: StreamId,
+        priority: &ExtensiblePriority,
+    ) -> Result<(), UserError> {
+        if self.counts.peer().is_server() {
+            return Err(UserError::UnexpectedFrameType);
+        }
+
+        if id.is_zero() || id.is_server_initiated() {
+            return Err(UserError::InactiveStreamId);
+        }
+
+        let frame = frame::PriorityUpdate::new(id, priority.to_string().into());
+
+        self.actions
+            .send
+            .send_priority_update(frame, &mut self.actions.task);
+
+        Ok(())
+    }
+
+    fn recv_altsvc(&mut self, frame: frame::AltSvc) {
+        let id = StreamId::from(frame.stream_id().as_u32());
+
+        // Stream level ALTSVC frames are only exposed with the response.
+        match self.store.find_mut(&id) {
+            Some(mut stream) if stream.state.is_recv_headers() => {
+                stream.altsvc = Some(frame);
+            }
+            _ => {
+                tracing::debug!(?id, "ignoring ALTSVC frame; no response pending");
+            }
+        }
+    }
+
+    fn send_altsvc(&mut self, frame: frame::AltSvc) -> Result<(), UserError> {
+        // Only servers advertise alternative services.
+        //
+        // See: https://www.rfc-editor.org/rfc/rfc7838#section-4
+        if !self.counts.peer().is_server() {
+            return Err(UserError::UnexpectedFrameType);
+        }
+
+        // Any peer accepts frames up to the default max frame size.
+        if frame.payload_len() > frame::DEFAULT_MAX_FRAME_SIZE as usize {
+            return Err(UserError::PayloadTooBig);
+        }
+
+        self.actions.send.send_altsvc(frame, &mut self.actions.task);
+
+        Ok(())
+    }
+
+    fn send_origin(&mut self, frame: frame::Origin) -> Result<(), UserError> {
+        // Only servers send ORIGIN frames.
+        //
+        // See: https://www.rfc-editor.org/rfc/rfc8336#section-2
+        if !self.counts.peer().is_server() {
+            return Err(UserError::UnexpectedFrameType);
+        }
+
+        if frame.payload_len() > frame::DEFAULT_MAX_FRAME_SIZE as usize {
+            return Err(UserError::PayloadTooBig);
+        }
+
+        self.actions.send.send_origin(frame, &mut self.actions.task);
+
+        Ok(())
+    }
+
     fn handle_error<B>(&mut self, send_buffer: &SendBuffer<B>, err: proto::Error) -> StreamId {
         let actions = &mut self.actions;
         let counts = &mut self.counts;
@@ -757,7 +978,11 @@ impl Inner {
 
         actions.send.recv_go_away(last_stream_id)?;
 
-        let err = Error::remote_go_away(frame.debug_data().clone(), frame.reason());
+        let err = Error::remote_go_away_refused(
+            frame.debug_data().clone(),
+            frame.reason(),
+            last_stream_id,
+        );
 
         self.store.for_each(|stream| {
             if stream.id > last_stream_id {
@@ -786,10 +1011,14 @@ impl Inner {
             Some(stream) => {
                 // The GOAWAY process has begun. All streams with a greater ID
                 // than specified as part of GOAWAY should be ignored.
-                if id > self.actions.recv.max_stream_id() {
+                if self
+                    .actions
+                    .recv
+                    .is_past_go_away(self.counts.peer(), promised_id)
+                {
                     tracing::trace!(
-                        "id ({:?}) > max_stream_id ({:?}), ignoring PUSH_PROMISE",
-                        id,
+                        "promised_id ({:?}) > max_stream_id ({:?}), ignoring PUSH_PROMISE",
+                        promised_id,
                         self.actions.recv.max_stream_id()
                     );
                     return Ok(());
@@ -1023,6 +1252,51 @@ where
         }
         Poll::Ready(Ok(()))
     }
+
+    /// Refuses to open new streams, for a graceful shutdown.
+    pub fn refuse_new_streams(&mut self) {
+        let mut me = self.inner.lock().unwrap();
+        me.actions.send.shut_down();
+    }
+
+    /// Opens the stream of the request sent over HTTP/1.1 before the
+    /// connection was upgraded. The stream is half-closed (local) and no
+    /// HEADERS frame is sent for it.
+    pub fn open_upgraded(&mut self, is_head: bool) -> Result<StreamRef<B>, UserError> {
+        use super::stream::ContentLength;
+
+        let mut me = self.inner.lock().unwrap();
+        let me = &mut *me;
+
+        let stream_id = me.actions.send.open()?;
+        debug_assert_eq!(stream_id, StreamId::from(1));
+
+        let mut stream = Stream::new(
+            stream_id,
+            me.actions.send.init_window_sz(),
+            me.actions.recv.init_window_sz(),
+        );
+
+        if is_head {
+            stream.content_length = ContentLength::Head;
+        }
+
+        let mut stream = me.store.insert(stream.id, stream);
+        stream.state.send_open(true)?;
+
+        // The stream was opened before the server's SETTINGS limited the
+        // number of concurrent streams, so it is only counted if it fits.
+        if me.counts.can_inc_num_send_streams() {
+            me.counts.inc_num_send_streams(&mut stream);
+        }
+
+        me.refs += 1;
+
+        Ok(StreamRef {
+            opaque: OpaqueStreamRef::new(self.inner.clone(), &mut stream),
+            send_buffer: self.send_buffer.clone(),
+        })
+    }
 }
 
 impl<B, P> Streams<B, P>
@@ -1138,6 +1412,27 @@ impl<B> StreamRef<B> {
         })
     }
 
+    pub fn send_priority_update(&mut self, priority: &ExtensiblePriority) -> Result<(), UserError> {
+        let mut me = self.opaque.inner.lock().unwrap();
+        let me = &mut *me;
+
+        let id = me.store.resolve(self.opaque.key).id;
+        me.send_priority_update(id, priority)
+    }
+
+    pub fn send_altsvc(&mut self, field_value: Bytes) -> Result<(), UserError> {
+        let mut me = self.opaque.inner.lock().unwrap();
+        let me = &mut *me;
+
+        let stream = me.store.resolve(self.opaque.key);
+        if stream.state.is_closed() {
+            return Err(UserError::InactiveStreamId);
+        }
+
+        let frame = frame::AltSvc::new(stream.id.into(), Bytes::new(), field_value);
+        me.send_altsvc(frame)
+    }
+
     pub fn send_trailers(&mut self, trailers: HeaderMap) -> Result<(), UserError> {
         let mut me = self.opaque.inner.lock().unwrap();
         let me = &mut *me;
@@ -1206,6 +1501,29 @@ impl<B> StreamRef<B> {
         })
     }
 
+    pub fn send_informational(&mut self, mut response: Response<()>) -> Result<(), UserError> {
+        let status = response.status();
+        if !status.is_informational() || status == http::StatusCode::SWITCHING_PROTOCOLS {
+            return Err(UserError::InvalidInformationalStatus);
+        }
+
+        // Clear before taking lock, incase extensions contain a StreamRef.
+        response.extensions_mut().clear();
+        let mut me = self.opaque.inner.lock().unwrap();
+        let me = &mut *me;
+
+        let mut stream = me.store.resolve(self.opaque.key);
+        let actions = &mut me.actions;
+        let mut send_buffer = self.send_buffer.inner.lock().unwrap();
+        let send_buffer = &mut *send_buffer;
+
+        let frame = server::Peer::convert_send_message(stream.id, response, false);
+
+        actions
+            .send
+            .send_informational_headers(frame, send_buffer, &mut stream, &mut actions.task)
+    }
+
     pub fn send_push_promise(
         &mut self,
         mut request: Request<()>,
@@ -1333,6 +1651,10 @@ impl<B> StreamRef<B> {
         self.opaque.clone()
     }
 
+    pub fn stats(&self) -> StreamStats {
+        self.opaque.stats()
+    }
+
     pub fn stream_id(&self) -> StreamId {
         self.opaque.stream_id()
     }
@@ -1366,6 +1688,18 @@ impl OpaqueStreamRef {
 
         me.actions.recv.poll_response(cx, &mut stream)
     }
+    /// Called by a client to check for a received informational response.
+    pub fn poll_informational(
+        &mut self,
+        cx: &Context,
+    ) -> Poll<Option<Result<Response<()>, proto::Error>>> {
+        let mut me = self.inner.lock().unwrap();
+        let me = &mut *me;
+
+        let mut stream = me.store.resolve(self.key);
+
+        me.actions.recv.poll_informational(cx, &mut stream)
+    }
     /// Called by a client to check for a pushed request.
     pub fn poll_pushed(
         &mut self,
@@ -1413,6 +1747,19 @@ impl OpaqueStreamRef {
         me.actions.recv.poll_trailers(cx, &mut stream)
     }
 
+    pub fn stats(&self) -> StreamStats {
+        let me = self.inner.lock().unwrap();
+        let me = &*me;
+
+        let stream = &me.store[self.key];
+        StreamStats::new(
+            stream.data_bytes_sent,
+            stream.data_bytes_recv,
+            stream.send_flow.window_size(),
+            stream.recv_flow.window_size(),
+        )
+    }
+
     pub(crate) fn available_recv_capacity(&self) -> isize {
         let me = self.inner.lock().unwrap();
         let me = &*me;
@@ -116,14 +116,19 @@
 //! [`TcpListener`]: https://docs.rs/tokio-core/0.1/tokio_core/net/struct.TcpListener.html
 
 use crate::codec::{Codec, UserError};
+use crate::ext::HeaderOrder;
 use crate::frame::{self, Pseudo, PushPromiseHeaderError, Reason, Settings, StreamId};
 use crate::proto::{self, Config, Error, Prioritized};
-use crate::{FlowControl, PingPong, RecvStream, SendStream};
+use crate::{
+    upgrade, ConnectionStats, EncodingPolicy, ExtensionFrame, Fingerprint, FlowControl,
+    FrameObserver, PingPong, RecvStream, SendStream, SettingsSnapshot, Timer, TokioTimer,
+};
 
 use bytes::{Buf, Bytes};
-use http::{HeaderMap, Method, Request, Response};
+use http::{HeaderMap, HeaderValue, Method, Request, Response};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 use std::{fmt, io};
@@ -152,6 +157,23 @@ pub struct Handshake<T, B: Buf = Bytes> {
     state: Handshaking<T, B>,
     /// Span tracking the handshake
     span: tracing::Span,
+    /// The request upgraded from HTTP/1.1, opening stream 1.
+    upgrade: Option<Upgrade>,
+}
+
+/// The `HTTP2-Settings` and request of an HTTP/1.1 upgrade.
+struct Upgrade {
+    settings: Settings,
+    request: frame::Headers,
+}
+
+/// The client settings of an HTTP/1.1 upgrade to HTTP/2.
+///
+/// Returned by [`decode_http2_settings`] and passed to
+/// [`Builder::handshake_upgraded`].
+#[derive(Clone, Debug)]
+pub struct UpgradeSettings {
+    settings: Settings,
 }
 
 /// Accepts inbound HTTP/2 streams on a connection.
@@ -237,6 +259,21 @@ pub struct Builder {
     /// Time to keep locally reset streams around before reaping.
     reset_stream_duration: Duration,
 
+    /// Time to wait for the remote to acknowledge our SETTINGS.
+    settings_ack_timeout: Option<Duration>,
+
+    /// Time without receiving a frame before a keep-alive PING is sent.
+    keep_alive_interval: Option<Duration>,
+
+    /// Time to wait for the acknowledgement of a keep-alive PING.
+    keep_alive_timeout: Duration,
+
+    /// Whether keep-alive PINGs are sent while there are no open streams.
+    keep_alive_while_idle: bool,
+
+    /// Timer used for timeouts and keep-alive intervals.
+    timer: Arc<dyn Timer>,
+
     /// Maximum number of locally reset streams to keep at a time.
     reset_stream_max: usize,
 
@@ -250,6 +287,9 @@ pub struct Builder {
     /// Initial target window size for new connections.
     initial_target_connection_window_size: Option<u32>,
 
+    /// Maximum window size when the windows adapt to the connection.
+    adaptive_window: Option<u32>,
+
     /// Maximum amount of bytes to "buffer" for writing per stream.
     max_send_buffer_size: usize,
 
@@ -258,6 +298,21 @@ pub struct Builder {
     ///
     /// When this gets exceeded, we issue GOAWAYs.
     local_max_error_reset_streams: Option<usize>,
+
+    /// Policy deciding how header fields are HPACK encoded.
+    encoding_policy: Option<Arc<dyn EncodingPolicy>>,
+
+    /// Whether response frames are scheduled by the RFC 7540 dependency tree.
+    dependency_tree_scheduling: bool,
+
+    /// Whether the fingerprint of the client is recorded.
+    record_peer_fingerprint: bool,
+
+    /// Extension frame types delivered to the application.
+    extension_frame_types: Vec<u8>,
+
+    /// Called with each frame sent and received.
+    frame_observer: Option<Arc<dyn FrameObserver>>,
 }
 
 /// Send a response back to the client
@@ -369,6 +424,48 @@ where
     Builder::new().handshake(io)
 }
 
+/// Decodes the value of the `HTTP2-Settings` header of an HTTP/1.1 request
+/// with `Upgrade: h2c`.
+///
+/// As required by [RFC 7540 §3.2.1], a server must not upgrade the
+/// connection if the header is not a valid base64url encoded SETTINGS
+/// payload, so this is called before writing the `101 Switching Protocols`
+/// response. The error has the reason `PROTOCOL_ERROR`.
+///
+/// [RFC 7540 §3.2.1]: https://datatracker.ietf.org/doc/html/rfc7540#section-3.2.1
+///
+/// # Examples
+///
+/// ```
+/// # use h2::server;
+/// # use http::HeaderValue;
+/// let settings = server::decode_http2_settings(&HeaderValue::from_static("AAMAAABk"))
+///     .unwrap();
+/// assert_eq!(settings.snapshot().max_concurrent_streams(), Some(100));
+///
+/// assert!(server::decode_http2_settings(&HeaderValue::from_static("AAMA!")).is_err());
+/// ```
+pub fn decode_http2_settings(value: &HeaderValue) -> Result<UpgradeSettings, crate::Error> {
+    match upgrade::decode_settings(value) {
+        Some(settings) => Ok(UpgradeSettings { settings }),
+        None => {
+            tracing::debug!("invalid HTTP2-Settings header; val={:?}", value);
+            Err(Error::library_go_away(Reason::PROTOCOL_ERROR).into())
+        }
+    }
+}
+
+// ===== impl UpgradeSettings =====
+
+impl UpgradeSettings {
+    /// Returns the decoded parameters.
+    pub fn snapshot(&self) -> SettingsSnapshot {
+        let mut snapshot = SettingsSnapshot::default();
+        snapshot.merge(&self.settings);
+        snapshot
+    }
+}
+
 // ===== impl Connection =====
 
 impl<T, B> Connection<T, B>
@@ -382,6 +479,9 @@ where
 
         // Create the codec.
         let mut codec = Codec::new(io);
+        if builder.record_peer_fingerprint {
+            codec.record_peer_fingerprint();
+        }
 
         if let Some(max) = builder.settings.max_frame_size() {
             codec.set_max_recv_frame_size(max as usize);
@@ -391,6 +491,16 @@ where
             codec.set_max_recv_header_list_size(max as usize);
         }
 
+        if let Some(policy) = builder.encoding_policy.clone() {
+            codec.set_encoding_policy(policy);
+        }
+
+        codec.set_extension_frame_types(builder.extension_frame_types.clone());
+
+        if let Some(observer) = builder.frame_observer.clone() {
+            codec.set_frame_observer(observer);
+        }
+
         // Send initial settings frame.
         codec
             .buffer(builder.settings.clone().into())
@@ -406,6 +516,7 @@ where
             builder,
             state,
             span,
+            upgrade: None,
         }
     }
 
@@ -527,7 +638,15 @@ where
     ///
     /// For graceful shutdowns, see [`graceful_shutdown`](Connection::graceful_shutdown).
     pub fn abrupt_shutdown(&mut self, reason: Reason) {
-        self.connection.go_away_from_user(reason);
+        self.connection.go_away_from_user(reason, Bytes::new());
+    }
+
+    /// Sets the connection to a GOAWAY state, with debug data in the GOAWAY
+    /// frame.
+    ///
+    /// See [`abrupt_shutdown`](Connection::abrupt_shutdown).
+    pub fn abrupt_shutdown_with_debug_data(&mut self, reason: Reason, debug_data: Bytes) {
+        self.connection.go_away_from_user(reason, debug_data);
     }
 
     /// Starts a [graceful shutdown][1] process.
@@ -553,6 +672,141 @@ where
         self.connection.take_user_pings().map(PingPong::new)
     }
 
+    /// Returns a snapshot of the statistics of the connection.
+    ///
+    /// This includes the frames and bytes sent and received by frame type,
+    /// the RST_STREAM and GOAWAY frames by reason, the HPACK compression of
+    /// the header blocks, the connection-level flow control windows and the
+    /// round trip time of the last acknowledged PING.
+    pub fn stats(&self) -> ConnectionStats {
+        self.connection.stats()
+    }
+
+    /// Returns the SETTINGS sent by the client.
+    ///
+    /// Every SETTINGS frame received so far is merged into the returned
+    /// snapshot, including parameters with an id unknown to h2.
+    pub fn remote_settings(&self) -> SettingsSnapshot {
+        self.connection.remote_settings()
+    }
+
+    /// Returns the local SETTINGS acknowledged by the client.
+    ///
+    /// Local SETTINGS are only included once the client acknowledged them.
+    pub fn local_settings(&self) -> SettingsSnapshot {
+        self.connection.local_settings()
+    }
+
+    /// Polls until the client sends new SETTINGS.
+    ///
+    /// Returns the SETTINGS sent by the client, as [`remote_settings`] does,
+    /// once a SETTINGS frame was received since this method last returned
+    /// them. SETTINGS are received while the connection is polled, which this
+    /// method does not do.
+    ///
+    /// Returns `None` once the connection is closed.
+    ///
+    /// [`remote_settings`]: #method.remote_settings
+    pub fn poll_remote_settings(&mut self, cx: &mut Context) -> Poll<Option<SettingsSnapshot>> {
+        self.connection.poll_remote_settings(cx)
+    }
+
+    /// Sends an ORIGIN frame listing origins the server is authoritative for.
+    ///
+    /// Each origin is a serialized origin, such as `https://example.com`.
+    /// Clients add the origins to the set of origins they may send requests
+    /// for on this connection. The frame is sent the next time the connection
+    /// is polled.
+    ///
+    /// See [RFC 8336] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an origin is not a serialized origin, or if the
+    /// origins do not fit in a single frame.
+    ///
+    /// [RFC 8336]: https://www.rfc-editor.org/rfc/rfc8336#section-2
+    pub fn send_origin(&mut self, origins: &[&str]) -> Result<(), crate::Error> {
+        if !origins.iter().all(|origin| frame::is_valid_origin(origin)) {
+            return Err(UserError::InvalidOrigin.into());
+        }
+
+        let origins = origins
+            .iter()
+            .map(|origin| Bytes::copy_from_slice(origin.as_bytes()))
+            .collect();
+
+        self.connection.send_origin(origins)?;
+        Ok(())
+    }
+
+    /// Advertises alternative services for `origin`.
+    ///
+    /// `origin` is a serialized origin, such as `https://example.com`, and
+    /// `field_value` has the format of the `Alt-Svc` header field, for example
+    /// `h3=":443"; ma=3600`. The ALTSVC frame is sent on stream 0 the next
+    /// time the connection is polled.
+    ///
+    /// See [RFC 7838] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `origin` is not a serialized origin, or if `origin`
+    /// and `field_value` do not fit in a single frame.
+    ///
+    /// [RFC 7838]: https://www.rfc-editor.org/rfc/rfc7838#section-4
+    pub fn send_altsvc(
+        &mut self,
+        origin: &str,
+        field_value: &HeaderValue,
+    ) -> Result<(), crate::Error> {
+        if !frame::is_valid_origin(origin) {
+            return Err(UserError::InvalidOrigin.into());
+        }
+
+        self.connection.send_altsvc(
+            Bytes::copy_from_slice(origin.as_bytes()),
+            Bytes::copy_from_slice(field_value.as_bytes()),
+        )?;
+        Ok(())
+    }
+
+    /// Polls for the next extension frame received from the client.
+    ///
+    /// Only frames of the types registered with
+    /// [`Builder::extension_frame_type`] are returned. Frames are read while
+    /// the connection is polled with [`Connection::accept`] or
+    /// [`Connection::poll_closed`], which this method does not do.
+    ///
+    /// Returns `None` once the connection is closed.
+    ///
+    /// [`Builder::extension_frame_type`]: struct.Builder.html#method.extension_frame_type
+    /// [`Connection::accept`]: struct.Connection.html#method.accept
+    /// [`Connection::poll_closed`]: struct.Connection.html#method.poll_closed
+    pub fn poll_extension_frame(&mut self, cx: &mut Context) -> Poll<Option<ExtensionFrame>> {
+        self.connection.poll_extension_frame(cx)
+    }
+
+    /// Sends an extension frame to the client.
+    ///
+    /// The frame is written the next time the connection is polled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `kind` is a frame type known to h2, or if `payload`
+    /// is larger than the maximum frame size of the client.
+    pub fn send_extension_frame(
+        &mut self,
+        kind: u8,
+        flags: u8,
+        stream_id: u32,
+        payload: Bytes,
+    ) -> Result<(), crate::Error> {
+        self.connection
+            .send_extension_frame(kind, flags, stream_id, payload)?;
+        Ok(())
+    }
+
     /// Checks if there are any streams
     pub fn has_streams(&self) -> bool {
         self.connection.has_streams()
@@ -586,6 +840,24 @@ where
         self.connection.max_recv_streams()
     }
 
+    /// Returns the HTTP/2 fingerprint of the client.
+    ///
+    /// The fingerprint is made of the SETTINGS parameters of the client
+    /// preface in wire order, including unknown ones, the increment of its
+    /// first connection level WINDOW_UPDATE, the PRIORITY frames it sent
+    /// before its first request, and the pseudo-header order of that request.
+    ///
+    /// Returns `None` unless recording was enabled with
+    /// [`Builder::record_peer_fingerprint`], and until the HEADERS frame of
+    /// the first request has been received.
+    ///
+    /// The pseudo-header order and stream dependency of every request are
+    /// also available in the extensions of the received `Request`, as
+    /// `PseudoOrder` and `StreamDependency` values.
+    pub fn peer_fingerprint(&self) -> Option<&Fingerprint> {
+        self.connection.peer_fingerprint()
+    }
+
     // Could disappear at anytime.
     #[doc(hidden)]
     #[cfg(feature = "unstable")]
@@ -650,13 +922,24 @@ impl Builder {
     pub fn new() -> Builder {
         Builder {
             reset_stream_duration: Duration::from_secs(proto::DEFAULT_RESET_STREAM_SECS),
+            settings_ack_timeout: None,
+            keep_alive_interval: None,
+            keep_alive_timeout: Duration::from_secs(proto::DEFAULT_KEEP_ALIVE_TIMEOUT_SECS),
+            keep_alive_while_idle: false,
+            timer: Arc::new(TokioTimer),
             reset_stream_max: proto::DEFAULT_RESET_STREAM_MAX,
             pending_accept_reset_stream_max: proto::DEFAULT_REMOTE_RESET_STREAM_MAX,
             settings: Settings::default(),
             initial_target_connection_window_size: None,
+            adaptive_window: None,
             max_send_buffer_size: proto::DEFAULT_MAX_SEND_BUFFER_SIZE,
 
             local_max_error_reset_streams: Some(proto::DEFAULT_LOCAL_RESET_COUNT_MAX),
+            encoding_policy: None,
+            dependency_tree_scheduling: false,
+            record_peer_fingerprint: false,
+            extension_frame_types: Vec::new(),
+            frame_observer: None,
         }
     }
 
@@ -728,6 +1011,50 @@ impl Builder {
         self
     }
 
+    /// Enables flow control windows which adapt to the connection.
+    ///
+    /// The bandwidth-delay product of the connection is estimated from the
+    /// round trip time of PING frames sent while DATA is received. When the
+    /// receive windows limit the throughput, the stream and connection
+    /// windows are increased together, by sending a SETTINGS frame with a new
+    /// initial window size and WINDOW_UPDATE frames, up to `max_window_size`.
+    ///
+    /// The estimate starts at the [`initial_window_size`], and each window is
+    /// only increased once the estimate exceeds it: a larger
+    /// [`initial_connection_window_size`] is kept until the estimate grows
+    /// past it, and windows are never decreased. While the new SETTINGS
+    /// frame is waiting for an acknowledgement, `set_initial_window_size` on
+    /// the connection returns an error.
+    ///
+    /// By default, the windows do not adapt.
+    ///
+    /// [`initial_window_size`]: #method.initial_window_size
+    /// [`initial_connection_window_size`]: #method.initial_connection_window_size
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::server::*;
+    /// #
+    /// # fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Handshake<T>
+    /// # {
+    /// // `server_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let server_fut = Builder::new()
+    ///     .adaptive_window(8 * 1024 * 1024)
+    ///     .handshake(my_io);
+    /// # server_fut
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn adaptive_window(&mut self, max_window_size: u32) -> &mut Self {
+        self.adaptive_window = Some(max_window_size.min(proto::MAX_WINDOW_SIZE));
+        self
+    }
+
     /// Indicates the size (in octets) of the largest HTTP/2 frame payload that the
     /// configured server is able to accept.
     ///
@@ -1020,6 +1347,132 @@ impl Builder {
         self
     }
 
+    /// Sets the maximum time to wait for the remote to acknowledge local
+    /// SETTINGS.
+    ///
+    /// Each SETTINGS frame sent by the connection, including the initial one
+    /// sent during the handshake, must be acknowledged by the remote. If the
+    /// acknowledgement does not arrive within `dur`, the connection is closed
+    /// with a GOAWAY frame carrying `SETTINGS_TIMEOUT`, and the connection
+    /// future returns an error for which [`Error::is_settings_timeout`]
+    /// returns `true`.
+    ///
+    /// The timeout is waited on with the [`timer`](#method.timer). With the
+    /// default [`TokioTimer`], the connection must then be polled within a
+    /// Tokio runtime with the time driver enabled, or it panics once the
+    /// timeout starts. By default, there is no timeout.
+    ///
+    /// [`Error::is_settings_timeout`]: ../struct.Error.html#method.is_settings_timeout
+    /// [`TokioTimer`]: ../struct.TokioTimer.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::server::*;
+    /// # use std::time::Duration;
+    /// #
+    /// # fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Handshake<T>
+    /// # {
+    /// // `server_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let server_fut = Builder::new()
+    ///     .settings_ack_timeout(Duration::from_secs(10))
+    ///     .handshake(my_io);
+    /// # server_fut
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn settings_ack_timeout(&mut self, dur: Duration) -> &mut Self {
+        self.settings_ack_timeout = Some(dur);
+        self
+    }
+
+    /// Sets the interval at which keep-alive PINGs are sent.
+    ///
+    /// When no frame is received from the remote for `interval`, a PING is
+    /// sent. If the remote does not acknowledge it within the
+    /// [`keep_alive_timeout`], the connection is closed and fails with an
+    /// error for which [`Error::is_keep_alive_timeout`] returns `true`.
+    ///
+    /// The interval and the timeout are waited on with the
+    /// [`timer`](#method.timer). With the default [`TokioTimer`], the
+    /// connection must then be polled within a Tokio runtime with the time
+    /// driver enabled, or it panics. By default, keep-alive PINGs are not
+    /// sent.
+    ///
+    /// [`keep_alive_timeout`]: #method.keep_alive_timeout
+    /// [`Error::is_keep_alive_timeout`]: ../struct.Error.html#method.is_keep_alive_timeout
+    /// [`TokioTimer`]: ../struct.TokioTimer.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::server::*;
+    /// # use std::time::Duration;
+    /// #
+    /// # fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Handshake<T>
+    /// # {
+    /// // `server_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let server_fut = Builder::new()
+    ///     .keep_alive_interval(Duration::from_secs(30))
+    ///     .keep_alive_timeout(Duration::from_secs(10))
+    ///     .handshake(my_io);
+    /// # server_fut
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn keep_alive_interval(&mut self, interval: Duration) -> &mut Self {
+        self.keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Sets the time to wait for the acknowledgement of a keep-alive PING.
+    ///
+    /// Only used when a [`keep_alive_interval`] is set.
+    ///
+    /// The default value is currently 20 seconds.
+    ///
+    /// [`keep_alive_interval`]: #method.keep_alive_interval
+    pub fn keep_alive_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.keep_alive_timeout = timeout;
+        self
+    }
+
+    /// Sets whether keep-alive PINGs are sent while there are no open streams.
+    ///
+    /// Only used when a [`keep_alive_interval`] is set.
+    ///
+    /// The default value is `false`.
+    ///
+    /// [`keep_alive_interval`]: #method.keep_alive_interval
+    pub fn keep_alive_while_idle(&mut self, enabled: bool) -> &mut Self {
+        self.keep_alive_while_idle = enabled;
+        self
+    }
+
+    /// Sets the [`Timer`] used to wait for timeouts and keep-alive intervals.
+    ///
+    /// The default is [`TokioTimer`], which requires the Tokio runtime's time
+    /// driver. The timer is only used when a [`settings_ack_timeout`] or a
+    /// [`keep_alive_interval`] is set, so connections without them do not
+    /// need the time driver.
+    ///
+    /// [`settings_ack_timeout`]: #method.settings_ack_timeout
+    /// [`keep_alive_interval`]: #method.keep_alive_interval
+    /// [`Timer`]: ../trait.Timer.html
+    /// [`TokioTimer`]: ../struct.TokioTimer.html
+    pub fn timer<T: Timer>(&mut self, timer: T) -> &mut Self {
+        self.timer = Arc::new(timer);
+        self
+    }
+
     /// Enables the [extended CONNECT protocol].
     ///
     /// [extended CONNECT protocol]: https://datatracker.ietf.org/doc/html/rfc8441#section-4
@@ -1028,6 +1481,198 @@ impl Builder {
         self
     }
 
+    /// Enables scheduling responses by the RFC 7540 dependency tree.
+    ///
+    /// Clients describe how their streams depend on each other, and the
+    /// relative weight of sibling streams, in HEADERS and PRIORITY frames.
+    /// When enabled, frames of a stream are only sent when the streams it
+    /// depends on have nothing to send, and sibling streams share the
+    /// connection in proportion to their weights.
+    ///
+    /// Scheduling falls back to the [Extensible Priorities] urgency of each
+    /// stream if the client sends `SETTINGS_NO_RFC7540_PRIORITIES`.
+    ///
+    /// The default value is `false`.
+    ///
+    /// [Extensible Priorities]: ../ext/struct.ExtensiblePriority.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::server::*;
+    /// #
+    /// # fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Handshake<T>
+    /// # {
+    /// // `server_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let server_fut = Builder::new()
+    ///     .dependency_tree_scheduling(true)
+    ///     .handshake(my_io);
+    /// # server_fut
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn dependency_tree_scheduling(&mut self, enabled: bool) -> &mut Self {
+        self.dependency_tree_scheduling = enabled;
+        self
+    }
+
+    /// Enables or disables recording the HTTP/2 fingerprint of the client.
+    ///
+    /// When enabled, the frames the client sends before its first request
+    /// are recorded, and the fingerprint is returned by
+    /// [`Connection::peer_fingerprint`]. At most 64 PRIORITY frames are
+    /// recorded; further ones are left out of the fingerprint.
+    ///
+    /// The default value is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::server::*;
+    /// #
+    /// # fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Handshake<T>
+    /// # {
+    /// // `server_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let server_fut = Builder::new()
+    ///     .record_peer_fingerprint(true)
+    ///     .handshake(my_io);
+    /// # server_fut
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn record_peer_fingerprint(&mut self, enabled: bool) -> &mut Self {
+        self.record_peer_fingerprint = enabled;
+        self
+    }
+
+    /// Sets the policy deciding how header fields are HPACK encoded.
+    ///
+    /// The policy chooses, for every header field, whether it is inserted into
+    /// the dynamic table and whether its strings are Huffman encoded. See
+    /// [`EncodingPolicy`] for details.
+    ///
+    /// By default, h2 uses its own heuristics.
+    ///
+    /// [`EncodingPolicy`]: ../trait.EncodingPolicy.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::server::*;
+    /// #
+    /// # fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Handshake<T>
+    /// # {
+    /// // `server_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let server_fut = Builder::new()
+    ///     .encoding_policy(h2::FirefoxPolicy)
+    ///     .handshake(my_io);
+    /// # server_fut
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn encoding_policy<P: EncodingPolicy>(&mut self, policy: P) -> &mut Self {
+        self.encoding_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Registers an extension frame type to receive.
+    ///
+    /// Received frames of this type are returned by
+    /// [`Connection::poll_extension_frame`]. Frames of unregistered unknown
+    /// types are ignored.
+    ///
+    /// [`Connection::poll_extension_frame`]: struct.Connection.html#method.poll_extension_frame
+    ///
+    /// # Panics
+    ///
+    /// Panics if `kind` is a frame type known to h2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::server::*;
+    /// #
+    /// # fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Handshake<T>
+    /// # {
+    /// // `server_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let server_fut = Builder::new()
+    ///     .extension_frame_type(0xf0)
+    ///     .handshake(my_io);
+    /// # server_fut
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn extension_frame_type(&mut self, kind: u8) -> &mut Self {
+        assert!(
+            frame::is_extension_type(kind),
+            "frame type {} is not an extension frame type",
+            kind
+        );
+
+        if !self.extension_frame_types.contains(&kind) {
+            self.extension_frame_types.push(kind);
+        }
+        self
+    }
+
+    /// Sets the [`FrameObserver`] called with each frame sent and received.
+    ///
+    /// The observer sees the frames as they are written to and read from the
+    /// I/O object, including the HPACK decoded header fields of header blocks.
+    ///
+    /// By default, no observer is set and frames are not inspected.
+    ///
+    /// [`FrameObserver`]: ../trait.FrameObserver.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite};
+    /// # use h2::server::*;
+    /// #
+    /// # fn doc<T: AsyncRead + AsyncWrite + Unpin>(my_io: T)
+    /// # -> Handshake<T>
+    /// # {
+    /// #[derive(Debug)]
+    /// struct Log;
+    ///
+    /// impl h2::FrameObserver for Log {
+    ///     fn on_frame(&self, frame: &h2::ObservedFrame<'_>) {
+    ///         println!("{:?}", frame);
+    ///     }
+    /// }
+    ///
+    /// // `server_fut` is a future representing the completion of the HTTP/2
+    /// // handshake.
+    /// let server_fut = Builder::new()
+    ///     .frame_observer(Log)
+    ///     .handshake(my_io);
+    /// # server_fut
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn frame_observer<O: FrameObserver>(&mut self, observer: O) -> &mut Self {
+        self.frame_observer = Some(Arc::new(observer));
+        self
+    }
+
     /// Creates a new configured HTTP/2 server backed by `io`.
     ///
     /// It is expected that `io` already be in an appropriate state to commence
@@ -1091,6 +1736,77 @@ impl Builder {
     {
         Connection::handshake2(io, self.clone())
     }
+
+    /// Creates a new configured HTTP/2 server backed by `io`, upgraded from
+    /// an HTTP/1.1 request with `Upgrade: h2c`.
+    ///
+    /// `http2_settings` is the value of the request's `HTTP2-Settings`
+    /// header, decoded by [`decode_http2_settings`]. The settings are applied
+    /// as if the client had sent them in its first SETTINGS frame. The caller
+    /// is responsible for writing the `101 Switching Protocols` response to
+    /// `io` and for reading the request body, if any, before calling this
+    /// function. If the header is invalid, the request must not be upgraded.
+    ///
+    /// As described in [RFC 7540 §3.2], the request continues as stream 1,
+    /// which is half-closed by the client: it is the first request returned
+    /// by [`Connection::accept`], with an empty body, and the response is
+    /// sent over HTTP/2. The client must still send the connection preface.
+    ///
+    /// [RFC 7540 §3.2]: https://datatracker.ietf.org/doc/html/rfc7540#section-3.2
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+    /// # use h2::server::*;
+    /// # use http::Request;
+    /// #
+    /// # async fn doc<T: AsyncRead + AsyncWrite + Unpin>(mut my_io: T, request: Request<()>) {
+    /// // Validate the header before agreeing to the upgrade.
+    /// let settings = match request.headers().get("http2-settings") {
+    ///     Some(value) => match decode_http2_settings(value) {
+    ///         Ok(settings) => settings,
+    ///         // Answer over HTTP/1.1 instead.
+    ///         Err(_) => return,
+    ///     },
+    ///     None => return,
+    /// };
+    ///
+    /// my_io
+    ///     .write_all(b"HTTP/1.1 101 Switching Protocols\r\n\
+    ///                  Connection: Upgrade\r\n\
+    ///                  Upgrade: h2c\r\n\r\n")
+    ///     .await
+    ///     .unwrap();
+    ///
+    /// let mut connection = Builder::new()
+    ///     .handshake_upgraded::<_, bytes::Bytes>(my_io, &settings, request)
+    ///     .await
+    ///     .unwrap();
+    ///
+    /// // The upgraded request is accepted first.
+    /// let (request, respond) = connection.accept().await.unwrap().unwrap();
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn handshake_upgraded<T, B>(
+        &self,
+        io: T,
+        http2_settings: &UpgradeSettings,
+        request: Request<()>,
+    ) -> Handshake<T, B>
+    where
+        T: AsyncRead + AsyncWrite + Unpin,
+        B: Buf,
+    {
+        let mut handshake = Connection::handshake2(io, self.clone());
+        handshake.upgrade = Some(Upgrade {
+            settings: http2_settings.settings.clone(),
+            request: upgrade::request_headers(request),
+        });
+        handshake
+    }
 }
 
 impl Default for Builder {
@@ -1115,9 +1831,13 @@ impl<B: Buf> SendResponse<B> {
     /// request.  This function may only be called once per instance and only if
     /// [`send_reset`] has not been previously called.
     ///
+    /// A [`HeaderOrder`] in the response extensions sets the order in which the
+    /// header fields are encoded.
+    ///
     /// [`SendResponse`]: #
     /// [`SendStream`]: ../struct.SendStream.html
     /// [`send_reset`]: #method.send_reset
+    /// [`HeaderOrder`]: ../ext/struct.HeaderOrder.html
     pub fn send_response(
         &mut self,
         response: Response<()>,
@@ -1129,6 +1849,23 @@ impl<B: Buf> SendResponse<B> {
             .map_err(Into::into)
     }
 
+    /// Send an informational (1xx) response to a client request.
+    ///
+    /// Informational responses, such as `103 Early Hints` or `100 Continue`,
+    /// may be sent any number of times before the final response is sent with
+    /// [`send_response`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the status of `response` is not 1xx, or is
+    /// `101 Switching Protocols`, which is not allowed in HTTP/2. Returns an
+    /// error as well if the final response was already sent.
+    ///
+    /// [`send_response`]: #method.send_response
+    pub fn send_informational(&mut self, response: Response<()>) -> Result<(), crate::Error> {
+        self.inner.send_informational(response).map_err(Into::into)
+    }
+
     /// Push a request and response to the client
     ///
     /// On success, a [`SendResponse`] instance is returned.
@@ -1181,6 +1918,28 @@ impl<B: Buf> SendResponse<B> {
         self.inner.poll_reset(cx, proto::PollReset::AwaitingHeaders)
     }
 
+    /// Advertises alternative services for the origin of the request.
+    ///
+    /// `field_value` has the format of the `Alt-Svc` header field, for
+    /// example `h3=":443"; ma=3600`. The ALTSVC frame is sent ahead of any
+    /// frame still queued on the stream, so calling this before
+    /// [`send_response`] lets the client see it with the response.
+    ///
+    /// See [RFC 7838] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream is closed, or if `field_value` does not
+    /// fit in a single frame.
+    ///
+    /// [`send_response`]: #method.send_response
+    /// [RFC 7838]: https://www.rfc-editor.org/rfc/rfc7838#section-4
+    pub fn send_altsvc(&mut self, field_value: &HeaderValue) -> Result<(), crate::Error> {
+        self.inner
+            .send_altsvc(Bytes::copy_from_slice(field_value.as_bytes()))
+            .map_err(Into::into)
+    }
+
     /// Returns the stream ID of the response stream.
     ///
     /// # Panics
@@ -1384,6 +2143,16 @@ where
                             initial_max_send_streams: 0,
                             max_send_buffer_size: self.builder.max_send_buffer_size,
                             reset_stream_duration: self.builder.reset_stream_duration,
+                            settings_ack_timeout: self.builder.settings_ack_timeout,
+                            keep_alive: self.builder.keep_alive_interval.map(|interval| {
+                                proto::KeepAliveConfig {
+                                    interval,
+                                    timeout: self.builder.keep_alive_timeout,
+                                    while_idle: self.builder.keep_alive_while_idle,
+                                }
+                            }),
+                            adaptive_window: self.builder.adaptive_window,
+                            timer: self.builder.timer.clone(),
                             reset_stream_max: self.builder.reset_stream_max,
                             remote_reset_stream_max: self.builder.pending_accept_reset_stream_max,
                             local_error_reset_streams_max: self
@@ -1393,6 +2162,7 @@ where
                             headers_stream_dependency: None,
                             headers_pseudo_order: None,
                             priorities: None,
+                            dependency_tree_scheduling: self.builder.dependency_tree_scheduling,
                         },
                     );
 
@@ -1402,6 +2172,12 @@ where
                         c.set_target_window_size(sz);
                     }
 
+                    if let Some(upgrade) = self.upgrade.take() {
+                        tracing::trace!("opening upgraded stream");
+                        c.connection
+                            .recv_upgrade(&upgrade.settings, upgrade.request)?;
+                    }
+
                     return Poll::Ready(Ok(c));
                 }
                 Handshaking::Done => {
@@ -1433,7 +2209,10 @@ impl Peer {
         // Extract the components of the HTTP request
         let (
             Parts {
-                status, headers, ..
+                status,
+                headers,
+                mut extensions,
+                ..
             },
             _,
         ) = response.into_parts();
@@ -1445,6 +2224,10 @@ impl Peer {
         // Create the HEADERS frame
         let mut frame = frame::Headers::new(id, pseudo, headers);
 
+        if let Some(order) = extensions.remove::<HeaderOrder>() {
+            frame.set_header_order(order);
+        }
+
         if end_of_stream {
             frame.set_end_stream()
         }
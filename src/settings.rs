@@ -0,0 +1,130 @@
+use crate::frame::{self, SettingId};
+
+use std::fmt;
+
+/// The maximum number of parameters kept in a `SettingsSnapshot`.
+const MAX_PARAMETERS: usize = 64;
+
+/// The SETTINGS parameters sent by one endpoint of a connection.
+///
+/// A `SettingsSnapshot` merges every SETTINGS frame sent by an endpoint so
+/// far. Parameters are kept in the order they were first sent in, and a
+/// parameter sent again replaces the previous value. Parameters with an id
+/// unknown to h2 are included.
+///
+/// The remote peer's settings are returned by `remote_settings`, and the local
+/// settings acknowledged by the remote peer are returned by `local_settings`,
+/// on `client::Connection`, `client::SendRequest` and `server::Connection`.
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct SettingsSnapshot {
+    parameters: Vec<(u16, u32)>,
+}
+
+// ===== impl SettingsSnapshot =====
+
+impl SettingsSnapshot {
+    /// Returns the value of the parameter `id`, if it was sent.
+    pub fn get(&self, id: u16) -> Option<u32> {
+        self.parameters
+            .iter()
+            .find(|&&(i, _)| i == id)
+            .map(|&(_, val)| val)
+    }
+
+    /// Returns an iterator over the `(id, value)` pairs of the parameters.
+    pub fn iter(&self) -> impl Iterator<Item = (u16, u32)> + '_ {
+        self.parameters.iter().copied()
+    }
+
+    /// Returns the value of `SETTINGS_HEADER_TABLE_SIZE`, if it was sent.
+    pub fn header_table_size(&self) -> Option<u32> {
+        self.get(SettingId::HeaderTableSize.into())
+    }
+
+    /// Returns the value of `SETTINGS_ENABLE_PUSH`, if it was sent.
+    pub fn is_push_enabled(&self) -> Option<bool> {
+        self.get(SettingId::EnablePush.into()).map(|val| val != 0)
+    }
+
+    /// Returns the value of `SETTINGS_MAX_CONCURRENT_STREAMS`, if it was sent.
+    pub fn max_concurrent_streams(&self) -> Option<u32> {
+        self.get(SettingId::MaxConcurrentStreams.into())
+    }
+
+    /// Returns the value of `SETTINGS_INITIAL_WINDOW_SIZE`, if it was sent.
+    pub fn initial_window_size(&self) -> Option<u32> {
+        self.get(SettingId::InitialWindowSize.into())
+    }
+
+    /// Returns the value of `SETTINGS_MAX_FRAME_SIZE`, if it was sent.
+    pub fn max_frame_size(&self) -> Option<u32> {
+        self.get(SettingId::MaxFrameSize.into())
+    }
+
+    /// Returns the value of `SETTINGS_MAX_HEADER_LIST_SIZE`, if it was sent.
+    pub fn max_header_list_size(&self) -> Option<u32> {
+        self.get(SettingId::MaxHeaderListSize.into())
+    }
+
+    /// Returns the value of `SETTINGS_ENABLE_CONNECT_PROTOCOL`, if it was
+    /// sent.
+    pub fn is_extended_connect_protocol_enabled(&self) -> Option<bool> {
+        self.get(SettingId::EnableConnectProtocol.into())
+            .map(|val| val != 0)
+    }
+
+    /// Returns the value of `SETTINGS_NO_RFC7540_PRIORITIES`, if it was sent.
+    pub fn is_no_rfc7540_priorities(&self) -> Option<bool> {
+        self.get(SettingId::NoRfc7540Priorities.into())
+            .map(|val| val != 0)
+    }
+
+    /// Merges the parameters of a SETTINGS frame.
+    pub(crate) fn merge(&mut self, frame: &frame::Settings) {
+        frame.for_each_parameter(|id, val| {
+            if let Some(entry) = self.parameters.iter_mut().find(|(i, _)| *i == id) {
+                entry.1 = val;
+            } else if self.parameters.len() < MAX_PARAMETERS {
+                self.parameters.push((id, val));
+            } else {
+                tracing::debug!("ignoring setting; snapshot is full; id={}", id);
+            }
+        });
+    }
+}
+
+impl fmt::Debug for SettingsSnapshot {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let mut snapshot = SettingsSnapshot::default();
+        assert_eq!(snapshot.max_concurrent_streams(), None);
+
+        let mut settings = frame::Settings::default();
+        settings.set_max_concurrent_streams(Some(100));
+        settings.set_initial_window_size(Some(65_536));
+        snapshot.merge(&settings);
+
+        let mut settings = frame::Settings::default();
+        settings.set_max_concurrent_streams(Some(10));
+        settings.set_enable_push(false);
+        snapshot.merge(&settings);
+
+        assert_eq!(snapshot.max_concurrent_streams(), Some(10));
+        assert_eq!(snapshot.initial_window_size(), Some(65_536));
+        assert_eq!(snapshot.is_push_enabled(), Some(false));
+        assert_eq!(snapshot.get(0x1f), None);
+        assert_eq!(
+            snapshot.iter().collect::<Vec<_>>(),
+            [(3, 10), (4, 65_536), (2, 0)]
+        );
+    }
+}
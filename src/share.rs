@@ -1,6 +1,8 @@
 use crate::codec::UserError;
+use crate::ext::ExtensiblePriority;
 use crate::frame::Reason;
 use crate::proto::{self, WindowSize};
+use crate::StreamStats;
 
 use bytes::{Buf, Bytes};
 use http::HeaderMap;
@@ -345,6 +347,21 @@ impl<B: Buf> SendStream<B> {
         self.inner.send_trailers(trailers).map_err(Into::into)
     }
 
+    /// Sends a PRIORITY_UPDATE frame, changing the priority of this request.
+    ///
+    /// Only clients can reprioritize streams, calling this on a server stream
+    /// returns an [`Error`].
+    ///
+    /// [`Error`]: struct.Error.html
+    pub fn send_priority_update(
+        &mut self,
+        priority: &ExtensiblePriority,
+    ) -> Result<(), crate::Error> {
+        self.inner
+            .send_priority_update(priority)
+            .map_err(Into::into)
+    }
+
     /// Resets the stream.
     ///
     /// This cancels the request / response exchange. If the response has not
@@ -380,6 +397,15 @@ impl<B: Buf> SendStream<B> {
     pub fn stream_id(&self) -> StreamId {
         StreamId::from_internal(self.inner.stream_id())
     }
+
+    /// Returns a snapshot of the statistics of this stream.
+    ///
+    /// # Panics
+    ///
+    /// If the lock on the stream store has been poisoned.
+    pub fn stats(&self) -> StreamStats {
+        self.inner.stats()
+    }
 }
 
 // ===== impl StreamId =====
@@ -458,6 +484,15 @@ impl RecvStream {
     pub fn stream_id(&self) -> StreamId {
         self.inner.stream_id()
     }
+
+    /// Returns a snapshot of the statistics of this stream.
+    ///
+    /// # Panics
+    ///
+    /// If the lock on the stream store has been poisoned.
+    pub fn stats(&self) -> StreamStats {
+        self.inner.inner.stats()
+    }
 }
 
 #[cfg(feature = "stream")]
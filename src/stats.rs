@@ -0,0 +1,375 @@
+use crate::frame::Reason;
+
+use std::time::Duration;
+
+/// The maximum number of distinct reasons counted for RST_STREAM and GOAWAY
+/// frames in each direction.
+const MAX_REASONS: usize = 32;
+
+/// A snapshot of the statistics of a connection.
+///
+/// Returned by `stats` on `client::Connection` and `server::Connection`.
+#[derive(Clone, Debug)]
+pub struct ConnectionStats {
+    sent: FrameStats,
+    received: FrameStats,
+    hpack_encoder: HpackStats,
+    hpack_decoder: HpackStats,
+    send_window: u32,
+    recv_window: u32,
+    rtt: Option<Duration>,
+}
+
+/// Counts of the frames sent or received on a connection.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FrameStats {
+    frames: u64,
+    bytes: u64,
+    /// Sorted by frame type.
+    frame_types: Vec<(u8, FrameTypeStats)>,
+    resets: Vec<(Reason, u64)>,
+    go_aways: Vec<(Reason, u64)>,
+}
+
+/// Counts of the frames of one type sent or received on a connection.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FrameTypeStats {
+    frames: u64,
+    bytes: u64,
+}
+
+/// Counts of the header fields encoded or decoded with HPACK.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HpackStats {
+    fields: u64,
+    dynamic_table_hits: u64,
+    field_bytes: u64,
+    encoded_bytes: u64,
+}
+
+/// A snapshot of the statistics of a stream.
+///
+/// Returned by `stats` on `SendStream` and `RecvStream`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StreamStats {
+    data_bytes_sent: u64,
+    data_bytes_received: u64,
+    send_window: u32,
+    recv_window: u32,
+}
+
+// ===== impl ConnectionStats =====
+
+impl ConnectionStats {
+    pub(crate) fn new(
+        sent: FrameStats,
+        received: FrameStats,
+        hpack_encoder: HpackStats,
+        hpack_decoder: HpackStats,
+        send_window: u32,
+        recv_window: u32,
+        rtt: Option<Duration>,
+    ) -> ConnectionStats {
+        ConnectionStats {
+            sent,
+            received,
+            hpack_encoder,
+            hpack_decoder,
+            send_window,
+            recv_window,
+            rtt,
+        }
+    }
+
+    /// Returns the counts of the frames sent to the remote.
+    pub fn sent(&self) -> &FrameStats {
+        &self.sent
+    }
+
+    /// Returns the counts of the frames received from the remote.
+    pub fn received(&self) -> &FrameStats {
+        &self.received
+    }
+
+    /// Returns the counts of the header fields encoded in sent frames.
+    pub fn hpack_encoder(&self) -> &HpackStats {
+        &self.hpack_encoder
+    }
+
+    /// Returns the counts of the header fields decoded from received frames.
+    pub fn hpack_decoder(&self) -> &HpackStats {
+        &self.hpack_decoder
+    }
+
+    /// Returns the connection-level window for sending DATA.
+    pub fn send_window(&self) -> u32 {
+        self.send_window
+    }
+
+    /// Returns the connection-level window for receiving DATA.
+    pub fn recv_window(&self) -> u32 {
+        self.recv_window
+    }
+
+    /// Returns the round trip time of the last PING acknowledged by the
+    /// remote.
+    ///
+    /// This includes the PINGs sent by `PingPong`, keep-alive PINGs and the
+    /// PINGs used to adapt the flow control windows.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+}
+
+// ===== impl FrameStats =====
+
+impl FrameStats {
+    /// Returns the number of frames.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Returns the number of bytes of the frames, including the frame headers.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns the counts of the frames of type `frame_type`, such as `0x0`
+    /// for DATA frames.
+    pub fn frame_type(&self, frame_type: u8) -> FrameTypeStats {
+        self.frame_types
+            .binary_search_by_key(&frame_type, |&(ty, _)| ty)
+            .map_or_else(|_| FrameTypeStats::default(), |i| self.frame_types[i].1)
+    }
+
+    /// Returns an iterator over the counts of each frame type, ordered by
+    /// frame type.
+    pub fn frame_types(&self) -> impl Iterator<Item = (u8, FrameTypeStats)> + '_ {
+        self.frame_types.iter().copied()
+    }
+
+    /// Returns the number of RST_STREAM frames with `reason`.
+    pub fn resets(&self, reason: Reason) -> u64 {
+        count_of(&self.resets, reason)
+    }
+
+    /// Returns an iterator over the number of RST_STREAM frames of each
+    /// reason.
+    pub fn reset_reasons(&self) -> impl Iterator<Item = (Reason, u64)> + '_ {
+        self.resets.iter().copied()
+    }
+
+    /// Returns the number of GOAWAY frames with `reason`.
+    pub fn go_aways(&self, reason: Reason) -> u64 {
+        count_of(&self.go_aways, reason)
+    }
+
+    /// Returns an iterator over the number of GOAWAY frames of each reason.
+    pub fn go_away_reasons(&self) -> impl Iterator<Item = (Reason, u64)> + '_ {
+        self.go_aways.iter().copied()
+    }
+
+    /// Records a frame of `len` bytes, including the frame header.
+    pub(crate) fn record_frame(&mut self, frame_type: u8, len: usize) {
+        self.frames += 1;
+        self.bytes += len as u64;
+
+        let i = match self
+            .frame_types
+            .binary_search_by_key(&frame_type, |&(ty, _)| ty)
+        {
+            Ok(i) => i,
+            Err(i) => {
+                self.frame_types
+                    .insert(i, (frame_type, FrameTypeStats::default()));
+                i
+            }
+        };
+
+        let stats = &mut self.frame_types[i].1;
+        stats.frames += 1;
+        stats.bytes += len as u64;
+    }
+
+    pub(crate) fn record_reset(&mut self, reason: Reason) {
+        count_reason(&mut self.resets, reason);
+    }
+
+    pub(crate) fn record_go_away(&mut self, reason: Reason) {
+        count_reason(&mut self.go_aways, reason);
+    }
+}
+
+fn count_of(counts: &[(Reason, u64)], reason: Reason) -> u64 {
+    counts
+        .iter()
+        .find(|&&(r, _)| r == reason)
+        .map_or(0, |&(_, count)| count)
+}
+
+fn count_reason(counts: &mut Vec<(Reason, u64)>, reason: Reason) {
+    if let Some(entry) = counts.iter_mut().find(|(r, _)| *r == reason) {
+        entry.1 += 1;
+    } else if counts.len() < MAX_REASONS {
+        counts.push((reason, 1));
+    } else {
+        tracing::debug!("not counting reason; too many reasons; reason={:?}", reason);
+    }
+}
+
+// ===== impl FrameTypeStats =====
+
+impl FrameTypeStats {
+    /// Returns the number of frames.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Returns the number of bytes of the frames, including the frame headers.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+// ===== impl HpackStats =====
+
+impl HpackStats {
+    /// Returns the number of header fields.
+    pub fn fields(&self) -> u64 {
+        self.fields
+    }
+
+    /// Returns the number of header fields represented by an index into the
+    /// dynamic table.
+    pub fn dynamic_table_hits(&self) -> u64 {
+        self.dynamic_table_hits
+    }
+
+    /// Returns the size of the names and values of the header fields.
+    pub fn field_bytes(&self) -> u64 {
+        self.field_bytes
+    }
+
+    /// Returns the size of the header blocks the fields are encoded in.
+    pub fn encoded_bytes(&self) -> u64 {
+        self.encoded_bytes
+    }
+
+    /// Returns the ratio of the size of the header fields to the size of their
+    /// encoding, or `None` if no header block was encoded.
+    pub fn compression_ratio(&self) -> Option<f64> {
+        if self.encoded_bytes == 0 {
+            return None;
+        }
+
+        Some(self.field_bytes as f64 / self.encoded_bytes as f64)
+    }
+
+    /// Returns the fraction of the header fields represented by an index into
+    /// the dynamic table, or `None` if no header field was encoded.
+    pub fn dynamic_table_hit_rate(&self) -> Option<f64> {
+        if self.fields == 0 {
+            return None;
+        }
+
+        Some(self.dynamic_table_hits as f64 / self.fields as f64)
+    }
+
+    /// Records a header field, of which the name and value are `len` bytes.
+    pub(crate) fn record_field(&mut self, len: usize, is_dynamic_table_hit: bool) {
+        self.fields += 1;
+        self.field_bytes += len as u64;
+        if is_dynamic_table_hit {
+            self.dynamic_table_hits += 1;
+        }
+    }
+
+    /// Records an encoded header block of `len` bytes.
+    pub(crate) fn record_block(&mut self, len: usize) {
+        self.encoded_bytes += len as u64;
+    }
+}
+
+// ===== impl StreamStats =====
+
+impl StreamStats {
+    pub(crate) fn new(
+        data_bytes_sent: u64,
+        data_bytes_received: u64,
+        send_window: u32,
+        recv_window: u32,
+    ) -> StreamStats {
+        StreamStats {
+            data_bytes_sent,
+            data_bytes_received,
+            send_window,
+            recv_window,
+        }
+    }
+
+    /// Returns the number of DATA payload bytes sent on the stream.
+    pub fn data_bytes_sent(&self) -> u64 {
+        self.data_bytes_sent
+    }
+
+    /// Returns the number of DATA payload bytes received on the stream.
+    pub fn data_bytes_received(&self) -> u64 {
+        self.data_bytes_received
+    }
+
+    /// Returns the stream-level window for sending DATA.
+    pub fn send_window(&self) -> u32 {
+        self.send_window
+    }
+
+    /// Returns the stream-level window for receiving DATA.
+    pub fn recv_window(&self) -> u32 {
+        self.recv_window
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_frame_stats() {
+        let mut stats = FrameStats::default();
+        stats.record_frame(0x4, 15);
+        stats.record_frame(0x0, 20);
+        stats.record_frame(0x4, 9);
+        stats.record_reset(Reason::CANCEL);
+        stats.record_reset(Reason::CANCEL);
+
+        assert_eq!(stats.frames(), 3);
+        assert_eq!(stats.bytes(), 44);
+        assert_eq!(stats.frame_type(0x4).frames(), 2);
+        assert_eq!(stats.frame_type(0x4).bytes(), 24);
+        assert_eq!(stats.frame_type(0x1), FrameTypeStats::default());
+        assert_eq!(
+            stats.frame_types().map(|(ty, _)| ty).collect::<Vec<_>>(),
+            [0x0, 0x4]
+        );
+        assert_eq!(stats.resets(Reason::CANCEL), 2);
+        assert_eq!(stats.resets(Reason::NO_ERROR), 0);
+
+        for code in 0..100 {
+            stats.record_go_away(code.into());
+        }
+        assert_eq!(stats.go_away_reasons().count(), MAX_REASONS);
+    }
+
+    #[test]
+    fn test_hpack_stats() {
+        let mut stats = HpackStats::default();
+        assert_eq!(stats.compression_ratio(), None);
+        assert_eq!(stats.dynamic_table_hit_rate(), None);
+
+        stats.record_field(20, false);
+        stats.record_field(20, true);
+        stats.record_block(10);
+
+        assert_eq!(stats.compression_ratio(), Some(4.0));
+        assert_eq!(stats.dynamic_table_hit_rate(), Some(0.5));
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// Creates the futures a connection uses to wait for a duration.
+///
+/// Connections wait on timers to send keep-alive PINGs and to detect a peer
+/// that stopped responding. By default, [`TokioTimer`] is used, which requires
+/// the Tokio runtime's time driver. Applications running on another runtime
+/// can supply their own `Timer` with [`client::Builder::timer`] or
+/// [`server::Builder::timer`].
+///
+/// [`client::Builder::timer`]: crate::client::Builder::timer
+/// [`server::Builder::timer`]: crate::server::Builder::timer
+pub trait Timer: fmt::Debug + Send + Sync + 'static {
+    /// Returns a future that completes once `duration` has elapsed.
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Sleep>>;
+}
+
+/// A future returned by [`Timer::sleep`].
+///
+/// This is implemented for every `Send + Sync` future with an output of `()`.
+pub trait Sleep: Future<Output = ()> + Send + Sync {}
+
+impl<F> Sleep for F where F: Future<Output = ()> + Send + Sync {}
+
+/// A [`Timer`] backed by [`tokio::time::sleep`].
+///
+/// Its sleeps must be polled within a Tokio runtime with the time driver
+/// enabled, such as one built with `enable_time` or `enable_all`, or they
+/// panic. For this timer, h2 always enables Tokio's `time` feature, even
+/// when another [`Timer`] is used.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioTimer;
+
+// ===== impl TokioTimer =====
+
+impl Timer for TokioTimer {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Sleep>> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
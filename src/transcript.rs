@@ -0,0 +1,640 @@
+//! Recording of the frames exchanged on a connection.
+//!
+//! A [`Recorder`] wraps the I/O of a `client::Connection` or a
+//! `server::Connection` and writes everything it reads and writes to a
+//! transcript, one frame per line. A [`Transcript`] parses it back, so that
+//! one side of a recorded connection can be played back in tests.
+//!
+//! Each line of a transcript holds the time elapsed since the recording
+//! started, `>` for bytes written by the connection or `<` for bytes it read,
+//! and the bytes of the frame in hexadecimal:
+//!
+//! ```text
+//! # h2 transcript
+//! 0.000000 > 505249202a20485454502f322e300d0a0d0a534d0d0a0d0a
+//! 0.000012 > 000012040000000000000100010000000200000000...
+//! 0.001503 < 000000040100000000
+//! ```
+//!
+//! The client connection preface and HTTP/1.1 messages preceding the frames,
+//! as sent when upgrading to h2c, are recorded on lines of their own. Empty
+//! lines and lines starting with `#` are ignored.
+//!
+//! Records are written to the transcript synchronously, from the task polling
+//! the connection. The sink should therefore be buffered, as the file opened
+//! by [`Recorder::create`] is: it is only flushed when the recorder is
+//! dropped, or by [`Recorder::flush_transcript`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use h2::client;
+//! use h2::transcript::Recorder;
+//! use tokio::net::TcpStream;
+//!
+//! # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+//! let tcp = TcpStream::connect("127.0.0.1:5928").await?;
+//! let io = Recorder::create(tcp, "incident.h2t")?;
+//! let (client, connection) = client::handshake(io).await?;
+//! # drop((client, connection));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::FrameDirection;
+
+use bytes::Bytes;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use std::fmt::{self, Write as _};
+use std::fs::File;
+use std::io::{self, BufWriter, IoSlice, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use std::{error, fs};
+
+const HEADER: &str = "# h2 transcript";
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+const FRAME_HEADER_LEN: usize = 9;
+
+/// An I/O wrapper recording the frames read and written to a transcript.
+///
+/// Recording never fails the connection: if writing the transcript fails,
+/// the error is logged and recording stops.
+///
+/// Each record is written to the sink while the connection reads or writes,
+/// which blocks the task until the sink accepts it. Writes to a buffered sink
+/// are cheap; flushing is left to [`flush_transcript`] and to dropping the
+/// recorder.
+///
+/// [`flush_transcript`]: Recorder::flush_transcript
+pub struct Recorder<T> {
+    io: T,
+    start: Instant,
+    sink: Option<Box<dyn Write + Send>>,
+    inbound: Framer,
+    outbound: Framer,
+}
+
+/// A recorded transcript.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Transcript {
+    records: Vec<Record>,
+}
+
+/// A frame, or the connection preface, recorded in a transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    elapsed: Duration,
+    direction: FrameDirection,
+    bytes: Bytes,
+}
+
+/// An error parsing a transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptError {
+    line: usize,
+}
+
+/// Splits the bytes of one direction into records.
+#[derive(Debug)]
+struct Framer {
+    buf: Vec<u8>,
+    /// True until the first frame, while the connection preface or HTTP/1.1
+    /// messages may be seen.
+    at_start: bool,
+}
+
+// ===== impl Recorder =====
+
+impl<T> Recorder<T> {
+    /// Wraps `io`, writing the transcript to `sink`.
+    ///
+    /// Writes to `sink` are not buffered by the recorder: wrap it in a
+    /// [`BufWriter`] if writing to it may block.
+    pub fn new<W>(io: T, sink: W) -> Recorder<T>
+    where
+        W: Write + Send + 'static,
+    {
+        let mut recorder = Recorder {
+            io,
+            start: Instant::now(),
+            sink: Some(Box::new(sink)),
+            inbound: Framer::new(),
+            outbound: Framer::new(),
+        };
+        recorder.write_line(HEADER);
+        recorder
+    }
+
+    /// Wraps `io`, writing the transcript to the file at `path`.
+    ///
+    /// The file is created, or truncated if it exists. Records are buffered,
+    /// and written to the file as the buffer fills up, on
+    /// [`flush_transcript`], and when the recorder is dropped.
+    ///
+    /// [`flush_transcript`]: Recorder::flush_transcript
+    pub fn create<P: AsRef<Path>>(io: T, path: P) -> io::Result<Recorder<T>> {
+        let file = File::create(path)?;
+        Ok(Recorder::new(io, BufWriter::new(file)))
+    }
+
+    /// Flushes the records written so far to the transcript sink.
+    ///
+    /// This blocks until the sink is flushed. Once writing the transcript has
+    /// failed, nothing is recorded anymore and this returns `Ok`.
+    pub fn flush_transcript(&mut self) -> io::Result<()> {
+        match self.sink {
+            Some(ref mut sink) => sink.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns a reference to the wrapped I/O.
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    /// Returns a mutable reference to the wrapped I/O.
+    ///
+    /// Bytes read from or written to it directly are not recorded.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.io
+    }
+
+    fn record(&mut self, direction: FrameDirection, data: &[u8]) {
+        if self.sink.is_none() || data.is_empty() {
+            return;
+        }
+
+        let elapsed = self.start.elapsed();
+        let mut lines = Vec::new();
+        let framer = match direction {
+            FrameDirection::Inbound => &mut self.inbound,
+            FrameDirection::Outbound => &mut self.outbound,
+        };
+        framer.push(data, |bytes| {
+            lines.push(Record::new(elapsed, direction, Bytes::copy_from_slice(bytes)).to_string())
+        });
+
+        for line in lines {
+            self.write_line(&line);
+        }
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if let Some(ref mut sink) = self.sink {
+            let res = sink
+                .write_all(line.as_bytes())
+                .and_then(|()| sink.write_all(b"\n"));
+
+            if let Err(e) = res {
+                tracing::warn!("stopped recording transcript; err={}", e);
+                self.sink = None;
+            }
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for Recorder<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut self.io).poll_read(cx, buf))?;
+        self.record(FrameDirection::Inbound, &buf.filled()[filled..]);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for Recorder<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let n = ready!(Pin::new(&mut self.io).poll_write(cx, buf))?;
+        self.record(FrameDirection::Outbound, &buf[..n]);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let n = ready!(Pin::new(&mut self.io).poll_write_vectored(cx, bufs))?;
+
+        let mut rem = n;
+        for buf in bufs {
+            let len = rem.min(buf.len());
+            self.record(FrameDirection::Outbound, &buf[..len]);
+            rem -= len;
+        }
+
+        Poll::Ready(Ok(n))
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
+
+impl<T> Drop for Recorder<T> {
+    fn drop(&mut self) {
+        // Record the bytes of frames cut short by the end of the connection.
+        let elapsed = self.start.elapsed();
+        for &direction in &[FrameDirection::Inbound, FrameDirection::Outbound] {
+            let framer = match direction {
+                FrameDirection::Inbound => &mut self.inbound,
+                FrameDirection::Outbound => &mut self.outbound,
+            };
+
+            if !framer.buf.is_empty() {
+                let bytes = Bytes::from(std::mem::take(&mut framer.buf));
+                let line = Record::new(elapsed, direction, bytes).to_string();
+                self.write_line(&line);
+            }
+        }
+
+        if let Err(e) = self.flush_transcript() {
+            tracing::warn!("failed to flush transcript; err={}", e);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Recorder<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Recorder")
+            .field("io", &self.io)
+            .field("start", &self.start)
+            .field("recording", &self.sink.is_some())
+            .finish()
+    }
+}
+
+// ===== impl Transcript =====
+
+impl Transcript {
+    /// Creates a transcript from its records.
+    pub fn new(records: Vec<Record>) -> Transcript {
+        Transcript { records }
+    }
+
+    /// Parses a transcript.
+    pub fn parse(src: &str) -> Result<Transcript, TranscriptError> {
+        let mut records = Vec::new();
+
+        for (i, line) in src.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let record = Record::parse(line).ok_or(TranscriptError { line: i + 1 })?;
+            records.push(record);
+        }
+
+        Ok(Transcript { records })
+    }
+
+    /// Reads and parses the transcript in the file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Transcript> {
+        let src = fs::read_to_string(path)?;
+        Transcript::parse(&src).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Returns the records, in the order they were read or written.
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+}
+
+impl FromStr for Transcript {
+    type Err = TranscriptError;
+
+    fn from_str(src: &str) -> Result<Transcript, TranscriptError> {
+        Transcript::parse(src)
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(fmt, "{}", HEADER)?;
+        for record in &self.records {
+            writeln!(fmt, "{}", record)?;
+        }
+        Ok(())
+    }
+}
+
+// ===== impl Record =====
+
+impl Record {
+    /// Creates a record of `bytes`, read or written after `elapsed`.
+    pub fn new(elapsed: Duration, direction: FrameDirection, bytes: Bytes) -> Record {
+        Record {
+            elapsed,
+            direction,
+            bytes,
+        }
+    }
+
+    /// Returns the time elapsed between the start of the recording and the
+    /// last byte of the record.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns whether the bytes were read or written by the recorded
+    /// connection.
+    pub fn direction(&self) -> FrameDirection {
+        self.direction
+    }
+
+    /// Returns the recorded bytes: a frame including its frame header, the
+    /// client connection preface, or an HTTP/1.1 message preceding the
+    /// frames.
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    fn parse(line: &str) -> Option<Record> {
+        let mut parts = line.split_whitespace();
+        let (elapsed, direction, hex) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(e), Some(d), h) if parts.next().is_none() => (e, d, h.unwrap_or("")),
+            _ => return None,
+        };
+
+        let (secs, micros) = elapsed.split_once('.')?;
+        if micros.len() != 6 {
+            return None;
+        }
+        let elapsed =
+            Duration::from_secs(secs.parse().ok()?) + Duration::from_micros(micros.parse().ok()?);
+
+        let direction = match direction {
+            ">" => FrameDirection::Outbound,
+            "<" => FrameDirection::Inbound,
+            _ => return None,
+        };
+
+        if hex.len() % 2 != 0 || !hex.is_ascii() {
+            return None;
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+
+        Some(Record::new(elapsed, direction, bytes.into()))
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let direction = match self.direction {
+            FrameDirection::Outbound => '>',
+            FrameDirection::Inbound => '<',
+        };
+
+        let mut hex = String::with_capacity(self.bytes.len() * 2);
+        for byte in &self.bytes {
+            write!(hex, "{:02x}", byte)?;
+        }
+
+        write!(
+            fmt,
+            "{}.{:06} {} {}",
+            self.elapsed.as_secs(),
+            self.elapsed.subsec_micros(),
+            direction,
+            hex
+        )
+    }
+}
+
+// ===== impl TranscriptError =====
+
+impl TranscriptError {
+    /// Returns the number of the invalid line, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl error::Error for TranscriptError {}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "invalid transcript record on line {}", self.line)
+    }
+}
+
+// ===== impl Framer =====
+
+impl Framer {
+    fn new() -> Framer {
+        Framer {
+            buf: Vec::new(),
+            at_start: true,
+        }
+    }
+
+    /// Buffers `data`, then calls `emit` with each complete record.
+    fn push(&mut self, data: &[u8], mut emit: impl FnMut(&[u8])) {
+        self.buf.extend_from_slice(data);
+
+        loop {
+            let len = match self.next_len() {
+                Some(len) => len,
+                None => return,
+            };
+
+            emit(&self.buf[..len]);
+            self.buf.drain(..len);
+        }
+    }
+
+    /// Returns the length of the record at the start of the buffer, if it is
+    /// complete.
+    fn next_len(&mut self) -> Option<usize> {
+        let buf = &self.buf[..];
+
+        if self.at_start && !buf.is_empty() {
+            let n = buf.len().min(PREFACE.len());
+            if buf[..n] == PREFACE[..n] {
+                return if n == PREFACE.len() { Some(n) } else { None };
+            }
+
+            // The first frame is a SETTINGS frame, whose length starts with a
+            // zero byte, while HTTP/1.1 messages start with a method or a
+            // version.
+            if buf[0].is_ascii_uppercase() {
+                return buf
+                    .windows(4)
+                    .position(|window| window == b"\r\n\r\n")
+                    .map(|end| end + 4);
+            }
+
+            self.at_start = false;
+        }
+
+        if buf.len() < FRAME_HEADER_LEN {
+            return None;
+        }
+
+        let payload_len = (buf[0] as usize) << 16 | (buf[1] as usize) << 8 | buf[2] as usize;
+        let len = FRAME_HEADER_LEN + payload_len;
+        if buf.len() < len {
+            return None;
+        }
+
+        Some(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    fn split(chunks: &[&[u8]]) -> Vec<Vec<u8>> {
+        let mut framer = Framer::new();
+        let mut records = Vec::new();
+        for chunk in chunks {
+            framer.push(chunk, |bytes| records.push(bytes.to_vec()));
+        }
+        records
+    }
+
+    #[test]
+    fn test_framer_splits_frames() {
+        let settings = [0, 0, 6, 4, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 100];
+        let ack = [0, 0, 0, 4, 1, 0, 0, 0, 0];
+        let mut data = PREFACE.to_vec();
+        data.extend_from_slice(&settings);
+        data.extend_from_slice(&ack);
+
+        let expected = vec![PREFACE.to_vec(), settings.to_vec(), ack.to_vec()];
+        assert_eq!(split(&[&data]), expected);
+
+        // Byte by byte
+        let chunks = data.chunks(1).collect::<Vec<_>>();
+        assert_eq!(split(&chunks), expected);
+    }
+
+    #[test]
+    fn test_framer_splits_upgrade() {
+        let request = b"GET / HTTP/1.1\r\nUpgrade: h2c\r\n\r\n";
+        let settings = [0, 0, 0, 4, 0, 0, 0, 0, 0];
+        let mut data = request.to_vec();
+        data.extend_from_slice(PREFACE);
+        data.extend_from_slice(&settings);
+
+        assert_eq!(
+            split(&[&data[..10], &data[10..]]),
+            vec![request.to_vec(), PREFACE.to_vec(), settings.to_vec()]
+        );
+    }
+
+    /// A sink counting how many times it is flushed.
+    #[derive(Clone, Default)]
+    struct Sink(Arc<Mutex<(Vec<u8>, usize)>>);
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().0.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.lock().unwrap().1 += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_recorder_flushes_on_drop() {
+        let sink = Sink::default();
+        let mut recorder = Recorder::new((), sink.clone());
+        recorder.record(FrameDirection::Outbound, PREFACE);
+        recorder.record(FrameDirection::Inbound, &[0, 0, 0, 4, 1, 0, 0, 0, 0]);
+        assert_eq!(sink.0.lock().unwrap().1, 0);
+
+        recorder.flush_transcript().unwrap();
+        assert_eq!(sink.0.lock().unwrap().1, 1);
+
+        recorder.record(FrameDirection::Inbound, &[0, 0, 0, 4]);
+        drop(recorder);
+
+        let (ref data, flushes) = *sink.0.lock().unwrap();
+        assert_eq!(flushes, 2);
+        let transcript = Transcript::parse(std::str::from_utf8(data).unwrap()).unwrap();
+        assert_eq!(transcript.records().len(), 3);
+        assert_eq!(&transcript.records()[2].bytes()[..], &[0, 0, 0, 4]);
+    }
+
+    #[test]
+    fn test_framer_holds_partial_frame() {
+        let mut framer = Framer::new();
+        framer.push(&[0, 0, 4, 0, 1, 0, 0, 0, 1, b'a'], |_| panic!());
+        assert_eq!(framer.buf.len(), 10);
+    }
+
+    #[test]
+    fn test_transcript_roundtrip() {
+        let transcript = Transcript::new(vec![
+            Record::new(
+                Duration::from_micros(0),
+                FrameDirection::Outbound,
+                Bytes::from_static(PREFACE),
+            ),
+            Record::new(
+                Duration::from_micros(2_000_042),
+                FrameDirection::Inbound,
+                Bytes::from_static(&[0, 0, 0, 4, 1, 0, 0, 0, 0]),
+            ),
+        ]);
+
+        let src = transcript.to_string();
+        assert_eq!(
+            src,
+            "# h2 transcript\n\
+             0.000000 > 505249202a20485454502f322e300d0a0d0a534d0d0a0d0a\n\
+             2.000042 < 000000040100000000\n"
+        );
+        assert_eq!(src.parse::<Transcript>().unwrap(), transcript);
+    }
+
+    #[test]
+    fn test_transcript_parse_errors() {
+        let src = "# h2 transcript\n\n0.000001 > 00\n0.1 > 00\n";
+        assert_eq!(Transcript::parse(src).unwrap_err().line(), 4);
+
+        for line in [
+            "0.000001 - 00",
+            "0.000001 > 0",
+            "0.000001 > zz",
+            "x.000001 > 00",
+        ] {
+            assert!(Transcript::parse(line).is_err(), "{:?}", line);
+        }
+    }
+}
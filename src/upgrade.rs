@@ -0,0 +1,210 @@
+//! HTTP/1.1 upgrade to HTTP/2 over cleartext TCP (RFC 7540 §3.2).
+//!
+//! The client sends its SETTINGS in the `HTTP2-Settings` header of the
+//! upgrade request, as the base64url encoded payload of a SETTINGS frame. Once
+//! the server answers with `101 Switching Protocols`, the request continues as
+//! stream 1, half-closed on the client side.
+
+use crate::frame::{self, Head, Kind, Pseudo, StreamId};
+use crate::hpack::BytesStr;
+
+use bytes::BytesMut;
+use http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use http::{uri, Request};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Returns the value of the `HTTP2-Settings` header carrying `settings`.
+pub(crate) fn encode_settings(settings: &frame::Settings) -> HeaderValue {
+    let mut buf = BytesMut::new();
+    settings.encode(&mut buf);
+
+    // Only the payload is sent, without the frame header.
+    let value = encode_base64url(&buf[frame::HEADER_LEN..]);
+    HeaderValue::from_str(&value).expect("base64url is a valid header value")
+}
+
+/// Decodes the value of an `HTTP2-Settings` header.
+pub(crate) fn decode_settings(value: &HeaderValue) -> Option<frame::Settings> {
+    let payload = decode_base64url(value.as_bytes())?;
+    let head = Head::new(Kind::Settings, 0, StreamId::zero());
+    frame::Settings::load(head, &payload).ok()
+}
+
+/// Converts the HTTP/1.1 request that was upgraded into the HEADERS opening
+/// stream 1.
+///
+/// The request body was sent over HTTP/1.1, so the stream is closed by the
+/// client. Connection-specific header fields are dropped, and the `Host`
+/// header becomes the `:authority` pseudo-header.
+pub(crate) fn request_headers(request: Request<()>) -> frame::Headers {
+    let (parts, ()) = request.into_parts();
+    let mut fields = parts.headers;
+
+    let mut pseudo = Pseudo::request(parts.method, parts.uri, None);
+    if pseudo.scheme.is_none() {
+        pseudo.set_scheme(uri::Scheme::HTTP);
+    }
+
+    if let Some(host) = fields.remove(header::HOST) {
+        if pseudo.authority.is_none() {
+            if let Ok(host) = host.to_str() {
+                pseudo.set_authority(BytesStr::from(host));
+            }
+        }
+    }
+
+    strip_connection_headers(&mut fields);
+    fields.remove(header::CONTENT_LENGTH);
+
+    let mut headers = frame::Headers::new(StreamId::from(1), pseudo, fields);
+    headers.set_end_stream();
+    headers
+}
+
+fn strip_connection_headers(fields: &mut HeaderMap) {
+    // Header fields named in `Connection` only apply to the HTTP/1.1 hop.
+    let named = fields
+        .get_all(header::CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+        .collect::<Vec<_>>();
+    for name in named {
+        fields.remove(name);
+    }
+
+    fields.remove(header::CONNECTION);
+    fields.remove(header::UPGRADE);
+    fields.remove(header::TRANSFER_ENCODING);
+    fields.remove("http2-settings");
+    fields.remove("keep-alive");
+    fields.remove("proxy-connection");
+
+    if fields.get(header::TE).map_or(false, |te| te != "trailers") {
+        fields.remove(header::TE);
+    }
+}
+
+fn encode_base64url(src: &[u8]) -> String {
+    let mut dst = String::with_capacity((src.len() * 4 + 2) / 3);
+
+    for chunk in src.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            dst.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+
+    dst
+}
+
+fn decode_base64url(src: &[u8]) -> Option<Vec<u8>> {
+    // The header is a token68, padding is tolerated though not expected.
+    let end = src.iter().rposition(|&b| b != b'=').map_or(0, |i| i + 1);
+    let src = &src[..end];
+
+    if src.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut dst = Vec::with_capacity(src.len() * 3 / 4);
+
+    for chunk in src.chunks(4) {
+        let mut n = 0u32;
+        for (i, &b) in chunk.iter().enumerate() {
+            let v = ALPHABET.iter().position(|&c| c == b)? as u32;
+            n |= v << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            dst.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+
+    Some(dst)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn base64url_roundtrip() {
+        let cases: &[(&[u8], &str)] = &[
+            (b"", ""),
+            (b"f", "Zg"),
+            (b"fo", "Zm8"),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg"),
+            (&[0xfb, 0xff, 0xbf], "-_-_"),
+        ];
+
+        for &(bytes, encoded) in cases {
+            assert_eq!(encode_base64url(bytes), encoded);
+            assert_eq!(decode_base64url(encoded.as_bytes()).unwrap(), bytes);
+        }
+
+        assert_eq!(decode_base64url(b"Zm8=").unwrap(), b"fo");
+        assert!(decode_base64url(b"Zm9vY").is_none());
+        assert!(decode_base64url(b"Zm+v").is_none());
+    }
+
+    #[test]
+    fn settings_roundtrip() {
+        let mut settings = frame::Settings::default();
+        settings.set_max_concurrent_streams(Some(100));
+        settings.set_initial_window_size(Some(65_535 * 2));
+
+        let value = encode_settings(&settings);
+        // SETTINGS_MAX_CONCURRENT_STREAMS (0x3) = 100,
+        // SETTINGS_INITIAL_WINDOW_SIZE (0x4) = 131070.
+        assert_eq!(value, "AAMAAABkAAQAAf_-");
+
+        let decoded = decode_settings(&value).unwrap();
+        assert_eq!(decoded.max_concurrent_streams(), Some(100));
+        assert_eq!(decoded.initial_window_size(), Some(65_535 * 2));
+
+        assert!(decode_settings(&HeaderValue::from_static("AAMAAABk!")).is_none());
+        // Not a multiple of 6 bytes.
+        assert!(decode_settings(&HeaderValue::from_static("AAMA")).is_none());
+    }
+
+    #[test]
+    fn request_headers_drop_connection_fields() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/upload?x=1")
+            .header("host", "example.com")
+            .header("connection", "Upgrade, HTTP2-Settings, x-hop")
+            .header("upgrade", "h2c")
+            .header("http2-settings", "")
+            .header("x-hop", "1")
+            .header("te", "gzip")
+            .header("content-length", "5")
+            .header("accept", "*/*")
+            .body(())
+            .unwrap();
+
+        let headers = request_headers(request);
+        assert_eq!(headers.stream_id(), 1);
+        assert!(headers.is_end_stream());
+
+        let (pseudo, fields) = headers.into_parts();
+        assert_eq!(pseudo.method, Some(http::Method::POST));
+        assert_eq!(pseudo.scheme.as_ref().map(|s| s.as_str()), Some("http"));
+        assert_eq!(
+            pseudo.authority.as_ref().map(|s| s.as_str()),
+            Some("example.com")
+        );
+        assert_eq!(
+            pseudo.path.as_ref().map(|s| s.as_str()),
+            Some("/upload?x=1")
+        );
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields["accept"], "*/*");
+    }
+}
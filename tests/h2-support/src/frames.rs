@@ -51,6 +51,13 @@ where
     frame::WindowUpdate::new(id.into(), sz)
 }
 
+pub fn priority_update<T>(id: T, field_value: &'static str) -> frame::PriorityUpdate
+where
+    T: Into<StreamId>,
+{
+    frame::PriorityUpdate::new(id.into(), Bytes::from_static(field_value.as_bytes()))
+}
+
 pub fn go_away<T>(id: T) -> Mock<frame::GoAway>
 where
     T: Into<StreamId>,
@@ -10,6 +10,9 @@ pub use h2::*;
 // Re-export mock
 pub use super::mock::{self, idle_ms};
 
+// Re-export the transcript replay
+pub use super::replay::Replay;
+
 // Re-export frames helpers
 pub use super::frames;
 
@@ -0,0 +1,162 @@
+use crate::mock::Handle;
+
+use h2::transcript::{Record, Transcript};
+use h2::FrameDirection;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::{self, Instant};
+
+use std::path::Path;
+
+const FRAME_HEADER_LEN: usize = 9;
+
+/// Plays back one side of a recorded transcript.
+///
+/// By default, the replay plays the peer of the recorded connection: it sends
+/// the records the connection read, and expects the connection under test to
+/// write the records the recorded connection wrote.
+///
+/// Written frames are matched on their frame header, without the length, so
+/// that changes to header compression or to SETTINGS values do not break
+/// replays. A frame may be written before the records recorded ahead of it
+/// were sent, as the order of the two directions depends on timing. The
+/// connection preface and HTTP/1.1 messages must match exactly.
+#[derive(Debug)]
+pub struct Replay {
+    records: Vec<Record>,
+    reversed: bool,
+    timing: bool,
+}
+
+impl Replay {
+    pub fn new(transcript: Transcript) -> Replay {
+        Replay {
+            records: transcript.records().to_vec(),
+            reversed: false,
+            timing: false,
+        }
+    }
+
+    /// Loads the transcript in the file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Replay {
+        let path = path.as_ref();
+        let transcript = Transcript::open(path)
+            .unwrap_or_else(|e| panic!("failed to open {}: {}", path.display(), e));
+        Replay::new(transcript)
+    }
+
+    /// Plays the recorded connection itself, instead of its peer.
+    pub fn reversed(mut self) -> Self {
+        self.reversed = !self.reversed;
+        self
+    }
+
+    /// Waits before sending each record until as much time has elapsed as
+    /// when it was recorded.
+    ///
+    /// Combined with `tokio::time::pause`, this replays timeouts and
+    /// keep-alive pings deterministically.
+    pub fn with_timing(mut self) -> Self {
+        self.timing = true;
+        self
+    }
+
+    /// Plays back the transcript over `handle`.
+    ///
+    /// The handle must not have read frames before, which would leave bytes
+    /// buffered in its codec.
+    pub async fn play(self, handle: &mut Handle) {
+        let start = Instant::now();
+        let send = if self.reversed {
+            FrameDirection::Outbound
+        } else {
+            FrameDirection::Inbound
+        };
+
+        // Frame headers, without the length, read ahead of their record.
+        let mut early = Vec::new();
+
+        for (i, record) in self.records.iter().enumerate() {
+            if record.direction() == send {
+                if self.timing {
+                    time::sleep_until(start + record.elapsed()).await;
+                }
+                handle.write_all(record.bytes()).await.unwrap();
+                continue;
+            }
+
+            let head = match frame_header(record.bytes()) {
+                Some(head) => head,
+                None => {
+                    expect_bytes(handle, i, record.bytes()).await;
+                    continue;
+                }
+            };
+
+            if let Some(pos) = early.iter().position(|early| early == head) {
+                early.remove(pos);
+                continue;
+            }
+
+            loop {
+                let actual = read_frame_header(handle).await;
+                if actual == head {
+                    break;
+                }
+
+                // Accept a frame recorded later, if it is not already
+                // expected by frames read ahead.
+                let later = self.records[i + 1..]
+                    .iter()
+                    .filter(|later| later.direction() != send)
+                    .filter(|later| frame_header(later.bytes()) == Some(&actual[..]))
+                    .count();
+                let read = early.iter().filter(|early| **early == actual).count();
+                assert!(
+                    read < later,
+                    "record {}: expected frame header {:02x?}, got {:02x?}",
+                    i,
+                    head,
+                    actual
+                );
+                early.push(actual);
+            }
+        }
+    }
+}
+
+/// Reads exactly the bytes `expected` from `handle`.
+async fn expect_bytes(handle: &mut Handle, i: usize, expected: &[u8]) {
+    let mut actual = vec![0; expected.len()];
+    handle.read_exact(&mut actual).await.unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&actual),
+        String::from_utf8_lossy(expected),
+        "record {} differs",
+        i
+    );
+}
+
+/// Reads a frame from `handle` and returns its frame header, without the
+/// length.
+async fn read_frame_header(handle: &mut Handle) -> Vec<u8> {
+    let mut head = [0; FRAME_HEADER_LEN];
+    handle.read_exact(&mut head).await.unwrap();
+
+    let len = (head[0] as usize) << 16 | (head[1] as usize) << 8 | head[2] as usize;
+    let mut payload = vec![0; len];
+    handle.read_exact(&mut payload).await.unwrap();
+
+    head[3..].to_vec()
+}
+
+/// Returns the frame header of `record`, without the length, if it holds a
+/// frame.
+fn frame_header(record: &[u8]) -> Option<&[u8]> {
+    let head = record.get(..FRAME_HEADER_LEN)?;
+    let len = (head[0] as usize) << 16 | (head[1] as usize) << 8 | head[2] as usize;
+    if record.len() != FRAME_HEADER_LEN + len {
+        return None;
+    }
+    Some(&head[3..])
+}
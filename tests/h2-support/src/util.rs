@@ -23,7 +23,7 @@ pub async fn yield_once() {
             Poll::Ready(())
         } else {
             yielded = true;
-            cx.waker().clone().wake();
+            cx.waker().wake_by_ref();
             Poll::Pending
         }
     })
@@ -0,0 +1,127 @@
+use futures::StreamExt;
+use h2_support::prelude::*;
+use http::HeaderValue;
+
+#[tokio::test]
+async fn client_recv_altsvc() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+
+        // Invalid frames are ignored
+        srv.send_frame(AltSvc::new(0, Bytes::new(), "h3=\":443\"".into()))
+            .await;
+        srv.send_frame(AltSvc::new(
+            1,
+            "https://example.com".into(),
+            "h3=\":443\"".into(),
+        ))
+        .await;
+
+        srv.send_frame(AltSvc::new(
+            0,
+            "https://example.com".into(),
+            "h3=\":8443\"".into(),
+        ))
+        .await;
+        srv.send_frame(AltSvc::new(1, Bytes::new(), "h3=\":443\"".into()))
+            .await;
+        srv.send_frame(frames::headers(1).response(200).eos()).await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut conn) = client::handshake(io).await.expect("handshake");
+
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = conn.drive(response).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let altsvc = response.extensions().get::<AltSvc>().expect("altsvc");
+        assert_eq!(altsvc.stream_id(), response.body().stream_id());
+        assert_eq!(altsvc.field_value(), "h3=\":443\"");
+
+        let altsvc = poll_fn(|cx| conn.poll_altsvc(cx)).await.expect("altsvc");
+        assert_eq!(altsvc.origin(), "https://example.com");
+        assert_eq!(altsvc.field_value(), "h3=\":8443\"");
+
+        drop(client);
+        conn.await.expect("client");
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn server_send_altsvc() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        let settings = client.assert_server_handshake().await;
+        assert_default_settings!(settings);
+
+        // Servers ignore ALTSVC frames
+        client
+            .send_frame(AltSvc::new(0, "https://a.com".into(), "h2=\":443\"".into()))
+            .await;
+        client
+            .send_frame(
+                frames::headers(1)
+                    .request("GET", "https://example.com/")
+                    .eos(),
+            )
+            .await;
+
+        client
+            .recv_frame(AltSvc::new(
+                0,
+                "https://example.com".into(),
+                "h3=\":443\"".into(),
+            ))
+            .await;
+        client
+            .recv_frame(AltSvc::new(1, Bytes::new(), "h3=\":8443\"".into()))
+            .await;
+        client
+            .recv_frame(frames::headers(1).response(200).eos())
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::handshake(io).await.expect("handshake");
+
+        let field_value = HeaderValue::from_static("h3=\":443\"");
+        for origin in [
+            "example.com",
+            "https://example.com/",
+            "https://a@example.com",
+        ] {
+            assert!(srv.send_altsvc(origin, &field_value).is_err());
+        }
+        srv.send_altsvc("https://example.com", &field_value)
+            .unwrap();
+
+        let (_, mut stream) = srv.next().await.unwrap().unwrap();
+        stream
+            .send_altsvc(&HeaderValue::from_static("h3=\":8443\""))
+            .unwrap();
+        stream.send_response(Response::new(()), true).unwrap();
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
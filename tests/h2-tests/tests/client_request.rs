@@ -1993,6 +1993,230 @@ async fn server_drop_connection_after_go_away() {
     join(srv, h2).await;
 }
 
+#[tokio::test]
+async fn profile_configures_settings_and_connection_window() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_eq!(settings.header_table_size(), Some(65_536));
+        assert_eq!(settings.is_push_enabled(), Some(false));
+        assert_eq!(settings.initial_window_size(), Some(6_291_456));
+        assert_eq!(settings.max_header_list_size(), Some(262_144));
+        assert_eq!(settings.max_concurrent_streams(), None);
+        assert_eq!(settings.max_frame_size(), None);
+        srv.recv_frame(frames::window_update(0, 15_663_105)).await;
+    };
+
+    let h2 = async move {
+        let (_client, h2) = client::Builder::new()
+            .max_concurrent_streams(100)
+            .profile(&client::Profile::chrome())
+            .handshake::<_, Bytes>(io)
+            .await
+            .unwrap();
+        h2.await.unwrap();
+    };
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn request_extensions_override_pseudo_order_and_stream_dependency() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let default_dep = frame::StreamDependency::new(0.into(), 255, true);
+    let request_dep = frame::StreamDependency::new(1.into(), 21, false);
+
+    let srv = async move {
+        srv.assert_client_handshake().await;
+
+        for (id, dep, order) in [
+            (1, default_dep, [b'm', b'a', b's', b'p']),
+            (3, request_dep, [b'm', b's', b'p', b'a']),
+        ] {
+            let frame = srv.next().await.unwrap().unwrap();
+            let headers = match frame {
+                frame::Frame::Headers(headers) => headers,
+                frame => panic!("unexpected frame; frame={:?}", frame),
+            };
+            assert_eq!(headers.stream_id(), StreamId::from(id));
+            assert_eq!(headers.stream_dependency(), Some(&dep));
+
+            let (pseudo, _) = headers.into_parts();
+            let letters: Vec<u8> = pseudo
+                .order
+                .into_iter()
+                .take(4)
+                .map(|id| match id {
+                    frame::PseudoId::Method => b'm',
+                    frame::PseudoId::Authority => b'a',
+                    frame::PseudoId::Scheme => b's',
+                    frame::PseudoId::Path => b'p',
+                    id => panic!("unexpected pseudo-header; id={:?}", id),
+                })
+                .collect();
+            assert_eq!(letters, order);
+
+            srv.send_frame(frames::headers(id).response(200).eos())
+                .await;
+        }
+    };
+
+    let h2 = async move {
+        let (mut client, mut h2) = client::Builder::new()
+            .headers_pseudo_order(
+                frame::PseudoOrder::builder()
+                    .extend([
+                        frame::PseudoId::Method,
+                        frame::PseudoId::Authority,
+                        frame::PseudoId::Scheme,
+                        frame::PseudoId::Path,
+                    ])
+                    .build(),
+            )
+            .headers_stream_dependency(default_dep)
+            .handshake::<_, Bytes>(io)
+            .await
+            .unwrap();
+
+        let request = Request::get("https://example.com/").body(()).unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        h2.drive(response).await.unwrap();
+
+        let mut request = Request::get("https://example.com/").body(()).unwrap();
+        request.extensions_mut().insert(
+            frame::PseudoOrder::builder()
+                .extend([
+                    frame::PseudoId::Method,
+                    frame::PseudoId::Scheme,
+                    frame::PseudoId::Path,
+                    frame::PseudoId::Authority,
+                ])
+                .build(),
+        );
+        request.extensions_mut().insert(request_dep);
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = h2.drive(response).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    };
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn encoding_policy_headers_decode() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        srv.assert_client_handshake().await;
+
+        for id in [1, 3] {
+            srv.recv_frame(
+                frames::headers(id)
+                    .request("GET", "https://example.com/search?q=h2")
+                    .field("cookie", "a=b")
+                    .field("user-agent", "h2-test")
+                    .eos(),
+            )
+            .await;
+            srv.send_frame(frames::headers(id).response(200).eos())
+                .await;
+        }
+    };
+
+    let h2 = async move {
+        let (mut client, mut h2) = client::Builder::new()
+            .encoding_policy(h2::FirefoxPolicy)
+            .handshake::<_, Bytes>(io)
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            let request = Request::get("https://example.com/search?q=h2")
+                .header("cookie", "a=b")
+                .header("user-agent", "h2-test")
+                .body(())
+                .unwrap();
+            let (response, _) = client.send_request(request, true).unwrap();
+            let response = h2.drive(response).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    };
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn preface_sequence_frames_sent_in_order() {
+    use tokio::io::AsyncReadExt;
+
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let priority =
+        frame::Priority::new(3.into(), frame::StreamDependency::new(0.into(), 200, false));
+
+    let srv = async move {
+        srv.send_frame(frames::settings()).await;
+        srv.read_preface().await.unwrap();
+
+        // SETTINGS comes first
+        let mut head = [0; 9];
+        srv.read_exact(&mut head).await.unwrap();
+        assert_eq!(head[3], 4);
+        let mut payload = vec![0; head[2] as usize];
+        srv.read_exact(&mut payload).await.unwrap();
+
+        // Then the GREASE frame
+        let mut grease = [0; 11];
+        srv.read_exact(&mut grease).await.unwrap();
+        assert_eq!(grease, [0, 0, 2, 0x2a, 0, 0, 0, 0, 0, b'h', b'i']);
+
+        srv.recv_frame(frames::window_update(0, 1_000)).await;
+        srv.recv_frame(frame::Priority::new(
+            3.into(),
+            frame::StreamDependency::new(0.into(), 200, false),
+        ))
+        .await;
+
+        srv.recv_frame(frames::settings_ack()).await;
+        srv.send_frame(frames::settings_ack()).await;
+
+        // Neither the WINDOW_UPDATE nor the PRIORITY frame is sent again
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frames::headers(1).response(200).eos()).await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut h2) = client::Builder::new()
+            .initial_connection_window_size(65_535 + 1_000)
+            .priorities(frame::Priorities::builder().push(priority).build())
+            .preface_sequence(
+                client::PrefaceSequence::builder()
+                    .settings()
+                    .unknown(0x2a, 0, 0, "hi")
+                    .window_update()
+                    .priorities()
+                    .build(),
+            )
+            .handshake::<_, Bytes>(io)
+            .await
+            .unwrap();
+
+        let request = Request::get("https://example.com/").body(()).unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = h2.drive(response).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    };
+    join(srv, h2).await;
+}
+
 const SETTINGS: &[u8] = &[0, 0, 0, 4, 0, 0, 0, 0, 0];
 const SETTINGS_ACK: &[u8] = &[0, 0, 0, 4, 1, 0, 0, 0, 0];
 
@@ -2009,3 +2233,283 @@ impl MockH2 for mock_io::Builder {
             .read(SETTINGS_ACK)
     }
 }
+
+#[tokio::test]
+async fn graceful_shutdown() {
+    use futures::channel::oneshot;
+
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+    let (tx, rx) = oneshot::channel();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+        tx.send(()).unwrap();
+
+        srv.recv_frame(frames::go_away(StreamId::MAX)).await;
+        srv.recv_frame(frames::ping(frame::Ping::SHUTDOWN)).await;
+        srv.send_frame(frames::ping(frame::Ping::SHUTDOWN).pong())
+            .await;
+        srv.recv_frame(frames::go_away(0)).await;
+
+        // The open stream completes before the connection is closed
+        srv.send_frame(frames::headers(1).response(200).eos()).await;
+        srv.recv_eof().await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut conn) = client::handshake(io).await.expect("handshake");
+
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        conn.drive(rx).await.unwrap();
+
+        conn.graceful_shutdown();
+
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let err = client.send_request(request, true).unwrap_err();
+        assert_eq!(err.to_string(), "user error: connection is shutting down");
+
+        let response = conn.drive(response).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        drop(response);
+
+        // The connection closes without dropping `client`
+        conn.await.expect("client");
+        drop(client);
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn abrupt_shutdown() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+        srv.recv_frame(frames::go_away(0).reason(Reason::CANCEL).data("bye"))
+            .await;
+        srv.recv_eof().await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut conn) = client::handshake(io).await.expect("handshake");
+
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        conn.drive(h2_support::util::yield_once()).await;
+
+        conn.abrupt_shutdown_with_debug_data(Reason::CANCEL, "bye".into());
+
+        let err = conn.drive(response).await.unwrap_err();
+        assert_eq!(err.reason(), Some(Reason::CANCEL));
+        conn.await.expect("client");
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn go_away_hands_back_refused_request() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+        srv.recv_frame(frames::headers(3).request("POST", "https://example.com/"))
+            .await;
+        srv.recv_frame(frames::data(3, "hello").eos()).await;
+        srv.send_frame(frames::go_away(1)).await;
+        srv.send_frame(frames::headers(1).response(200).eos()).await;
+        srv.recv_frame(frames::go_away(0)).await;
+        srv.recv_eof().await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut conn) = client::handshake(io).await.expect("handshake");
+        client.set_retain_requests(true);
+
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let (response1, _) = client.send_request(request, true).unwrap();
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let (mut response3, mut stream) = client.send_request(request, false).unwrap();
+        response3.set_replay_body(Bytes::from_static(b"hello"));
+        stream.send_data("hello".into(), true).unwrap();
+
+        let mut err = conn.drive(response3).await.unwrap_err();
+        assert!(err.is_go_away());
+        assert!(err.is_retryable());
+        assert_eq!(err.last_stream_id().map(|id| id.as_u32()), Some(1));
+        let request = err.take_request().expect("request");
+        assert_eq!(request.method(), Method::POST);
+        assert_eq!(request.uri(), "https://example.com/");
+        assert_eq!(request.body().as_deref(), Some(&b"hello"[..]));
+
+        // Requests sent after the GOAWAY are refused too
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let mut err = client.send_request(request, true).unwrap_err();
+        assert!(err.is_retryable());
+        let request = err.take_request().expect("request");
+        assert_eq!(request.body(), &None);
+
+        let response = conn.drive(response1).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        drop(response);
+
+        conn.await.expect("client");
+        drop(client);
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn refused_stream_is_retryable() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frames::reset(1).refused()).await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut conn) = client::handshake(io).await.expect("handshake");
+
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+
+        let mut err = conn.drive(response).await.unwrap_err();
+        assert!(err.is_retryable());
+        assert_eq!(err.last_stream_id(), None);
+        // Requests are not retained by default
+        assert!(err.take_request().is_none());
+
+        drop(client);
+        conn.await.expect("client");
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn recv_informational_responses() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(
+            frames::headers(1)
+                .response(103)
+                .field("link", "</style.css>; rel=preload"),
+        )
+        .await;
+        srv.send_frame(frames::headers(1).response(103)).await;
+        srv.send_frame(frames::headers(1).response(200).eos()).await;
+
+        srv.recv_frame(
+            frames::headers(3)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frames::headers(3).response(100)).await;
+        srv.send_frame(frames::headers(3).response(204).eos()).await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut conn) = client::handshake(io).await.expect("handshake");
+
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let (mut response, _) = client.send_request(request, true).unwrap();
+
+        let informational = conn
+            .drive(response.informational_responses().collect::<Vec<_>>())
+            .await;
+        assert_eq!(informational.len(), 2);
+        let early_hints = informational[0].as_ref().unwrap();
+        assert_eq!(early_hints.status(), StatusCode::EARLY_HINTS);
+        assert_eq!(early_hints.headers()["link"], "</style.css>; rel=preload");
+
+        let response = conn.drive(response).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Informational responses which are not polled are dropped
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = conn.drive(response).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        drop(client);
+        conn.await.expect("client");
+    };
+
+    join(srv, h2).await;
+}
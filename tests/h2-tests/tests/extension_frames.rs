@@ -0,0 +1,103 @@
+use futures::StreamExt;
+use h2_support::prelude::*;
+use std::pin::Pin;
+
+#[tokio::test]
+async fn server_recv_and_send_extension_frames() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        let settings = client.assert_server_handshake().await;
+        assert_default_settings!(settings);
+        client.codec_mut().set_extension_frame_types(vec![0xf2]);
+
+        // Not registered by the server, ignored
+        client
+            .send_frame(ExtensionFrame::new(0xf1, 0, 0, "ignored".into()))
+            .await;
+        client
+            .send_frame(ExtensionFrame::new(0xf0, 1, 3, "hello".into()))
+            .await;
+        client
+            .recv_frame(ExtensionFrame::new(0xf2, 0, 0, "reply".into()))
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::Builder::new()
+            .extension_frame_type(0xf0)
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        let frame = poll_fn(|cx| {
+            let _ = srv.poll_closed(cx);
+            srv.poll_extension_frame(cx)
+        })
+        .await
+        .expect("extension frame");
+
+        assert_eq!(frame.kind(), 0xf0);
+        assert_eq!(frame.flags(), 1);
+        assert_eq!(u32::from(frame.stream_id()), 3);
+        assert_eq!(frame.payload(), "hello");
+
+        srv.send_extension_frame(0xf2, 0, 0, "reply".into())
+            .unwrap();
+        assert!(srv.next().await.is_none());
+
+        let frame = poll_fn(|cx| srv.poll_extension_frame(cx)).await;
+        assert!(frame.is_none());
+    };
+
+    join(client, srv).await;
+}
+
+#[tokio::test]
+async fn client_recv_and_send_extension_frames() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.codec_mut().set_extension_frame_types(vec![0xf3]);
+
+        srv.send_frame(ExtensionFrame::new(0xf0, 0, 0, "hello".into()))
+            .await;
+        srv.recv_frame(ExtensionFrame::new(0xf3, 0, 0, "reply".into()))
+            .await;
+    };
+
+    let h2 = async move {
+        let (client, mut conn) = client::Builder::new()
+            .extension_frame_type(0xf0)
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        let frame = poll_fn(|cx| {
+            let _ = Pin::new(&mut conn).poll(cx);
+            conn.poll_extension_frame(cx)
+        })
+        .await
+        .expect("extension frame");
+
+        assert_eq!(frame.kind(), 0xf0);
+        assert_eq!(frame.payload(), "hello");
+
+        // PING is not an extension frame type
+        let err = conn
+            .send_extension_frame(0x6, 0, 0, Bytes::new())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "user error: unexpected frame type");
+
+        conn.send_extension_frame(0xf3, 0, 0, "reply".into())
+            .unwrap();
+        drop(client);
+        conn.await.expect("client");
+    };
+
+    join(srv, h2).await;
+}
@@ -1989,3 +1989,152 @@ async fn reclaim_reserved_capacity() {
 
     join(mock, h2).await;
 }
+
+#[tokio::test]
+async fn adaptive_window_grows() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://http2.akamai.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frames::headers(1).response(200)).await;
+        for _ in 0..3 {
+            srv.send_frame(frames::data(1, vec![0u8; 16_384])).await;
+        }
+
+        srv.recv_frame(frames::ping(frame::Ping::BDP)).await;
+        srv.send_frame(frames::ping(frame::Ping::BDP).pong()).await;
+
+        // The windows grow to twice the DATA received within the round trip
+        let settings = loop {
+            match srv.next().await.unwrap().unwrap() {
+                frame::Frame::Settings(settings) => break settings,
+                frame::Frame::WindowUpdate(_) => {}
+                frame => panic!("unexpected frame; frame={:?}", frame),
+            }
+        };
+        assert_eq!(settings.initial_window_size(), Some(98_304));
+        srv.send_frame(frames::settings_ack()).await;
+        srv.send_frame(frames::data(1, vec![]).eos()).await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut conn) = client::Builder::new()
+            .adaptive_window(1024 * 1024)
+            .handshake::<_, Bytes>(io)
+            .await
+            .unwrap();
+
+        let request = Request::builder()
+            .uri("https://http2.akamai.com/")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = conn.drive(response).await.unwrap();
+        let body = conn.drive(util::concat(response.into_body())).await;
+        assert_eq!(body.unwrap().len(), 3 * 16_384);
+
+        assert_eq!(conn.local_settings().initial_window_size(), Some(98_304));
+
+        drop(client);
+        conn.await.unwrap();
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn adaptive_window_keeps_larger_connection_window() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(frames::window_update(0, 262_144 - 65_535))
+            .await;
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://http2.akamai.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frames::headers(1).response(200)).await;
+        for _ in 0..3 {
+            srv.send_frame(frames::data(1, vec![0u8; 16_384])).await;
+        }
+
+        srv.recv_frame(frames::ping(frame::Ping::BDP)).await;
+        srv.send_frame(frames::ping(frame::Ping::BDP).pong()).await;
+
+        let settings = loop {
+            match srv.next().await.unwrap().unwrap() {
+                frame::Frame::Settings(settings) => break settings,
+                frame::Frame::WindowUpdate(frame) => {
+                    assert_ne!(frame.stream_id(), 0, "unexpected connection WINDOW_UPDATE");
+                }
+                frame => panic!("unexpected frame; frame={:?}", frame),
+            }
+        };
+        assert_eq!(settings.initial_window_size(), Some(98_304));
+        srv.send_frame(frames::settings_ack()).await;
+
+        // Once a third of the 256 KiB connection window is consumed, it is
+        // released, which would not happen had it been lowered to 96 KiB.
+        for _ in 0..3 {
+            srv.send_frame(frames::data(1, vec![0u8; 16_384])).await;
+        }
+
+        let update = async {
+            loop {
+                match srv.next().await.unwrap().unwrap() {
+                    frame::Frame::WindowUpdate(frame) if frame.stream_id() == 0 => break,
+                    frame::Frame::WindowUpdate(_) | frame::Frame::Ping(_) => {}
+                    frame => panic!("unexpected frame; frame={:?}", frame),
+                }
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), update)
+            .await
+            .expect("connection WINDOW_UPDATE");
+
+        srv.send_frame(frames::data(1, vec![]).eos()).await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut conn) = client::Builder::new()
+            .initial_connection_window_size(262_144)
+            .adaptive_window(1024 * 1024)
+            .handshake::<_, Bytes>(io)
+            .await
+            .unwrap();
+
+        let request = Request::builder()
+            .uri("https://http2.akamai.com/")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = conn.drive(response).await.unwrap();
+
+        let mut body = response.into_body();
+        let mut len = 0;
+        while let Some(data) = conn.drive(body.data()).await {
+            let data = data.unwrap();
+            len += data.len();
+            body.flow_control().release_capacity(data.len()).unwrap();
+        }
+        assert_eq!(len, 6 * 16_384);
+
+        drop(client);
+        conn.await.unwrap();
+    };
+
+    join(srv, h2).await;
+}
@@ -0,0 +1,206 @@
+use futures::StreamExt;
+use h2::{FrameDirection, FrameObserver, ObservedFrame};
+use h2_support::prelude::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Observed {
+    direction: FrameDirection,
+    frame_type: u8,
+    flags: u8,
+    stream_id: u32,
+    payload_len: usize,
+    pad_len: Option<u8>,
+    payload: Option<Vec<u8>>,
+    fields: Option<Vec<(String, String)>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<Observed>>>);
+
+impl Recorder {
+    fn frames(&self, direction: FrameDirection) -> Vec<Observed> {
+        let frames = self.0.lock().unwrap();
+        frames
+            .iter()
+            .filter(|frame| frame.direction == direction)
+            .cloned()
+            .collect()
+    }
+}
+
+impl FrameObserver for Recorder {
+    fn on_frame(&self, frame: &ObservedFrame<'_>) {
+        let fields = frame.fields().map(|fields| {
+            fields
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        String::from_utf8_lossy(name).into_owned(),
+                        String::from_utf8_lossy(value).into_owned(),
+                    )
+                })
+                .collect()
+        });
+
+        self.0.lock().unwrap().push(Observed {
+            direction: frame.direction(),
+            frame_type: frame.frame_type(),
+            flags: frame.flags(),
+            stream_id: frame.stream_id().as_u32(),
+            payload_len: frame.payload_len(),
+            pad_len: frame.pad_len(),
+            payload: frame.payload().map(|payload| payload.to_vec()),
+            fields,
+        });
+    }
+}
+
+fn field(name: &str, value: &str) -> (String, String) {
+    (name.to_string(), value.to_string())
+}
+
+#[tokio::test]
+async fn client_observes_frames() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+    let recorder = Recorder::default();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://http2.akamai.com/")
+                .field("x-custom", "1")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frames::headers(1).response(200)).await;
+        // A padded DATA frame ending the stream
+        srv.send_bytes(&[0, 0, 5, 0, 0x9, 0, 0, 0, 1, 2, b'h', b'i', 0, 0])
+            .await;
+        srv.recv_frame(frames::go_away(0)).await;
+        srv.recv_eof().await;
+    };
+
+    let observer = recorder.clone();
+    let h2 = async move {
+        let (mut client, mut conn) = client::Builder::new()
+            .frame_observer(observer)
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        let request = Request::builder()
+            .uri("https://http2.akamai.com/")
+            .header("x-custom", "1")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = conn.drive(response).await.unwrap();
+        let mut body = response.into_body();
+        assert_eq!(conn.drive(body.data()).await.unwrap().unwrap(), "hi");
+
+        drop(client);
+        drop(body);
+        conn.await.expect("client");
+    };
+
+    join(srv, h2).await;
+
+    let sent = recorder.frames(FrameDirection::Outbound);
+    assert_eq!(
+        sent.iter().map(|f| f.frame_type).collect::<Vec<_>>(),
+        [0x4, 0x4, 0x1, 0x7]
+    );
+    let headers = &sent[2];
+    assert_eq!(headers.stream_id, 1);
+    assert_eq!(headers.flags, 0x5);
+    let fields = headers.fields.as_ref().unwrap();
+    assert_eq!(fields.len(), 5);
+    assert_eq!(fields[0], field(":method", "GET"));
+    assert!(fields.contains(&field(":authority", "http2.akamai.com")));
+    assert_eq!(fields[4], field("x-custom", "1"));
+    assert!(sent[0].fields.is_none());
+    assert_eq!(sent[1].payload, Some(vec![]));
+
+    let received = recorder.frames(FrameDirection::Inbound);
+    assert_eq!(
+        received.iter().map(|f| f.frame_type).collect::<Vec<_>>(),
+        [0x4, 0x4, 0x1, 0x0]
+    );
+    assert_eq!(received[2].fields, Some(vec![field(":status", "200")]));
+    assert_eq!(
+        received[3],
+        Observed {
+            direction: FrameDirection::Inbound,
+            frame_type: 0x0,
+            flags: 0x9,
+            stream_id: 1,
+            payload_len: 5,
+            pad_len: Some(2),
+            payload: Some(vec![2, b'h', b'i', 0, 0]),
+            fields: None,
+        }
+    );
+}
+
+#[tokio::test]
+async fn server_observes_continuation_frames() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+    let recorder = Recorder::default();
+
+    let client = async move {
+        let settings = client.assert_server_handshake().await;
+        assert_default_settings!(settings);
+        // The mock splits the header block into CONTINUATION frames
+        client
+            .send_frame(
+                frames::headers(1)
+                    .request("GET", "https://example.com/")
+                    .field("a", "b".repeat(30_000))
+                    .eos(),
+            )
+            .await;
+        client
+            .recv_frame(frames::headers(1).response(200).eos())
+            .await;
+    };
+
+    let observer = recorder.clone();
+    let srv = async move {
+        let mut srv = server::Builder::new()
+            .frame_observer(observer)
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        let (_, mut stream) = srv.next().await.unwrap().unwrap();
+        stream.send_response(Response::new(()), true).unwrap();
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+
+    let received = recorder.frames(FrameDirection::Inbound);
+    let block = received
+        .iter()
+        .filter(|f| f.stream_id == 1)
+        .collect::<Vec<_>>();
+    assert_eq!(block.len(), 2);
+    assert_eq!(block[0].frame_type, 0x1);
+    assert_eq!(block[0].flags, 0x1);
+    assert_eq!(block[0].fields, None);
+    assert_eq!(block[1].frame_type, 0x9);
+    assert_eq!(block[1].flags, 0x4);
+    let fields = block[1].fields.as_ref().unwrap();
+    assert_eq!(fields.len(), 5);
+    assert_eq!(fields[4], field("a", &"b".repeat(30_000)));
+
+    let sent = recorder.frames(FrameDirection::Outbound);
+    let headers = sent.iter().find(|f| f.frame_type == 0x1).unwrap();
+    assert_eq!(headers.fields, Some(vec![field(":status", "200")]));
+}
@@ -0,0 +1,86 @@
+use futures::StreamExt;
+use h2_support::prelude::*;
+
+#[tokio::test]
+async fn client_recv_origin() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frame::Origin::new(vec![
+            "https://a.com".into(),
+            "https://B.com:8443".into(),
+            "http://c.com".into(),
+        ]))
+        .await;
+        srv.send_frame(frames::headers(1).response(200).eos()).await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut conn) = client::handshake(io).await.expect("handshake");
+        let authority = |s: &'static str| uri::Authority::from_static(s);
+
+        assert!(!client.is_authoritative_for(&authority("a.com")));
+
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        conn.drive(response).await.unwrap();
+
+        assert!(client.is_authoritative_for(&authority("a.com")));
+        assert!(client.is_authoritative_for(&authority("A.com:443")));
+        assert!(client.is_authoritative_for(&authority("b.com:8443")));
+        assert!(!client.is_authoritative_for(&authority("b.com")));
+        assert!(!client.is_authoritative_for(&authority("c.com")));
+        assert!(!client.is_authoritative_for(&authority("example.com")));
+
+        drop(client);
+        conn.await.expect("client");
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn server_send_origin() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        let settings = client.assert_server_handshake().await;
+        assert_default_settings!(settings);
+
+        // Servers ignore ORIGIN frames
+        client
+            .send_frame(frame::Origin::new(vec!["https://a.com".into()]))
+            .await;
+        client
+            .recv_frame(frame::Origin::new(vec![
+                "https://a.com".into(),
+                "https://b.com:8443".into(),
+            ]))
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::handshake(io).await.expect("handshake");
+
+        assert!(srv.send_origin(&["https://a.com", "b.com"]).is_err());
+        srv.send_origin(&["https://a.com", "https://b.com:8443"])
+            .unwrap();
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
@@ -2,6 +2,7 @@ use futures::channel::oneshot;
 use futures::StreamExt;
 use h2_support::assert_ping;
 use h2_support::prelude::*;
+use std::time::Duration;
 
 #[tokio::test]
 async fn recv_single_ping() {
@@ -183,3 +184,117 @@ async fn user_notifies_when_connection_closes() {
         "broken pipe",
     );
 }
+
+#[tokio::test]
+async fn keep_alive_timeout() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+
+        // The PING is never acknowledged
+        srv.recv_frame(frames::ping(frame::Ping::KEEP_ALIVE)).await;
+        srv.recv_eof().await;
+    };
+
+    let h2 = async move {
+        let (_client, conn) = client::Builder::new()
+            .keep_alive_interval(Duration::from_millis(20))
+            .keep_alive_timeout(Duration::from_millis(20))
+            .keep_alive_while_idle(true)
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        let err = conn.await.unwrap_err();
+        assert!(err.is_keep_alive_timeout());
+        assert!(!err.is_io());
+        assert_eq!(err.to_string(), "keep-alive timed out");
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn keep_alive_while_idle() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        let settings = client.assert_server_handshake().await;
+        assert_default_settings!(settings);
+
+        for _ in 0..2 {
+            client
+                .recv_frame(frames::ping(frame::Ping::KEEP_ALIVE))
+                .await;
+            client
+                .send_frame(frames::ping(frame::Ping::KEEP_ALIVE).pong())
+                .await;
+        }
+    };
+
+    let srv = async move {
+        let mut srv = server::Builder::new()
+            .keep_alive_interval(Duration::from_millis(20))
+            .keep_alive_while_idle(true)
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
+
+#[tokio::test]
+async fn keep_alive_with_open_streams() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        let settings = client.assert_server_handshake().await;
+        assert_default_settings!(settings);
+
+        // No PING is sent while there are no open streams
+        idle_ms(100).await;
+
+        client
+            .send_frame(frames::headers(1).request("POST", "https://example.com/"))
+            .await;
+        client
+            .recv_frame(frames::ping(frame::Ping::KEEP_ALIVE))
+            .await;
+        client
+            .send_frame(frames::ping(frame::Ping::KEEP_ALIVE).pong())
+            .await;
+        client.send_frame(frames::data(1, "hello").eos()).await;
+        client
+            .recv_frame(frames::headers(1).response(200).eos())
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::Builder::new()
+            .keep_alive_interval(Duration::from_millis(20))
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        let (req, mut stream) = srv.next().await.unwrap().unwrap();
+        let respond = async move {
+            let body = util::concat(req.into_body()).await.unwrap();
+            assert_eq!(body, "hello");
+            stream.send_response(Response::new(()), true).unwrap();
+        };
+        join(respond, async move {
+            assert!(srv.next().await.is_none());
+        })
+        .await;
+    };
+
+    join(client, srv).await;
+}
@@ -500,3 +500,386 @@ async fn stream_count_over_max_stream_limit_does_not_starve_capacity() {
 
     select(task, t).await;
 }
+
+#[tokio::test]
+async fn server_recv_extensible_priorities() {
+    use h2::ext::ExtensiblePriority;
+
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        let mut settings = frame::Settings::default();
+        settings.set_no_rfc7540_priorities(true);
+        client.assert_server_handshake_with_settings(settings).await;
+
+        let mut headers: frame::Headers = frames::headers(1)
+            .request("GET", "https://example.com/")
+            .field("priority", "u=5")
+            .eos()
+            .into();
+        headers.set_stream_dependency(frame::StreamDependency::new(0.into(), 255, true));
+        client.send_frame(headers).await;
+
+        // An update for an idle stream overrides its `priority` header.
+        client
+            .send_frame(frames::priority_update(3, "u=0, i"))
+            .await;
+        client
+            .send_frame(
+                frames::headers(3)
+                    .request("GET", "https://example.com/")
+                    .field("priority", "u=6")
+                    .eos(),
+            )
+            .await;
+
+        // Stream 3 is more urgent.
+        client
+            .recv_frame(frames::headers(3).response(200).eos())
+            .await;
+        client
+            .recv_frame(frames::headers(1).response(200).eos())
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::handshake(io).await.expect("handshake");
+
+        let (req, mut stream1) = srv.next().await.unwrap().unwrap();
+        assert_eq!(
+            req.extensions().get::<ExtensiblePriority>(),
+            Some(&ExtensiblePriority::new(5, false))
+        );
+        assert!(req.extensions().get::<frame::StreamDependency>().is_none());
+
+        let (req, mut stream3) = srv.next().await.unwrap().unwrap();
+        assert_eq!(
+            req.extensions().get::<ExtensiblePriority>(),
+            Some(&ExtensiblePriority::new(0, true))
+        );
+
+        let rsp = || http::Response::builder().status(200).body(()).unwrap();
+        stream1.send_response(rsp(), true).unwrap();
+        stream3.send_response(rsp(), true).unwrap();
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
+
+#[tokio::test]
+async fn server_sends_more_urgent_streams_first() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        client.assert_server_handshake().await;
+
+        client
+            .send_frame(
+                frames::headers(1)
+                    .request("GET", "https://example.com/")
+                    .field("priority", "u=7")
+                    .eos(),
+            )
+            .await;
+        client
+            .send_frame(
+                frames::headers(3)
+                    .request("GET", "https://example.com/")
+                    .eos(),
+            )
+            .await;
+
+        client.recv_frame(frames::headers(3).response(200)).await;
+        client
+            .recv_frame(frames::data(3, &b"urgent"[..]).eos())
+            .await;
+        client.recv_frame(frames::headers(1).response(200)).await;
+        client
+            .recv_frame(frames::data(1, &b"background"[..]).eos())
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::handshake(io).await.expect("handshake");
+
+        let (_, mut stream1) = srv.next().await.unwrap().unwrap();
+        let (_, mut stream3) = srv.next().await.unwrap().unwrap();
+
+        let rsp = || http::Response::builder().status(200).body(()).unwrap();
+        let mut send1 = stream1.send_response(rsp(), false).unwrap();
+        send1.send_data("background".into(), true).unwrap();
+        let mut send3 = stream3.send_response(rsp(), false).unwrap();
+        send3.send_data("urgent".into(), true).unwrap();
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
+
+#[tokio::test]
+async fn server_requeues_reprioritized_streams() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        client
+            .assert_server_handshake_with_settings(frames::settings().initial_window_size(0))
+            .await;
+
+        for id in [1, 3] {
+            client
+                .send_frame(
+                    frames::headers(id)
+                        .request("GET", "https://example.com/")
+                        .eos(),
+                )
+                .await;
+        }
+        client.recv_frame(frames::headers(1).response(200)).await;
+        client.recv_frame(frames::headers(3).response(200)).await;
+
+        // Both streams get capacity, queueing their DATA in order, then
+        // stream 3 becomes more urgent before anything is sent.
+        client.send_frame(frames::window_update(1, 5)).await;
+        client.send_frame(frames::window_update(3, 5)).await;
+        client.send_frame(frames::priority_update(3, "u=0")).await;
+
+        client
+            .recv_frame(frames::data(3, &b"three"[..]).eos())
+            .await;
+        client.recv_frame(frames::data(1, &b"one"[..]).eos()).await;
+    };
+
+    let srv = async move {
+        let mut srv = server::handshake(io).await.expect("handshake");
+
+        let (_, mut stream1) = srv.next().await.unwrap().unwrap();
+        let (_, mut stream3) = srv.next().await.unwrap().unwrap();
+
+        let rsp = || http::Response::builder().status(200).body(()).unwrap();
+        let mut send1 = stream1.send_response(rsp(), false).unwrap();
+        send1.send_data("one".into(), true).unwrap();
+        let mut send3 = stream3.send_response(rsp(), false).unwrap();
+        send3.send_data("three".into(), true).unwrap();
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
+
+#[tokio::test]
+async fn client_sends_extensible_priorities() {
+    use h2::ext::ExtensiblePriority;
+
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        srv.assert_client_handshake().await;
+
+        // The updates are flushed before the request is opened.
+        srv.recv_frame(frames::priority_update(1, "u=2")).await;
+        srv.recv_frame(frames::priority_update(1, "u=5")).await;
+        srv.recv_frame(
+            frames::headers(1)
+                .request("POST", "https://example.com/")
+                .field("priority", "u=1, i"),
+        )
+        .await;
+        srv.send_frame(frames::headers(1).response(200).eos()).await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut h2) = client::handshake(io).await.unwrap();
+
+        let mut request = Request::post("https://example.com/").body(()).unwrap();
+        request
+            .extensions_mut()
+            .insert(ExtensiblePriority::new(1, true));
+        let (response, mut stream) = client.send_request(request, false).unwrap();
+
+        client
+            .send_priority_update(stream.stream_id(), &ExtensiblePriority::new(2, false))
+            .unwrap();
+        stream
+            .send_priority_update(&ExtensiblePriority::new(5, false))
+            .unwrap();
+
+        let response = h2.drive(response).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn client_recv_priority_update_is_connection_error() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        srv.assert_client_handshake().await;
+        srv.send_frame(frames::priority_update(1, "u=0")).await;
+        srv.recv_frame(frames::go_away(0).protocol_error()).await;
+    };
+
+    let h2 = async move {
+        let (_client, h2) = client::handshake(io).await.unwrap();
+        let err = h2.await.unwrap_err();
+        assert_eq!(err.reason(), Some(Reason::PROTOCOL_ERROR));
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn dependency_tree_sends_parents_first() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        client.assert_server_handshake().await;
+
+        client
+            .send_frame(frames::headers(1).request("GET", "https://example.com/"))
+            .await;
+        // Stream 3 depends on stream 1, then stream 1 is made to depend on
+        // its own dependent, which moves stream 3 to the root first.
+        let mut headers: frame::Headers = frames::headers(3)
+            .request("GET", "https://example.com/")
+            .into();
+        headers.set_stream_dependency(frame::StreamDependency::new(1.into(), 15, false));
+        client.send_frame(headers).await;
+
+        client.send_frame(frames::data(1, &b""[..]).eos()).await;
+        client.send_frame(frames::data(3, &b""[..]).eos()).await;
+
+        client.recv_frame(frames::headers(1).response(200)).await;
+        client
+            .recv_frame(frames::data(1, &b"first"[..]).eos())
+            .await;
+        client.recv_frame(frames::headers(3).response(200)).await;
+        client
+            .recv_frame(frames::data(3, &b"second"[..]).eos())
+            .await;
+
+        client
+            .send_frame(
+                frames::headers(5)
+                    .request("GET", "https://example.com/")
+                    .eos(),
+            )
+            .await;
+        let mut headers: frame::Headers = frames::headers(7)
+            .request("GET", "https://example.com/")
+            .into();
+        headers.set_stream_dependency(frame::StreamDependency::new(5.into(), 15, false));
+        headers.set_end_stream();
+        client.send_frame(headers).await;
+        client
+            .send_frame(frame::Priority::new(
+                5.into(),
+                frame::StreamDependency::new(7.into(), 15, true),
+            ))
+            .await;
+
+        client.recv_frame(frames::headers(7).response(200)).await;
+        client
+            .recv_frame(frames::data(7, &b"first"[..]).eos())
+            .await;
+        client.recv_frame(frames::headers(5).response(200)).await;
+        client
+            .recv_frame(frames::data(5, &b"second"[..]).eos())
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::Builder::new()
+            .dependency_tree_scheduling(true)
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        for (parent, child) in [(1, 3), (7, 5)] {
+            let (_, mut first) = srv.next().await.unwrap().unwrap();
+            let (_, mut second) = srv.next().await.unwrap().unwrap();
+            if first.stream_id().as_u32() != parent {
+                std::mem::swap(&mut first, &mut second);
+            }
+            assert_eq!(second.stream_id().as_u32(), child);
+
+            let rsp = || http::Response::builder().status(200).body(()).unwrap();
+            let mut send = second.send_response(rsp(), false).unwrap();
+            send.send_data("second".into(), true).unwrap();
+            let mut send = first.send_response(rsp(), false).unwrap();
+            send.send_data("first".into(), true).unwrap();
+        }
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
+
+#[tokio::test]
+async fn dependency_tree_shares_by_weight() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    const FRAME: usize = frame::DEFAULT_MAX_FRAME_SIZE as usize;
+
+    let client = async move {
+        client.assert_server_handshake().await;
+        client.send_frame(frames::window_update(0, 65_536)).await;
+
+        for (id, weight) in [(1, 255), (3, 63)] {
+            let mut headers: frame::Headers = frames::headers(id)
+                .request("GET", "https://example.com/")
+                .into();
+            headers.set_stream_dependency(frame::StreamDependency::new(0.into(), weight, false));
+            headers.set_end_stream();
+            client.send_frame(headers).await;
+        }
+
+        // Stream 1 weighs four times as much as stream 3.
+        client.recv_frame(frames::headers(1).response(200)).await;
+        client.recv_frame(frames::data(1, vec![0; FRAME])).await;
+        client.recv_frame(frames::headers(3).response(200)).await;
+        client.recv_frame(frames::data(3, vec![0; FRAME])).await;
+        client.recv_frame(frames::data(1, vec![0; FRAME])).await;
+        client
+            .recv_frame(frames::data(1, vec![0; FRAME]).eos())
+            .await;
+        client.recv_frame(frames::data(3, vec![0; FRAME])).await;
+        client
+            .recv_frame(frames::data(3, vec![0; FRAME]).eos())
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::Builder::new()
+            .dependency_tree_scheduling(true)
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        let (_, mut stream1) = srv.next().await.unwrap().unwrap();
+        let (_, mut stream3) = srv.next().await.unwrap().unwrap();
+
+        let rsp = || http::Response::builder().status(200).body(()).unwrap();
+        for stream in [&mut stream1, &mut stream3] {
+            let mut send = stream.send_response(rsp(), false).unwrap();
+            send.send_data(vec![0; 3 * FRAME].into(), true).unwrap();
+        }
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
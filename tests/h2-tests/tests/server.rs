@@ -1552,3 +1552,182 @@ async fn init_window_size_smaller_than_default_should_use_default_before_ack() {
 
     join(client, h2).await;
 }
+
+#[tokio::test]
+async fn records_client_fingerprint() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        client.write_preface().await;
+        // SETTINGS with an unknown id between two known ones
+        client
+            .send_bytes(&[
+                0, 0, 18, 4, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0x10, 0, 0x0a, 0x0a, 0, 0, 0, 7, 0, 4, 0,
+                0x60, 0, 0,
+            ])
+            .await;
+        let settings = assert_settings!(client.next().await.unwrap().unwrap());
+        assert!(!settings.is_ack());
+        client.send(frame::Settings::ack().into()).await.unwrap();
+        let settings = assert_settings!(client.next().await.unwrap().unwrap());
+        assert!(settings.is_ack());
+
+        client
+            .send_frame(frames::window_update(0, 15_663_105))
+            .await;
+        client
+            .send_frame(frame::Priority::new(
+                3.into(),
+                frame::StreamDependency::new(0.into(), 200, false),
+            ))
+            .await;
+
+        let order = frame::PseudoOrder::builder()
+            .extend([
+                frame::PseudoId::Method,
+                frame::PseudoId::Authority,
+                frame::PseudoId::Scheme,
+                frame::PseudoId::Path,
+            ])
+            .build();
+        let mut pseudo =
+            frame::Pseudo::request(Method::GET, "https://example.com/".parse().unwrap(), None);
+        pseudo.set_pseudo_order(order);
+        let mut headers: frame::Headers = frames::headers(1).pseudo(pseudo).into();
+        headers.set_stream_dependency(frame::StreamDependency::new(0.into(), 255, true));
+        client.send_frame(headers).await;
+
+        client
+            .recv_frame(frames::headers(1).response(200).eos())
+            .await;
+    };
+
+    let h2 = async move {
+        let mut srv = server::Builder::new()
+            .record_peer_fingerprint(true)
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+        assert!(srv.peer_fingerprint().is_none());
+
+        let (req, mut stream) = srv.next().await.unwrap().unwrap();
+
+        let fingerprint = srv.peer_fingerprint().expect("fingerprint");
+        assert_eq!(
+            fingerprint.to_string(),
+            "1:4096;2570:7;4:6291456|15663105|3:0:0:201|m,a,s,p"
+        );
+
+        let order = req.extensions().get::<frame::PseudoOrder>().unwrap();
+        assert_eq!(
+            order.into_iter().take(4).copied().collect::<Vec<_>>(),
+            [
+                frame::PseudoId::Method,
+                frame::PseudoId::Authority,
+                frame::PseudoId::Scheme,
+                frame::PseudoId::Path,
+            ]
+        );
+        assert_eq!(
+            req.extensions().get::<frame::StreamDependency>(),
+            Some(&frame::StreamDependency::new(0.into(), 255, true))
+        );
+
+        let rsp = http::Response::builder().status(200).body(()).unwrap();
+        stream.send_response(rsp, true).unwrap();
+
+        poll_fn(|cx| srv.poll_closed(cx)).await.unwrap();
+    };
+
+    join(client, h2).await;
+}
+
+#[tokio::test]
+async fn client_fingerprint_not_recorded_by_default() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        let settings = client.assert_server_handshake().await;
+        assert_default_settings!(settings);
+        client
+            .send_frame(
+                frames::headers(1)
+                    .request("GET", "https://example.com/")
+                    .eos(),
+            )
+            .await;
+        client
+            .recv_frame(frames::headers(1).response(200).eos())
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::handshake(io).await.expect("handshake");
+        let (_, mut stream) = srv.next().await.unwrap().unwrap();
+        assert!(srv.peer_fingerprint().is_none());
+
+        let rsp = http::Response::builder().status(200).body(()).unwrap();
+        stream.send_response(rsp, true).unwrap();
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
+
+#[tokio::test]
+async fn send_informational_responses() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        let settings = client.assert_server_handshake().await;
+        assert_default_settings!(settings);
+        client
+            .send_frame(frames::headers(1).request("POST", "https://example.com/"))
+            .await;
+        client.recv_frame(frames::headers(1).response(100)).await;
+        client
+            .recv_frame(
+                frames::headers(1)
+                    .response(103)
+                    .field("link", "</style.css>; rel=preload"),
+            )
+            .await;
+        client
+            .recv_frame(frames::headers(1).response(200).eos())
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::handshake(io).await.expect("handshake");
+        let (_, mut stream) = srv.next().await.unwrap().unwrap();
+
+        let rsp = |status| http::Response::builder().status(status).body(()).unwrap();
+
+        let err = stream.send_informational(rsp(200)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "user error: invalid informational response status"
+        );
+        assert!(stream.send_informational(rsp(101)).is_err());
+
+        stream.send_informational(rsp(100)).unwrap();
+        let early_hints = http::Response::builder()
+            .status(103)
+            .header("link", "</style.css>; rel=preload")
+            .body(())
+            .unwrap();
+        stream.send_informational(early_hints).unwrap();
+        stream.send_response(rsp(200), true).unwrap();
+
+        // The final response was sent already
+        assert!(stream.send_informational(rsp(103)).is_err());
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
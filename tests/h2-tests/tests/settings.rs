@@ -0,0 +1,221 @@
+use futures::StreamExt;
+use h2_support::prelude::*;
+use std::pin::Pin;
+use std::time::Duration;
+
+#[tokio::test]
+async fn client_exposes_settings() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        srv.assert_client_handshake_with_settings(frames::settings().max_concurrent_streams(10))
+            .await;
+
+        srv.send_bytes(&[
+            0, 0, 12, 4, 0, 0, 0, 0, 0, // SETTINGS
+            0, 4, 0, 0, 0x03, 0xe8, // INITIAL_WINDOW_SIZE
+            0x0a, 0x0a, 0, 0, 0, 7, // unknown
+        ])
+        .await;
+        srv.recv_frame(frames::settings_ack()).await;
+    };
+
+    let h2 = async move {
+        let (client, mut conn) = client::Builder::new()
+            .enable_push(false)
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        let settings = poll_fn(|cx| {
+            let _ = Pin::new(&mut conn).poll(cx);
+            conn.poll_remote_settings(cx)
+        })
+        .await
+        .expect("settings");
+        assert_eq!(settings.max_concurrent_streams(), Some(10));
+        assert_eq!(settings.initial_window_size(), None);
+
+        let settings = poll_fn(|cx| {
+            let _ = Pin::new(&mut conn).poll(cx);
+            conn.poll_remote_settings(cx)
+        })
+        .await
+        .expect("settings");
+        assert_eq!(settings.max_concurrent_streams(), Some(10));
+        assert_eq!(settings.initial_window_size(), Some(1000));
+        assert_eq!(settings.get(0x0a0a), Some(7));
+        assert_eq!(
+            settings.iter().collect::<Vec<_>>(),
+            [(3, 10), (4, 1000), (0x0a0a, 7)]
+        );
+
+        assert_eq!(client.remote_settings(), settings);
+        assert_eq!(conn.local_settings().is_push_enabled(), Some(false));
+        assert_eq!(client.local_settings(), conn.local_settings());
+
+        drop(client);
+        conn.await.expect("client");
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn remote_settings_keep_wire_order() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        srv.assert_client_handshake_with_settings(frames::settings())
+            .await;
+
+        srv.send_bytes(&[
+            0, 0, 30, 4, 0, 0, 0, 0, 0, // SETTINGS
+            0x0a, 0x0a, 0, 0, 0, 7, // unknown
+            0, 4, 0, 0, 0x03, 0xe8, // INITIAL_WINDOW_SIZE
+            0, 3, 0, 0, 0, 10, // MAX_CONCURRENT_STREAMS
+            0, 1, 0, 0, 0x08, 0, // HEADER_TABLE_SIZE
+            0, 4, 0, 0, 0x07, 0xd0, // INITIAL_WINDOW_SIZE, again
+        ])
+        .await;
+        srv.recv_frame(frames::settings_ack()).await;
+    };
+
+    let h2 = async move {
+        let (client, mut conn) = client::handshake(io).await.expect("handshake");
+
+        let settings = poll_fn(|cx| {
+            let _ = Pin::new(&mut conn).poll(cx);
+            conn.poll_remote_settings(cx)
+        })
+        .await
+        .expect("settings");
+        assert_eq!(settings.iter().count(), 0);
+
+        let settings = poll_fn(|cx| {
+            let _ = Pin::new(&mut conn).poll(cx);
+            conn.poll_remote_settings(cx)
+        })
+        .await
+        .expect("settings");
+        assert_eq!(
+            settings.iter().collect::<Vec<_>>(),
+            [(0x0a0a, 7), (4, 2000), (3, 10), (1, 2048)]
+        );
+
+        drop(client);
+        conn.await.expect("client");
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn server_exposes_settings() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        client
+            .assert_server_handshake_with_settings(frames::settings().initial_window_size(1000))
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::Builder::new()
+            .max_concurrent_streams(20)
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        let settings = poll_fn(|cx| {
+            let _ = srv.poll_closed(cx);
+            srv.poll_remote_settings(cx)
+        })
+        .await
+        .expect("settings");
+        assert_eq!(settings.initial_window_size(), Some(1000));
+
+        assert!(srv.next().await.is_none());
+        assert_eq!(srv.local_settings().max_concurrent_streams(), Some(20));
+        assert_eq!(srv.remote_settings(), settings);
+
+        let settings = poll_fn(|cx| srv.poll_remote_settings(cx)).await;
+        assert!(settings.is_none());
+    };
+
+    join(client, srv).await;
+}
+
+#[tokio::test]
+async fn client_settings_ack_timeout() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        srv.send_frame(frames::settings()).await;
+        srv.read_preface().await.unwrap();
+        srv.recv_frame(frames::settings()).await;
+        srv.recv_frame(frames::settings_ack()).await;
+
+        // The client SETTINGS are never acknowledged
+        srv.recv_frame(frames::go_away(0).reason(Reason::SETTINGS_TIMEOUT))
+            .await;
+        srv.recv_eof().await;
+    };
+
+    let h2 = async move {
+        let (_client, conn) = client::Builder::new()
+            .settings_ack_timeout(Duration::from_millis(50))
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        let err = conn.await.unwrap_err();
+        assert!(err.is_settings_timeout());
+        assert_eq!(err.reason(), Some(Reason::SETTINGS_TIMEOUT));
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn server_settings_acked_in_time() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        let settings = client.assert_server_handshake().await;
+        assert_default_settings!(settings);
+
+        idle_ms(100).await;
+
+        client
+            .send_frame(
+                frames::headers(1)
+                    .request("GET", "https://example.com/")
+                    .eos(),
+            )
+            .await;
+        client
+            .recv_frame(frames::headers(1).response(200).eos())
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::Builder::new()
+            .settings_ack_timeout(Duration::from_millis(50))
+            .handshake::<_, Bytes>(io)
+            .await
+            .expect("handshake");
+
+        let (_, mut stream) = srv.next().await.unwrap().unwrap();
+        stream.send_response(Response::new(()), true).unwrap();
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
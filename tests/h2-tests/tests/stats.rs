@@ -0,0 +1,229 @@
+use futures::StreamExt;
+use h2_support::prelude::*;
+
+#[tokio::test]
+async fn client_connection_stats() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://http2.akamai.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frames::headers(1).response(200)).await;
+        srv.send_frame(frames::data(1, "hello").eos()).await;
+        srv.recv_frame(
+            frames::headers(3)
+                .request("GET", "https://http2.akamai.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frames::reset(3).refused()).await;
+        srv.recv_frame(frames::ping(frame::Ping::USER)).await;
+        srv.send_frame(frames::ping(frame::Ping::USER).pong()).await;
+        srv.recv_frame(frames::go_away(0)).await;
+        srv.recv_eof().await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut conn) = client::handshake(io).await.expect("handshake");
+
+        let request = Request::builder()
+            .uri("https://http2.akamai.com/")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = conn.drive(response).await.unwrap();
+        let mut body = response.into_body();
+        assert_eq!(conn.drive(body.data()).await.unwrap().unwrap(), "hello");
+        assert_eq!(body.stats().data_bytes_received(), 5);
+        assert_eq!(body.stats().data_bytes_sent(), 0);
+
+        let request = Request::builder()
+            .uri("https://http2.akamai.com/")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let err = conn.drive(response).await.unwrap_err();
+        assert_eq!(err.reason(), Some(Reason::REFUSED_STREAM));
+
+        assert_eq!(conn.stats().rtt(), None);
+        let mut ping_pong = client::Connection::ping_pong(&mut conn).expect("ping_pong");
+        ping_pong.send_ping(Ping::opaque()).expect("send ping");
+        conn.drive(poll_fn(|cx| ping_pong.poll_pong(cx)))
+            .await
+            .unwrap();
+
+        let stats = conn.stats();
+        assert!(stats.rtt().is_some());
+        assert_eq!(stats.send_window(), 65_535);
+        assert_eq!(stats.recv_window(), 65_535 - 5);
+
+        let sent = stats.sent();
+        assert_eq!(sent.frame_type(0x1).frames(), 2);
+        assert_eq!(sent.frame_type(0x6).frames(), 1);
+        assert_eq!(sent.frame_type(0x6).bytes(), 17);
+        assert_eq!(sent.frame_type(0x0).frames(), 0);
+        assert_eq!(sent.resets(Reason::REFUSED_STREAM), 0);
+
+        let received = stats.received();
+        assert_eq!(received.frame_type(0x0).frames(), 1);
+        assert_eq!(received.frame_type(0x0).bytes(), 14);
+        assert_eq!(received.resets(Reason::REFUSED_STREAM), 1);
+        assert_eq!(received.reset_reasons().count(), 1);
+        assert_eq!(
+            received.frames(),
+            received.frame_types().map(|(_, s)| s.frames()).sum::<u64>()
+        );
+
+        // The `:authority` of the second request is in the dynamic table.
+        let hpack = stats.hpack_encoder();
+        assert_eq!(hpack.fields(), 8);
+        assert!(hpack.dynamic_table_hits() > 0);
+        assert!(hpack.compression_ratio().unwrap() > 1.0);
+        assert_eq!(stats.hpack_decoder().fields(), 1);
+
+        drop(client);
+        drop(body);
+        conn.await.expect("client");
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn server_connection_stats() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    let client = async move {
+        let settings = client.assert_server_handshake().await;
+        assert_default_settings!(settings);
+        client
+            .send_frame(frames::headers(1).request("POST", "https://example.com/"))
+            .await;
+        client.send_frame(frames::data(1, "hello").eos()).await;
+        client
+            .recv_frame(frames::headers(1).response(200).field("server", "h2"))
+            .await;
+        client.recv_frame(frames::data(1, "world!").eos()).await;
+        client
+            .send_frame(frames::go_away(1).reason(Reason::ENHANCE_YOUR_CALM))
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::handshake(io).await.expect("handshake");
+
+        let (req, mut stream) = srv.next().await.unwrap().unwrap();
+        let mut body = req.into_body();
+        assert_eq!(body.data().await.unwrap().unwrap(), "hello");
+        assert_eq!(body.stats().data_bytes_received(), 5);
+
+        let response = Response::builder().header("server", "h2").body(()).unwrap();
+        let mut send = stream.send_response(response, false).unwrap();
+        send.send_data("world!".into(), true).unwrap();
+
+        let err = srv.next().await.unwrap().unwrap_err();
+        assert_eq!(err.reason(), Some(Reason::ENHANCE_YOUR_CALM));
+
+        let stream_stats = send.stats();
+        assert_eq!(stream_stats.data_bytes_sent(), 6);
+        assert_eq!(stream_stats.send_window(), 65_535 - 6);
+
+        let stats = srv.stats();
+        assert_eq!(stats.sent().frame_type(0x0).frames(), 1);
+        assert_eq!(stats.sent().frame_type(0x0).bytes(), 15);
+        assert_eq!(stats.received().frame_type(0x0).bytes(), 14);
+        assert_eq!(stats.received().go_aways(Reason::ENHANCE_YOUR_CALM), 1);
+        assert_eq!(stats.hpack_encoder().fields(), 2);
+        assert_eq!(stats.hpack_decoder().fields(), 4);
+        assert_eq!(stats.rtt(), None);
+    };
+
+    join(client, srv).await;
+}
+
+#[tokio::test]
+async fn client_stats_count_preface_sequence() {
+    use tokio::io::AsyncReadExt;
+
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        srv.send_frame(frames::settings()).await;
+        srv.read_preface().await.unwrap();
+
+        let mut head = [0; 9];
+        srv.read_exact(&mut head).await.unwrap();
+        assert_eq!(head[3], 4);
+        let mut payload = vec![0; head[2] as usize];
+        srv.read_exact(&mut payload).await.unwrap();
+
+        let mut grease = [0; 11];
+        srv.read_exact(&mut grease).await.unwrap();
+        srv.recv_frame(frames::window_update(0, 1_000)).await;
+        srv.recv_frame(frame::Priority::new(
+            3.into(),
+            frame::StreamDependency::new(0.into(), 200, false),
+        ))
+        .await;
+
+        srv.recv_frame(frames::settings_ack()).await;
+        srv.send_frame(frames::settings_ack()).await;
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frames::headers(1).response(200).eos()).await;
+        payload.len()
+    };
+
+    let h2 = async move {
+        let priority =
+            frame::Priority::new(3.into(), frame::StreamDependency::new(0.into(), 200, false));
+        let (mut client, mut conn) = client::Builder::new()
+            .initial_connection_window_size(65_535 + 1_000)
+            .priorities(frame::Priorities::builder().push(priority).build())
+            .preface_sequence(
+                client::PrefaceSequence::builder()
+                    .settings()
+                    .unknown(0x2a, 0, 0, "hi")
+                    .window_update()
+                    .priorities()
+                    .build(),
+            )
+            .handshake::<_, Bytes>(io)
+            .await
+            .unwrap();
+
+        let request = Request::get("https://example.com/").body(()).unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = conn.drive(response).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        conn.stats()
+    };
+
+    let (settings_len, stats) = join(srv, h2).await;
+
+    let sent = stats.sent();
+    // The SETTINGS of the preface sequence, then the ACK
+    assert_eq!(sent.frame_type(0x4).frames(), 2);
+    assert_eq!(sent.frame_type(0x4).bytes(), (9 + settings_len + 9) as u64);
+    assert_eq!(sent.frame_type(0x2a).frames(), 1);
+    assert_eq!(sent.frame_type(0x2a).bytes(), 11);
+    assert_eq!(sent.frame_type(0x8).frames(), 1);
+    assert_eq!(sent.frame_type(0x8).bytes(), 13);
+    assert_eq!(sent.frame_type(0x2).frames(), 1);
+    assert_eq!(sent.frame_type(0x2).bytes(), 14);
+    assert_eq!(sent.frame_type(0x1).frames(), 1);
+    assert_eq!(sent.frames(), 6);
+}
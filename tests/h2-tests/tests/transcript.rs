@@ -0,0 +1,155 @@
+use futures::StreamExt;
+use h2::transcript::{Recorder, Transcript};
+use h2_support::prelude::*;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Default)]
+struct Sink(Arc<Mutex<Vec<u8>>>);
+
+impl Sink {
+    fn transcript(&self) -> Transcript {
+        let src = String::from_utf8(self.0.lock().unwrap().clone()).unwrap();
+        src.parse().unwrap()
+    }
+}
+
+impl io::Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+async fn get<T>(io: T) -> Bytes
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut client, mut conn) = client::handshake(io).await.expect("handshake");
+
+    let request = Request::builder()
+        .uri("https://example.com/")
+        .body(())
+        .unwrap();
+    let (response, _) = client.send_request(request, true).unwrap();
+    let response = conn.drive(response).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut body = response.into_body();
+    let data = conn.drive(body.data()).await.unwrap().unwrap();
+
+    drop(client);
+    drop(body);
+    conn.await.expect("client");
+    data
+}
+
+async fn record_get() -> Transcript {
+    let (io, mut srv) = mock::new();
+    let sink = Sink::default();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.recv_frame(
+            frames::headers(1)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frames::headers(1).response(200)).await;
+        srv.send_frame(frames::data(1, "hello").eos()).await;
+        srv.recv_frame(frames::go_away(0)).await;
+        srv.recv_eof().await;
+    };
+
+    let io = Recorder::new(io, sink.clone());
+    let h2 = async move {
+        assert_eq!(get(io).await, "hello");
+    };
+
+    join(srv, h2).await;
+    sink.transcript()
+}
+
+#[tokio::test]
+async fn recorder_records_frames() {
+    h2_support::trace_init!();
+    let transcript = record_get().await;
+
+    let frames = |direction| {
+        transcript
+            .records()
+            .iter()
+            .filter(|record| record.direction() == direction)
+            .map(|record| record.bytes().clone())
+            .collect::<Vec<_>>()
+    };
+
+    let sent = frames(FrameDirection::Outbound);
+    assert_eq!(sent[0], MAGIC_PREFACE);
+    assert_eq!(
+        sent[1..].iter().map(|f| (f[3], f[4])).collect::<Vec<_>>(),
+        [(0x4, 0x0), (0x4, 0x1), (0x1, 0x5), (0x7, 0x0)]
+    );
+
+    let received = frames(FrameDirection::Inbound);
+    assert_eq!(
+        received.iter().map(|f| (f[3], f[4])).collect::<Vec<_>>(),
+        [(0x4, 0x0), (0x4, 0x1), (0x1, 0x4), (0x0, 0x1)]
+    );
+    assert_eq!(received[3][9..], b"hello"[..]);
+
+    let elapsed = transcript
+        .records()
+        .iter()
+        .map(|record| record.elapsed())
+        .collect::<Vec<_>>();
+    assert!(elapsed.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+#[tokio::test]
+async fn replay_plays_recorded_server() {
+    h2_support::trace_init!();
+    let transcript = record_get().await;
+
+    let (io, mut srv) = mock::new();
+    let srv = async move {
+        Replay::new(transcript).play(&mut srv).await;
+        srv.recv_eof().await;
+    };
+
+    let h2 = async move {
+        assert_eq!(get(io).await, "hello");
+    };
+
+    join(srv, h2).await;
+}
+
+#[tokio::test]
+async fn replay_plays_recorded_client() {
+    h2_support::trace_init!();
+    let transcript = record_get().await;
+
+    let (io, mut client) = mock::new();
+    let client = async move {
+        Replay::new(transcript).reversed().play(&mut client).await;
+    };
+
+    let srv = async move {
+        let mut srv = server::handshake(io).await.expect("handshake");
+        let (request, mut stream) = srv.next().await.unwrap().unwrap();
+        assert_eq!(request.uri(), "https://example.com/");
+
+        let mut stream = stream.send_response(Response::new(()), false).unwrap();
+        stream.send_data("hello".into(), true).unwrap();
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
@@ -0,0 +1,131 @@
+#![deny(warnings)]
+
+use futures::StreamExt;
+use h2_support::prelude::*;
+
+fn upgrade_request() -> Request<()> {
+    Request::builder()
+        .uri("/")
+        .header("host", "example.com")
+        .header("connection", "Upgrade, HTTP2-Settings")
+        .header("upgrade", "h2c")
+        .header("user-agent", "test")
+        .body(())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn server_upgrade_accepts_request_on_stream_1() {
+    h2_support::trace_init!();
+    let (io, mut client) = mock::new();
+
+    // The upgrade settings hold only the initial window size.
+    let http2_settings = client::Builder::new()
+        .initial_window_size(5)
+        .http2_settings_header();
+    let http2_settings = server::decode_http2_settings(&http2_settings).unwrap();
+    assert_eq!(http2_settings.snapshot().initial_window_size(), Some(5));
+
+    let client = async move {
+        let settings = client.assert_server_handshake().await;
+        assert_default_settings!(settings);
+        client.recv_frame(frames::headers(1).response(200)).await;
+        client.recv_frame(frames::data(1, "hello")).await;
+        client.send_frame(frames::window_update(1, 6)).await;
+        client.recv_frame(frames::data(1, " world").eos()).await;
+
+        client
+            .send_frame(
+                frames::headers(3)
+                    .request("GET", "http://example.com/")
+                    .eos(),
+            )
+            .await;
+        client
+            .recv_frame(frames::headers(3).response(204).eos())
+            .await;
+    };
+
+    let srv = async move {
+        let mut srv = server::Builder::new()
+            .handshake_upgraded::<_, Bytes>(io, &http2_settings, upgrade_request())
+            .await
+            .expect("handshake");
+
+        let (req, mut stream) = srv.next().await.unwrap().unwrap();
+        assert_eq!(req.method(), Method::GET);
+        assert_eq!(req.uri(), "http://example.com/");
+        assert_eq!(req.headers().len(), 1);
+        assert_eq!(req.headers()["user-agent"], "test");
+        assert!(req.into_body().is_end_stream());
+
+        let rsp = Response::builder().status(200).body(()).unwrap();
+        let mut stream = stream.send_response(rsp, false).unwrap();
+        stream.send_data("hello world".into(), true).unwrap();
+
+        let (req, mut stream) = srv.next().await.unwrap().unwrap();
+        assert_eq!(req.uri(), "http://example.com/");
+        let rsp = Response::builder().status(204).body(()).unwrap();
+        stream.send_response(rsp, true).unwrap();
+
+        assert!(srv.next().await.is_none());
+    };
+
+    join(client, srv).await;
+}
+
+#[test]
+fn server_upgrade_rejects_invalid_settings() {
+    for value in ["AAMA!", "AAMA", "AAIAAAAC"] {
+        let err =
+            server::decode_http2_settings(&http::HeaderValue::from_static(value)).unwrap_err();
+        assert_eq!(err.reason(), Some(Reason::PROTOCOL_ERROR), "{:?}", value);
+    }
+}
+
+#[tokio::test]
+async fn client_upgrade_receives_response_on_stream_1() {
+    h2_support::trace_init!();
+    let (io, mut srv) = mock::new();
+
+    let srv = async move {
+        let settings = srv.assert_client_handshake().await;
+        assert_default_settings!(settings);
+        srv.send_frame(frames::headers(1).response(200)).await;
+        srv.send_frame(frames::data(1, "hello").eos()).await;
+
+        srv.recv_frame(
+            frames::headers(3)
+                .request("GET", "https://example.com/")
+                .eos(),
+        )
+        .await;
+        srv.send_frame(frames::headers(3).response(204).eos()).await;
+    };
+
+    let h2 = async move {
+        let (mut client, mut conn, response) = client::Builder::new()
+            .handshake_upgraded::<_, Bytes>(io, &Method::GET)
+            .await
+            .expect("handshake");
+
+        let response = conn.drive(response).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut body = response.into_body();
+        assert_eq!(conn.drive(body.data()).await.unwrap().unwrap(), "hello");
+        assert!(conn.drive(body.data()).await.is_none());
+
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = conn.drive(response).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        drop(client);
+        conn.await.expect("client");
+    };
+
+    join(srv, h2).await;
+}
@@ -0,0 +1,742 @@
+//! Decodes both directions of an HTTP/2 connection and prints their frames.
+
+use bytes::{Bytes, BytesMut};
+use futures_core::Stream;
+use h2::{Codec, FrameObserver, ObservedFrame};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+const DEFAULT_WINDOW_SIZE: i64 = 65_535;
+const MAX_FRAME_SIZE: usize = (1 << 24) - 1;
+
+const DATA: u8 = 0x0;
+const HEADERS: u8 = 0x1;
+const PRIORITY: u8 = 0x2;
+const RST_STREAM: u8 = 0x3;
+const SETTINGS: u8 = 0x4;
+const PUSH_PROMISE: u8 = 0x5;
+const PING: u8 = 0x6;
+const GOAWAY: u8 = 0x7;
+const WINDOW_UPDATE: u8 = 0x8;
+const CONTINUATION: u8 = 0x9;
+
+const END_STREAM: u8 = 0x1;
+const ACK: u8 = 0x1;
+const END_HEADERS: u8 = 0x4;
+const PADDED: u8 = 0x8;
+const PRIORITY_FLAG: u8 = 0x20;
+
+const SETTINGS_HEADER_TABLE_SIZE: u16 = 0x1;
+const SETTINGS_INITIAL_WINDOW_SIZE: u16 = 0x4;
+
+/// Writes a line to the output, like `println!` does to stdout.
+macro_rules! out {
+    ($out:expr) => {
+        writeln!($out).expect("failed to write output")
+    };
+    ($out:expr, $($arg:tt)*) => {
+        writeln!($out, $($arg)*).expect("failed to write output")
+    };
+}
+
+/// The two sides of an HTTP/2 connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Peer {
+    Client,
+    Server,
+}
+
+/// Decodes the bytes sent by the client and the server of a connection.
+#[derive(Debug)]
+pub struct Connection<W = io::Stdout> {
+    client: Side,
+    server: Side,
+    /// The number of frames printed, in both directions.
+    frames: u64,
+    out: W,
+}
+
+/// The bytes sent by one side of the connection.
+#[derive(Debug)]
+struct Side {
+    peer: Peer,
+    codec: Codec<Input, Bytes>,
+    records: Arc<Mutex<Vec<Record>>>,
+    /// Whether the bytes preceding the HTTP/2 frames were skipped.
+    started: bool,
+    /// Bytes received before the HTTP/2 frames were found.
+    prelude: Vec<u8>,
+    /// Whether decoding stopped, at the end of the input or on an error.
+    done: bool,
+    /// The windows this side may send DATA frames in.
+    flow: Flow,
+}
+
+#[derive(Debug)]
+struct Flow {
+    connection: i64,
+    streams: HashMap<u32, i64>,
+    /// The SETTINGS_INITIAL_WINDOW_SIZE of the other side.
+    initial: i64,
+}
+
+/// A frame reported by the codec's observer.
+#[derive(Debug)]
+struct Record {
+    frame_type: u8,
+    flags: u8,
+    stream_id: u32,
+    payload_len: usize,
+    pad_len: Option<u8>,
+    payload: Vec<u8>,
+    fields: Option<Vec<(Bytes, Bytes)>>,
+}
+
+#[derive(Debug, Clone)]
+struct Recorder(Arc<Mutex<Vec<Record>>>);
+
+/// Bytes handed to a codec. Reading returns `Pending` once they are
+/// consumed, until the end of the input is reached.
+#[derive(Debug, Default)]
+struct Input {
+    buf: BytesMut,
+    eof: bool,
+}
+
+struct NoopWaker;
+
+// ===== impl Peer =====
+
+impl Peer {
+    fn name(self) -> &'static str {
+        match self {
+            Peer::Client => "client",
+            Peer::Server => "server",
+        }
+    }
+}
+
+// ===== impl Connection =====
+
+impl Connection {
+    /// Decodes a connection, printing to stdout.
+    pub fn new() -> Connection {
+        Connection::with_output(io::stdout())
+    }
+}
+
+impl<W: Write> Connection<W> {
+    /// Decodes a connection, printing to `out`.
+    pub fn with_output(out: W) -> Connection<W> {
+        Connection {
+            client: Side::new(Peer::Client),
+            server: Side::new(Peer::Server),
+            frames: 0,
+            out,
+        }
+    }
+
+    /// Decodes bytes sent by `peer`, printing the frames they complete.
+    pub fn feed(&mut self, peer: Peer, data: &[u8]) {
+        let side = self.side(peer);
+        if side.done {
+            return;
+        }
+
+        if side.started {
+            side.codec.get_mut().buf.extend_from_slice(data);
+        } else {
+            side.prelude.extend_from_slice(data);
+            let side = match peer {
+                Peer::Client => &mut self.client,
+                Peer::Server => &mut self.server,
+            };
+            side.skip_prelude(&mut self.out);
+        }
+
+        self.poll(peer);
+    }
+
+    /// Ends the input, then prints what remains and the client fingerprint.
+    pub fn finish(&mut self) {
+        for &peer in &[Peer::Client, Peer::Server] {
+            let side = self.side(peer);
+            if !side.started {
+                let skipped = side.prelude.len();
+                if skipped > 0 {
+                    out!(
+                        self.out,
+                        "{}: {} bytes without HTTP/2 frames",
+                        peer.name(),
+                        skipped
+                    );
+                }
+                continue;
+            }
+
+            side.codec.get_mut().eof = true;
+            self.poll(peer);
+
+            let buffered = self.side(peer).codec.get_ref().buf.len();
+            if buffered > 0 {
+                out!(
+                    self.out,
+                    "{}: {} bytes of a truncated frame",
+                    peer.name(),
+                    buffered
+                );
+            }
+        }
+
+        out!(self.out);
+        self.client.print_summary(&mut self.out);
+        self.server.print_summary(&mut self.out);
+
+        out!(self.out);
+        match self.client.codec.peer_fingerprint() {
+            Some(fingerprint) => out!(self.out, "fingerprint: {}", fingerprint),
+            None => out!(
+                self.out,
+                "fingerprint: incomplete, no HEADERS frame from the client"
+            ),
+        }
+    }
+
+    fn side(&mut self, peer: Peer) -> &mut Side {
+        match peer {
+            Peer::Client => &mut self.client,
+            Peer::Server => &mut self.server,
+        }
+    }
+
+    /// Decodes the frames buffered for `peer`.
+    fn poll(&mut self, peer: Peer) {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            let side = self.side(peer);
+            if side.done || !side.started {
+                return;
+            }
+
+            let res = Pin::new(&mut side.codec).poll_next(&mut cx);
+            self.print_records(peer);
+
+            match res {
+                Poll::Ready(Some(Ok(_))) => {}
+                Poll::Ready(Some(Err(e))) => {
+                    out!(self.out, "{}: error: {}", peer.name(), e);
+                    self.side(peer).done = true;
+                }
+                Poll::Ready(None) => self.side(peer).done = true,
+                Poll::Pending => return,
+            }
+        }
+    }
+
+    fn print_records(&mut self, peer: Peer) {
+        let records = std::mem::take(&mut *self.side(peer).records.lock().unwrap());
+        for record in records {
+            self.frames += 1;
+            self.print_record(peer, &record);
+        }
+    }
+
+    fn print_record(&mut self, peer: Peer, record: &Record) {
+        let mut line = format!(
+            "{} #{} {} stream={}",
+            peer.name(),
+            self.frames,
+            frame_type_name(record.frame_type),
+            record.stream_id
+        );
+        let flags = flag_names(record.frame_type, record.flags);
+        if !flags.is_empty() {
+            line.push_str(&format!(" flags={}", flags));
+        }
+        line.push_str(&format!(" len={}", record.payload_len));
+        if let Some(pad_len) = record.pad_len {
+            line.push_str(&format!(" pad={}", pad_len));
+        }
+        let out = &mut self.out;
+        out!(out, "{}", line);
+
+        let (side, other) = match peer {
+            Peer::Client => (&mut self.client, &mut self.server),
+            Peer::Server => (&mut self.server, &mut self.client),
+        };
+        let payload = &record.payload[..];
+
+        match record.frame_type {
+            DATA => {
+                let len = record.payload_len as i64;
+                let flow = &mut side.flow;
+                let stream = flow.stream(record.stream_id);
+                *stream -= len;
+                let stream = *stream;
+                flow.connection -= len;
+                out!(
+                    out,
+                    "    window: connection={} stream={}",
+                    flow.connection,
+                    stream
+                );
+                if record.flags & END_STREAM == END_STREAM {
+                    flow.streams.remove(&record.stream_id);
+                }
+            }
+            HEADERS if record.flags & PRIORITY_FLAG == PRIORITY_FLAG => {
+                let offset = record.pad_len.map_or(0, |_| 1);
+                print_priority(out, payload.get(offset..).unwrap_or_default());
+            }
+            PRIORITY => print_priority(out, payload),
+            RST_STREAM => {
+                if let Some(code) = read_u32(payload) {
+                    out!(out, "    error: {}", h2::Reason::from(code));
+                }
+                side.flow.streams.remove(&record.stream_id);
+                other.flow.streams.remove(&record.stream_id);
+            }
+            SETTINGS => {
+                for setting in payload.chunks_exact(6) {
+                    let id = u16::from_be_bytes([setting[0], setting[1]]);
+                    let value = read_u32(&setting[2..]).unwrap();
+                    out!(out, "    {} ({:#x}) = {}", setting_name(id), id, value);
+
+                    match id {
+                        SETTINGS_HEADER_TABLE_SIZE => {
+                            // The other side may now use a table of this size
+                            // when encoding.
+                            other.codec.set_recv_header_table_size(value as usize);
+                        }
+                        SETTINGS_INITIAL_WINDOW_SIZE => {
+                            let flow = &mut other.flow;
+                            let delta = value as i64 - flow.initial;
+                            flow.initial = value as i64;
+                            for window in flow.streams.values_mut() {
+                                *window += delta;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            PUSH_PROMISE => {
+                let offset = record.pad_len.map_or(0, |_| 1);
+                if let Some(promised) = payload.get(offset..).and_then(read_u32) {
+                    out!(out, "    promised stream={}", promised & !(1 << 31));
+                }
+            }
+            PING => out!(out, "    data={:02x?}", payload),
+            GOAWAY if payload.len() >= 8 => {
+                let last_stream_id = read_u32(payload).unwrap() & !(1 << 31);
+                let code = read_u32(&payload[4..]).unwrap();
+                out!(
+                    out,
+                    "    last stream={} error: {}",
+                    last_stream_id,
+                    h2::Reason::from(code)
+                );
+                if payload.len() > 8 {
+                    out!(
+                        out,
+                        "    debug data: {:?}",
+                        String::from_utf8_lossy(&payload[8..])
+                    );
+                }
+            }
+            WINDOW_UPDATE => {
+                if let Some(increment) = read_u32(payload) {
+                    let increment = (increment & !(1 << 31)) as i64;
+                    // The update is for the windows the other side sends in.
+                    let flow = &mut other.flow;
+                    let window = if record.stream_id == 0 {
+                        flow.connection += increment;
+                        flow.connection
+                    } else {
+                        let window = flow.stream(record.stream_id);
+                        *window += increment;
+                        *window
+                    };
+                    out!(
+                        out,
+                        "    increment={} {} window={}",
+                        increment,
+                        other.peer.name(),
+                        window
+                    );
+                }
+            }
+            _ => {}
+        }
+
+        for (name, value) in record.fields.iter().flatten() {
+            out!(
+                out,
+                "    {}: {}",
+                String::from_utf8_lossy(name),
+                String::from_utf8_lossy(value)
+            );
+        }
+    }
+}
+
+/// Prints the priority fields starting `priority`, or its bytes if they are
+/// truncated.
+fn print_priority(out: &mut dyn Write, priority: &[u8]) {
+    match read_priority(priority) {
+        Some((exclusive, dependency, weight)) => out!(
+            out,
+            "    priority: exclusive={} dependency={} weight={}",
+            exclusive,
+            dependency,
+            weight
+        ),
+        None => out!(out, "    malformed priority: {:02x?}", priority),
+    }
+}
+
+/// Returns the exclusive flag, stream dependency and weight of a priority.
+fn read_priority(priority: &[u8]) -> Option<(u32, u32, u16)> {
+    let priority = priority.get(..5)?;
+    let dependency = read_u32(priority)?;
+    Some((
+        dependency >> 31,
+        dependency & !(1 << 31),
+        priority[4] as u16 + 1,
+    ))
+}
+
+fn read_u32(buf: &[u8]) -> Option<u32> {
+    let bytes = buf.get(..4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn frame_type_name(frame_type: u8) -> String {
+    let name = match frame_type {
+        DATA => "DATA",
+        HEADERS => "HEADERS",
+        PRIORITY => "PRIORITY",
+        RST_STREAM => "RST_STREAM",
+        SETTINGS => "SETTINGS",
+        PUSH_PROMISE => "PUSH_PROMISE",
+        PING => "PING",
+        GOAWAY => "GOAWAY",
+        WINDOW_UPDATE => "WINDOW_UPDATE",
+        CONTINUATION => "CONTINUATION",
+        0xa => "ALTSVC",
+        0xc => "ORIGIN",
+        0x10 => "PRIORITY_UPDATE",
+        _ => return format!("UNKNOWN({:#x})", frame_type),
+    };
+    name.to_string()
+}
+
+fn flag_names(frame_type: u8, flags: u8) -> String {
+    let known: &[(u8, &str)] = match frame_type {
+        DATA => &[(END_STREAM, "END_STREAM"), (PADDED, "PADDED")],
+        HEADERS => &[
+            (END_STREAM, "END_STREAM"),
+            (END_HEADERS, "END_HEADERS"),
+            (PADDED, "PADDED"),
+            (PRIORITY_FLAG, "PRIORITY"),
+        ],
+        SETTINGS | PING => &[(ACK, "ACK")],
+        PUSH_PROMISE => &[(END_HEADERS, "END_HEADERS"), (PADDED, "PADDED")],
+        CONTINUATION => &[(END_HEADERS, "END_HEADERS")],
+        _ => &[],
+    };
+
+    let mut names = known
+        .iter()
+        .filter(|&&(flag, _)| flags & flag == flag)
+        .map(|&(_, name)| name.to_string())
+        .collect::<Vec<_>>();
+
+    let unknown = known.iter().fold(flags, |flags, &(flag, _)| flags & !flag);
+    if unknown != 0 {
+        names.push(format!("{:#x}", unknown));
+    }
+
+    names.join("|")
+}
+
+fn setting_name(id: u16) -> &'static str {
+    match id {
+        0x1 => "HEADER_TABLE_SIZE",
+        0x2 => "ENABLE_PUSH",
+        0x3 => "MAX_CONCURRENT_STREAMS",
+        0x4 => "INITIAL_WINDOW_SIZE",
+        0x5 => "MAX_FRAME_SIZE",
+        0x6 => "MAX_HEADER_LIST_SIZE",
+        0x8 => "ENABLE_CONNECT_PROTOCOL",
+        0x9 => "NO_RFC7540_PRIORITIES",
+        _ => "UNKNOWN",
+    }
+}
+
+// ===== impl Side =====
+
+impl Side {
+    fn new(peer: Peer) -> Side {
+        let records = Arc::new(Mutex::new(Vec::new()));
+
+        let mut codec = Codec::with_max_recv_frame_size(Input::default(), MAX_FRAME_SIZE);
+        codec.set_max_recv_header_list_size(usize::MAX);
+        codec.set_frame_observer(Arc::new(Recorder(records.clone())));
+        if peer == Peer::Client {
+            codec.record_peer_fingerprint();
+        }
+
+        Side {
+            peer,
+            codec,
+            records,
+            started: false,
+            prelude: Vec::new(),
+            done: false,
+            flow: Flow {
+                connection: DEFAULT_WINDOW_SIZE,
+                streams: HashMap::new(),
+                initial: DEFAULT_WINDOW_SIZE,
+            },
+        }
+    }
+
+    /// Skips the connection preface sent by the client, and the HTTP/1.1
+    /// upgrade exchange of h2c connections.
+    fn skip_prelude(&mut self, out: &mut dyn Write) {
+        let skip = match self.peer {
+            Peer::Client => {
+                let start = match find(&self.prelude, PREFACE) {
+                    Some(start) => start,
+                    None => return,
+                };
+                if start > 0 {
+                    out!(
+                        out,
+                        "client: skipped {} bytes of HTTP/1.1 upgrade request",
+                        start
+                    );
+                }
+                start + PREFACE.len()
+            }
+            Peer::Server => {
+                const HTTP: &[u8] = b"HTTP/1.1 ";
+                let len = self.prelude.len().min(HTTP.len());
+                if self.prelude[..len] != HTTP[..len] {
+                    0
+                } else if len < HTTP.len() {
+                    return;
+                } else {
+                    let end = match find(&self.prelude, b"\r\n\r\n") {
+                        Some(end) => end + 4,
+                        None => return,
+                    };
+                    let status = self.prelude[..end].split(|&b| b == b'\r').next().unwrap();
+                    out!(out, "server: skipped {}", String::from_utf8_lossy(status));
+                    end
+                }
+            }
+        };
+
+        self.started = true;
+        let data = self.prelude.split_off(skip);
+        self.prelude.clear();
+        self.codec.get_mut().buf.extend_from_slice(&data);
+    }
+
+    fn print_summary(&self, out: &mut dyn Write) {
+        let stats = self.codec.recv_stats();
+        out!(
+            out,
+            "{}: {} frames, {} bytes",
+            self.peer.name(),
+            stats.frames(),
+            stats.bytes()
+        );
+        for (frame_type, frame_stats) in stats.frame_types() {
+            out!(
+                out,
+                "    {}: {} frames, {} bytes",
+                frame_type_name(frame_type),
+                frame_stats.frames(),
+                frame_stats.bytes()
+            );
+        }
+
+        let hpack = self.codec.recv_hpack_stats();
+        if let Some(ratio) = hpack.compression_ratio() {
+            out!(
+                out,
+                "    hpack: {} fields, {} bytes encoded as {}, ratio {:.2}",
+                hpack.fields(),
+                hpack.field_bytes(),
+                hpack.encoded_bytes(),
+                ratio
+            );
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+// ===== impl Flow =====
+
+impl Flow {
+    fn stream(&mut self, id: u32) -> &mut i64 {
+        let initial = self.initial;
+        self.streams.entry(id).or_insert(initial)
+    }
+}
+
+// ===== impl Recorder =====
+
+impl FrameObserver for Recorder {
+    fn on_frame(&self, frame: &ObservedFrame<'_>) {
+        self.0.lock().unwrap().push(Record {
+            frame_type: frame.frame_type(),
+            flags: frame.flags(),
+            stream_id: frame.stream_id().as_u32(),
+            payload_len: frame.payload_len(),
+            pad_len: frame.pad_len(),
+            payload: frame.payload().unwrap_or_default().to_vec(),
+            fields: frame.fields().map(|fields| fields.to_vec()),
+        });
+    }
+}
+
+// ===== impl Input =====
+
+impl AsyncRead for Input {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        dst: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.buf.is_empty() {
+            return if this.eof {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            };
+        }
+
+        let len = dst.remaining().min(this.buf.len());
+        dst.put_slice(&this.buf.split_to(len));
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for Input {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+// ===== impl NoopWaker =====
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SETTINGS_FRAME: &[u8] = &[0, 0, 0, 4, 0, 0, 0, 0, 0];
+    const SETTINGS_ACK: &[u8] = &[0, 0, 0, 4, 1, 0, 0, 0, 0];
+
+    fn dump(exchange: &[(Peer, &[u8])]) -> String {
+        let mut conn = Connection::with_output(Vec::new());
+        for &(peer, data) in exchange {
+            conn.feed(peer, data);
+        }
+        conn.finish();
+        String::from_utf8(conn.out).unwrap()
+    }
+
+    #[test]
+    fn feed_decodes_exchange() {
+        let request = [PREFACE, SETTINGS_FRAME, SETTINGS_ACK].concat();
+        // HEADERS with END_STREAM and END_HEADERS: GET https /
+        let headers = [0, 0, 3, 1, 5, 0, 0, 0, 1, 0x82, 0x87, 0x84];
+        // HEADERS with END_HEADERS, then DATA with END_STREAM.
+        let response = [
+            &[0, 0, 1, 1, 4, 0, 0, 0, 1, 0x88][..],
+            &[0, 0, 2, 0, 1, 0, 0, 0, 1, b'o', b'k'],
+        ]
+        .concat();
+
+        let out = dump(&[
+            (Peer::Client, &request[..10]),
+            (Peer::Server, SETTINGS_FRAME),
+            (Peer::Client, &request[10..]),
+            (Peer::Server, SETTINGS_ACK),
+            (Peer::Client, &headers),
+            (Peer::Server, &response),
+        ]);
+        let lines = out.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "server #1 SETTINGS stream=0 len=0");
+        assert_eq!(lines[1], "client #2 SETTINGS stream=0 len=0");
+        assert_eq!(lines[2], "client #3 SETTINGS stream=0 flags=ACK len=0");
+        assert_eq!(lines[3], "server #4 SETTINGS stream=0 flags=ACK len=0");
+        assert_eq!(
+            lines[4],
+            "client #5 HEADERS stream=1 flags=END_STREAM|END_HEADERS len=3"
+        );
+        assert!(out.contains("    :path: /\n"), "{}", out);
+        assert!(out.contains("    :status: 200\n"), "{}", out);
+        assert!(
+            out.contains("    window: connection=65533 stream=65533\n"),
+            "{}",
+            out
+        );
+        assert!(out.contains("client: 3 frames"), "{}", out);
+        assert!(out.contains("server: 4 frames"), "{}", out);
+    }
+
+    #[test]
+    fn feed_prints_short_priority_frame() {
+        let request = [
+            PREFACE,
+            SETTINGS_FRAME,
+            &[0, 0, 4, 2, 0, 0, 0, 0, 1, 0, 0, 0, 3],
+        ]
+        .concat();
+
+        let out = dump(&[(Peer::Client, &request)]);
+
+        assert!(
+            out.contains("    malformed priority: [00, 00, 00, 03]\n"),
+            "{}",
+            out
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Prints the frames of a cleartext HTTP/2 connection.
+//!
+//! ```text
+//! h2dump <CAPTURE.pcap>
+//! h2dump <STREAM> [STREAM]
+//! ```
+//!
+//! A libpcap capture is split into its TCP connections, and each connection
+//! carrying the HTTP/2 connection preface is decoded, including h2c upgrades.
+//! Otherwise, each file holds the raw bytes sent by one side: the file
+//! starting with the preface, or an HTTP/1.1 upgrade request, is the client's.
+//!
+//! Both directions are decoded with the crate's codec. Every frame is printed
+//! with its decoded header fields, SETTINGS in wire order, priority data and
+//! the flow-control windows left to the sender. The Akamai fingerprint of the
+//! client is printed last. Windows are only exact for captures, where both
+//! directions are interleaved as they were sent.
+
+mod dump;
+mod pcap;
+
+use dump::{Connection, Peer};
+
+use std::{env, fs, process};
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+fn main() {
+    let paths = env::args().skip(1).collect::<Vec<_>>();
+    if paths.is_empty() || paths.len() > 2 {
+        eprintln!("usage: h2dump <CAPTURE.pcap>");
+        eprintln!("       h2dump <STREAM> [STREAM]");
+        process::exit(2);
+    }
+
+    let files = paths
+        .iter()
+        .map(|path| {
+            fs::read(path).unwrap_or_else(|e| {
+                eprintln!("error: {}: {}", path, e);
+                process::exit(1);
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if files.len() == 1 && pcap::is_capture(&files[0]) {
+        if let Err(e) = dump_capture(&files[0]) {
+            eprintln!("error: {}: {}", paths[0], e);
+            process::exit(1);
+        }
+    } else {
+        dump_streams(&files);
+    }
+}
+
+fn dump_capture(data: &[u8]) -> Result<(), String> {
+    let mut found = false;
+
+    for mut conn in pcap::parse(data)? {
+        if !conn.sent(true, PREFACE) {
+            if !conn.sent(false, PREFACE) {
+                continue;
+            }
+            conn.swap();
+        }
+
+        if found {
+            println!();
+        }
+        found = true;
+        println!("connection {} -> {}", conn.client, conn.server);
+        println!();
+
+        let mut dump = Connection::new();
+        for segment in &conn.segments {
+            let peer = if segment.from_client {
+                Peer::Client
+            } else {
+                Peer::Server
+            };
+            dump.feed(peer, &segment.data);
+        }
+        dump.finish();
+    }
+
+    if !found {
+        return Err("no HTTP/2 connection found".into());
+    }
+    Ok(())
+}
+
+fn dump_streams(files: &[Vec<u8>]) {
+    let mut dump = Connection::new();
+
+    let mut streams = files
+        .iter()
+        .map(|data| {
+            let is_client = data.starts_with(PREFACE)
+                || data.starts_with(b"GET ")
+                || data.starts_with(b"POST ")
+                || data.starts_with(b"OPTIONS ");
+            let peer = if is_client {
+                Peer::Client
+            } else {
+                Peer::Server
+            };
+            (peer, data)
+        })
+        .collect::<Vec<_>>();
+
+    // Decode the client first, so that the fingerprint and windows start
+    // from its settings.
+    streams.sort_by_key(|&(peer, _)| peer == Peer::Server);
+
+    for (peer, data) in streams {
+        dump.feed(peer, data);
+    }
+    dump.finish();
+}
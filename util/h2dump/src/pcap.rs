@@ -0,0 +1,409 @@
+//! Reads the TCP connections of a classic libpcap capture.
+
+use std::convert::TryInto;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LOOP: u32 = 108;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const TCP_SYN: u8 = 0x02;
+const TCP_ACK: u8 = 0x10;
+
+/// A TCP connection, with the payload of each direction reassembled.
+#[derive(Debug)]
+pub struct Connection {
+    pub client: SocketAddr,
+    pub server: SocketAddr,
+    /// The reassembled payloads, in capture order.
+    pub segments: Vec<Segment>,
+    flows: [Flow; 2],
+}
+
+/// In-order payload sent by one side of a connection.
+#[derive(Debug)]
+pub struct Segment {
+    pub from_client: bool,
+    pub data: Vec<u8>,
+}
+
+/// Reassembly state of one direction of a connection.
+#[derive(Debug, Default)]
+struct Flow {
+    /// Sequence number of the next byte expected.
+    next_seq: Option<u32>,
+    /// Segments received ahead of `next_seq`.
+    out_of_order: Vec<(u32, Vec<u8>)>,
+}
+
+#[derive(Debug)]
+struct Packet<'a> {
+    src: SocketAddr,
+    dst: SocketAddr,
+    seq: u32,
+    flags: u8,
+    payload: &'a [u8],
+}
+
+/// Returns whether `data` starts like a libpcap or pcapng capture.
+pub fn is_capture(data: &[u8]) -> bool {
+    data.len() >= 4 && (byte_order(data).is_some() || data[..4] == [0x0a, 0x0d, 0x0d, 0x0a])
+}
+
+/// Parses a capture into the TCP connections it contains.
+pub fn parse(data: &[u8]) -> Result<Vec<Connection>, String> {
+    if data.len() >= 4 && data[..4] == [0x0a, 0x0d, 0x0d, 0x0a] {
+        return Err("pcapng captures are not supported; convert with `editcap -F pcap`".into());
+    }
+
+    let big_endian = byte_order(data).ok_or("not a pcap capture")?;
+    if data.len() < 24 {
+        return Err("truncated pcap header".into());
+    }
+
+    let read_u32 = |buf: &[u8]| {
+        let bytes = [buf[0], buf[1], buf[2], buf[3]];
+        if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    };
+
+    let linktype = read_u32(&data[20..]) & 0x0fff_ffff;
+    let mut connections: Vec<Connection> = Vec::new();
+    let mut rest = &data[24..];
+
+    while rest.len() >= 16 {
+        let len = read_u32(&rest[8..]) as usize;
+        if rest.len() < 16 + len {
+            eprintln!("warning: truncated pcap record");
+            break;
+        }
+
+        let record = &rest[16..16 + len];
+        rest = &rest[16 + len..];
+
+        let packet = match strip_link(linktype, record)?.and_then(parse_ip) {
+            Some(packet) => packet,
+            None => continue,
+        };
+
+        let conn = match connections.iter_mut().position(|conn| conn.is_for(&packet)) {
+            Some(i) => &mut connections[i],
+            None => {
+                // The sender of a SYN is the client. Otherwise, the sides are
+                // guessed and fixed once the connection preface is found.
+                let (client, server) = if packet.flags & (TCP_SYN | TCP_ACK) == TCP_SYN
+                    || packet.flags & TCP_SYN == 0
+                {
+                    (packet.src, packet.dst)
+                } else {
+                    (packet.dst, packet.src)
+                };
+
+                connections.push(Connection {
+                    client,
+                    server,
+                    segments: Vec::new(),
+                    flows: Default::default(),
+                });
+                connections.last_mut().unwrap()
+            }
+        };
+
+        conn.recv(&packet);
+    }
+
+    Ok(connections)
+}
+
+fn byte_order(data: &[u8]) -> Option<bool> {
+    match data.get(..4)? {
+        [0xd4, 0xc3, 0xb2, 0xa1] | [0x4d, 0x3c, 0xb2, 0xa1] => Some(false),
+        [0xa1, 0xb2, 0xc3, 0xd4] | [0xa1, 0xb2, 0x3c, 0x4d] => Some(true),
+        _ => None,
+    }
+}
+
+/// Returns the IP packet of a link layer frame, or `None` if the frame does
+/// not carry IP.
+fn strip_link(linktype: u32, record: &[u8]) -> Result<Option<&[u8]>, String> {
+    let offset = match linktype {
+        LINKTYPE_NULL | LINKTYPE_LOOP => 4,
+        LINKTYPE_RAW | 12 | 14 => 0,
+        LINKTYPE_LINUX_SLL => 16,
+        LINKTYPE_LINUX_SLL2 => 20,
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            loop {
+                let ethertype = match record.get(offset..offset + 2) {
+                    Some(ty) => u16::from_be_bytes([ty[0], ty[1]]),
+                    None => return Ok(None),
+                };
+                match ethertype {
+                    // 802.1Q and 802.1ad tags
+                    0x8100 | 0x88a8 => offset += 4,
+                    0x0800 | 0x86dd => break offset + 2,
+                    _ => return Ok(None),
+                }
+            }
+        }
+        _ => return Err(format!("unsupported link type {}", linktype)),
+    };
+
+    Ok(record.get(offset..))
+}
+
+/// Parses an IPv4 or IPv6 packet carrying a TCP segment.
+fn parse_ip(ip: &[u8]) -> Option<Packet<'_>> {
+    let (src, dst, tcp) = match ip.first()? >> 4 {
+        4 => {
+            let header_len = (ip[0] & 0x0f) as usize * 4;
+            let total_len = u16::from_be_bytes([*ip.get(2)?, *ip.get(3)?]) as usize;
+            let is_fragment = u16::from_be_bytes([*ip.get(6)?, *ip.get(7)?]) & 0x3fff != 0;
+            if *ip.get(9)? != 6 || is_fragment {
+                return None;
+            }
+
+            let src: [u8; 4] = ip.get(12..16)?.try_into().ok()?;
+            let dst: [u8; 4] = ip.get(16..20)?.try_into().ok()?;
+            let tcp = ip.get(header_len..total_len.min(ip.len()))?;
+            (
+                IpAddr::V4(Ipv4Addr::from(src)),
+                IpAddr::V4(Ipv4Addr::from(dst)),
+                tcp,
+            )
+        }
+        6 => {
+            let payload_len = u16::from_be_bytes([*ip.get(4)?, *ip.get(5)?]) as usize;
+            if *ip.get(6)? != 6 {
+                return None;
+            }
+
+            let src: [u8; 16] = ip.get(8..24)?.try_into().ok()?;
+            let dst: [u8; 16] = ip.get(24..40)?.try_into().ok()?;
+            let tcp = ip.get(40..(40 + payload_len).min(ip.len()))?;
+            (
+                IpAddr::V6(Ipv6Addr::from(src)),
+                IpAddr::V6(Ipv6Addr::from(dst)),
+                tcp,
+            )
+        }
+        _ => return None,
+    };
+
+    let src_port = u16::from_be_bytes([*tcp.first()?, *tcp.get(1)?]);
+    let dst_port = u16::from_be_bytes([*tcp.get(2)?, *tcp.get(3)?]);
+    let seq = u32::from_be_bytes(tcp.get(4..8)?.try_into().ok()?);
+    let data_offset = (*tcp.get(12)? >> 4) as usize * 4;
+    let flags = *tcp.get(13)?;
+
+    Some(Packet {
+        src: SocketAddr::new(src, src_port),
+        dst: SocketAddr::new(dst, dst_port),
+        seq,
+        flags,
+        payload: tcp.get(data_offset..)?,
+    })
+}
+
+// ===== impl Connection =====
+
+impl Connection {
+    fn is_for(&self, packet: &Packet<'_>) -> bool {
+        (packet.src == self.client && packet.dst == self.server)
+            || (packet.src == self.server && packet.dst == self.client)
+    }
+
+    fn recv(&mut self, packet: &Packet<'_>) {
+        let from_client = packet.src == self.client;
+        let flow = &mut self.flows[from_client as usize];
+        let segments = &mut self.segments;
+
+        if packet.flags & TCP_SYN == TCP_SYN {
+            flow.next_seq = Some(packet.seq.wrapping_add(1));
+            return;
+        }
+
+        if packet.payload.is_empty() {
+            return;
+        }
+
+        flow.recv(packet.seq, packet.payload, &mut |data| {
+            segments.push(Segment {
+                from_client,
+                data: data.to_vec(),
+            })
+        });
+    }
+
+    /// Returns whether the bytes sent by one side contain `needle`.
+    pub fn sent(&self, from_client: bool, needle: &[u8]) -> bool {
+        let data = self
+            .segments
+            .iter()
+            .filter(|segment| segment.from_client == from_client)
+            .flat_map(|segment| segment.data.iter().copied())
+            .collect::<Vec<_>>();
+
+        data.windows(needle.len()).any(|window| window == needle)
+    }
+
+    /// Swaps the client and the server.
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.client, &mut self.server);
+        for segment in &mut self.segments {
+            segment.from_client = !segment.from_client;
+        }
+    }
+}
+
+// ===== impl Flow =====
+
+impl Flow {
+    fn recv(&mut self, seq: u32, payload: &[u8], emit: &mut dyn FnMut(&[u8])) {
+        let next_seq = *self.next_seq.get_or_insert(seq);
+
+        // Positive if the segment starts after the next expected byte.
+        let ahead = seq.wrapping_sub(next_seq) as i32;
+        if ahead > 0 {
+            self.out_of_order.push((seq, payload.to_vec()));
+            return;
+        }
+
+        // Skip the bytes already received, for retransmissions.
+        let skip = ahead.unsigned_abs() as usize;
+        if skip >= payload.len() {
+            return;
+        }
+
+        emit(&payload[skip..]);
+        self.next_seq = Some(seq.wrapping_add(payload.len() as u32));
+
+        // Segments received ahead may now be in order.
+        while let Some(i) = self
+            .out_of_order
+            .iter()
+            .position(|&(seq, _)| seq.wrapping_sub(self.next_seq.unwrap()) as i32 <= 0)
+        {
+            let (seq, payload) = self.out_of_order.swap_remove(i);
+            self.recv(seq, &payload, emit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CLIENT: [u8; 4] = [10, 0, 0, 1];
+    const SERVER: [u8; 4] = [10, 0, 0, 2];
+
+    /// Returns an IPv4 packet carrying a TCP segment.
+    fn packet(src: [u8; 4], dst: [u8; 4], seq: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let (src_port, dst_port) = if src == CLIENT {
+            (50000u16, 443u16)
+        } else {
+            (443, 50000)
+        };
+
+        let mut ip = vec![0x45, 0];
+        ip.extend_from_slice(&((40 + payload.len()) as u16).to_be_bytes());
+        ip.extend_from_slice(&[0, 0, 0x40, 0, 64, 6, 0, 0]);
+        ip.extend_from_slice(&src);
+        ip.extend_from_slice(&dst);
+        ip.extend_from_slice(&src_port.to_be_bytes());
+        ip.extend_from_slice(&dst_port.to_be_bytes());
+        ip.extend_from_slice(&seq.to_be_bytes());
+        ip.extend_from_slice(&[0, 0, 0, 0, 0x50, flags, 0xff, 0xff, 0, 0, 0, 0]);
+        ip.extend_from_slice(payload);
+        ip
+    }
+
+    /// Returns a little endian capture of raw IP packets.
+    fn capture(packets: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = vec![0xd4, 0xc3, 0xb2, 0xa1, 2, 0, 4, 0];
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&65535u32.to_le_bytes());
+        data.extend_from_slice(&LINKTYPE_RAW.to_le_bytes());
+        for packet in packets {
+            data.extend_from_slice(&[0; 8]);
+            data.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+            data.extend_from_slice(packet);
+        }
+        data
+    }
+
+    fn collect(flow: &mut Flow, seq: u32, payload: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        flow.recv(seq, payload, &mut |bytes| data.extend_from_slice(bytes));
+        data
+    }
+
+    #[test]
+    fn flow_reorders_segments() {
+        let mut flow = Flow::default();
+        assert_eq!(collect(&mut flow, 100, b"ab"), b"ab");
+        assert!(collect(&mut flow, 104, b"ef").is_empty());
+        assert!(collect(&mut flow, 106, b"gh").is_empty());
+        assert_eq!(collect(&mut flow, 102, b"cd"), b"cdefgh");
+        assert!(flow.out_of_order.is_empty());
+    }
+
+    #[test]
+    fn flow_skips_retransmitted_bytes() {
+        let mut flow = Flow::default();
+        assert_eq!(collect(&mut flow, 100, b"abcd"), b"abcd");
+        assert!(collect(&mut flow, 100, b"abcd").is_empty());
+        assert_eq!(collect(&mut flow, 102, b"cdef"), b"ef");
+    }
+
+    #[test]
+    fn flow_wraps_sequence_numbers() {
+        let mut flow = Flow::default();
+        assert_eq!(collect(&mut flow, u32::MAX - 1, b"ab"), b"ab");
+        assert!(collect(&mut flow, 2, b"ef").is_empty());
+        assert_eq!(collect(&mut flow, 0, b"cd"), b"cdef");
+    }
+
+    #[test]
+    fn parse_reassembles_connection() {
+        let data = capture(&[
+            packet(CLIENT, SERVER, 998, TCP_SYN, b""),
+            packet(SERVER, CLIENT, 5000, TCP_SYN | TCP_ACK, b""),
+            packet(CLIENT, SERVER, 1002, TCP_ACK, b"lo"),
+            packet(CLIENT, SERVER, 999, TCP_ACK, b"hel"),
+            packet(CLIENT, SERVER, 1000, TCP_ACK, b"el"),
+            packet(SERVER, CLIENT, 5001, TCP_ACK, b"world"),
+        ]);
+
+        let connections = parse(&data).unwrap();
+        assert_eq!(connections.len(), 1);
+
+        let conn = &connections[0];
+        assert_eq!(conn.client, "10.0.0.1:50000".parse().unwrap());
+        assert_eq!(conn.server, "10.0.0.2:443".parse().unwrap());
+
+        let segments = conn
+            .segments
+            .iter()
+            .map(|segment| (segment.from_client, &segment.data[..]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            segments,
+            [(true, &b"hel"[..]), (true, b"lo"), (false, b"world")]
+        );
+        assert!(conn.sent(true, b"hello"));
+    }
+
+    #[test]
+    fn parse_rejects_pcapng() {
+        let err = parse(&[0x0a, 0x0d, 0x0d, 0x0a, 0, 0, 0, 0]).unwrap_err();
+        assert!(err.contains("pcapng"), "{}", err);
+    }
+}